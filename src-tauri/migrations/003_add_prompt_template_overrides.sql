@@ -0,0 +1,13 @@
+-- User overrides for the built-in LLM prompt templates
+-- The built-in templates live in src/llm/prompts/ and are identified by a
+-- snake_case template id (e.g. 'dream_analysis'). A row here replaces the
+-- default text for that template; deleting the row resets it to the default.
+--
+-- ASSUMES: prompt_template_overrides table does NOT exist
+
+CREATE TABLE IF NOT EXISTS prompt_template_overrides (
+    template_id TEXT PRIMARY KEY,
+    template_text TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,19 @@
+-- Record which provider, model, prompt version and temperature produced each
+-- generated artifact, so outdated analyses can be found and regenerated
+--
+-- ASSUMES: dream_analyses and dream_creative_prompts tables exist from migration 001
+-- ASSUMES: provenance columns do NOT exist
+--
+-- All columns allow NULL: rows generated before this migration have unknown provenance.
+-- prompt_version looks like 'dream_analysis@v1' for built-in templates and
+-- 'dream_analysis@custom-<hash>' for user overrides.
+
+ALTER TABLE dream_analyses ADD COLUMN provider TEXT;
+ALTER TABLE dream_analyses ADD COLUMN model TEXT;
+ALTER TABLE dream_analyses ADD COLUMN prompt_version TEXT;
+ALTER TABLE dream_analyses ADD COLUMN temperature REAL;
+
+ALTER TABLE dream_creative_prompts ADD COLUMN provider TEXT;
+ALTER TABLE dream_creative_prompts ADD COLUMN model TEXT;
+ALTER TABLE dream_creative_prompts ADD COLUMN prompt_version TEXT;
+ALTER TABLE dream_creative_prompts ADD COLUMN temperature REAL;
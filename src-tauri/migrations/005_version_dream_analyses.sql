@@ -0,0 +1,59 @@
+-- Keep every generated analysis of a dream instead of one per dream
+-- Trying a different model or prompt now adds a new version rather than losing the old one.
+--
+-- ASSUMES: dream_analyses exists with the provenance columns from migration 004
+--
+-- is_current marks the version shown for the dream (exactly one per dream).
+-- is_pinned marks a version the user prefers; a pinned version stays current
+-- when new versions are generated.
+--
+-- SQLite cannot drop a UNIQUE constraint, so the table is rebuilt following
+-- SQLite's table rebuild procedure. Foreign keys are off during the rebuild so
+-- dropping the old table does not cascade into dream_analysis_cards and
+-- dream_creative_prompts; row ids are kept so those rows stay attached.
+-- Existing analyses become the current version of their dream.
+
+PRAGMA foreign_keys = OFF;
+
+BEGIN;
+
+CREATE TABLE dream_analyses_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    dream_id INTEGER NOT NULL,
+    themes_patterns TEXT NOT NULL,
+    emotional_analysis TEXT NOT NULL,
+    narrative_summary TEXT NOT NULL,
+    provider TEXT,
+    model TEXT,
+    prompt_version TEXT,
+    temperature REAL,
+    is_current INTEGER NOT NULL DEFAULT 0,
+    is_pinned INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (dream_id) REFERENCES dreams(id) ON DELETE CASCADE
+);
+
+INSERT INTO dream_analyses_new (
+    id, dream_id, themes_patterns, emotional_analysis, narrative_summary,
+    provider, model, prompt_version, temperature, is_current, is_pinned,
+    created_at, updated_at
+)
+SELECT
+    id, dream_id, themes_patterns, emotional_analysis, narrative_summary,
+    provider, model, prompt_version, temperature, 1, 0,
+    created_at, updated_at
+FROM dream_analyses;
+
+DROP TABLE dream_analyses;
+
+ALTER TABLE dream_analyses_new RENAME TO dream_analyses;
+
+CREATE INDEX IF NOT EXISTS idx_dream_analyses_dream_id ON dream_analyses(dream_id);
+CREATE INDEX IF NOT EXISTS idx_dream_analyses_current ON dream_analyses(dream_id, is_current);
+
+PRAGMA foreign_key_check;
+
+COMMIT;
+
+PRAGMA foreign_keys = ON;
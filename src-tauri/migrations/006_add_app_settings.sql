@@ -0,0 +1,12 @@
+-- Key/value store for settings owned by the backend
+-- Values are JSON documents keyed by a snake_case setting name (e.g. 'llm_config').
+-- Secrets such as API keys are NOT stored here; they live in the OS keyring
+-- or the encrypted secrets file (see src/secrets.rs).
+--
+-- ASSUMES: app_settings table does NOT exist
+
+CREATE TABLE IF NOT EXISTS app_settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,17 @@
+-- Token usage for every successful LLM provider call
+-- `operation` is the feature that made the call (title, chat, dream_analysis, ...).
+-- Costs are not stored; they are estimated from a price table when usage is queried.
+--
+-- ASSUMES: llm_usage table does NOT exist
+
+CREATE TABLE IF NOT EXISTS llm_usage (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_llm_usage_created_at ON llm_usage(created_at);
@@ -0,0 +1,18 @@
+-- Cached LLM responses for deterministic operations (titles, card commentary)
+-- cache_key is a hash of provider, model, operation and the rendered prompt.
+-- Entries expire after the configured TTL and the least recently used ones
+-- are evicted beyond the configured size limit. Caching is opt-in.
+--
+-- ASSUMES: llm_response_cache table does NOT exist
+
+CREATE TABLE IF NOT EXISTS llm_response_cache (
+    cache_key TEXT PRIMARY KEY,
+    operation TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    response TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    last_used_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_llm_response_cache_last_used_at ON llm_response_cache(last_used_at);
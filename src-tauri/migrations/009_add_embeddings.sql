@@ -0,0 +1,18 @@
+-- Embedding vectors for dreams, mind dumps and bugs, used by semantic search
+-- `entity_type` is 'dream', 'mind_dump' or 'bug'. `vector` holds little-endian f32s.
+-- `content_hash` identifies the text that was embedded, so edited entries are re-embedded.
+-- Vectors from different models are not comparable; search only uses the current model's.
+--
+-- ASSUMES: embeddings table does NOT exist
+
+CREATE TABLE IF NOT EXISTS embeddings (
+    entity_type TEXT NOT NULL,
+    entity_id INTEGER NOT NULL,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    dimensions INTEGER NOT NULL,
+    vector BLOB NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (entity_type, entity_id)
+);
@@ -0,0 +1,22 @@
+-- Weekly, monthly and custom-range reflections over dreams, mind dumps and bugs
+-- `period_type` is 'week', 'month' or 'custom'; the range is [start_date, end_date).
+-- `stats` is a JSON object (counts, top emotions and cards) computed when the digest was generated.
+-- Provenance columns match dream_analyses (migration 004).
+--
+-- ASSUMES: digests table does NOT exist
+
+CREATE TABLE IF NOT EXISTS digests (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    period_type TEXT NOT NULL,
+    start_date TEXT NOT NULL,
+    end_date TEXT NOT NULL,
+    stats TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    provider TEXT,
+    model TEXT,
+    prompt_version TEXT,
+    temperature REAL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_digests_start_date ON digests(start_date);
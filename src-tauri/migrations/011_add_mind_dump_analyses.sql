@@ -0,0 +1,35 @@
+-- LLM analyses of mind dumps: summary, worries, action items and suggested cards
+-- One analysis per mind dump; analysing again replaces it.
+-- `worries` and `action_items` are JSON arrays of strings.
+-- mind_dump_analysis_cards mirrors dream_analysis_cards.
+--
+-- ASSUMES: mind_dumps and cards tables exist from migration 001
+-- ASSUMES: mind_dump_analyses and mind_dump_analysis_cards tables do NOT exist
+
+CREATE TABLE IF NOT EXISTS mind_dump_analyses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    mind_dump_id INTEGER NOT NULL UNIQUE,
+    summary TEXT NOT NULL,
+    worries TEXT NOT NULL,
+    action_items TEXT NOT NULL,
+    provider TEXT,
+    model TEXT,
+    prompt_version TEXT,
+    temperature REAL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (mind_dump_id) REFERENCES mind_dumps(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS mind_dump_analysis_cards (
+    mind_dump_analysis_id INTEGER NOT NULL,
+    card_id INTEGER NOT NULL,
+    relevance_note TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (mind_dump_analysis_id, card_id),
+    FOREIGN KEY (mind_dump_analysis_id) REFERENCES mind_dump_analyses(id) ON DELETE CASCADE,
+    FOREIGN KEY (card_id) REFERENCES cards(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_mind_dump_analysis_cards_analysis_id ON mind_dump_analysis_cards(mind_dump_analysis_id);
+CREATE INDEX IF NOT EXISTS idx_mind_dump_analysis_cards_card_id ON mind_dump_analysis_cards(card_id);
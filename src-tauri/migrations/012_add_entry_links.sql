@@ -0,0 +1,19 @@
+-- Links between journal entries (dreams, mind dumps and bugs)
+-- `source_type` / `target_type` are 'dream', 'mind_dump' or 'bug'.
+-- `relation` reads source -> target, e.g. a bug 'promoted_from' the dream it was created from.
+--
+-- ASSUMES: entry_links table does NOT exist
+
+CREATE TABLE IF NOT EXISTS entry_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_type TEXT NOT NULL,
+    source_id INTEGER NOT NULL,
+    target_type TEXT NOT NULL,
+    target_id INTEGER NOT NULL,
+    relation TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (source_type, source_id, target_type, target_id, relation)
+);
+
+CREATE INDEX IF NOT EXISTS idx_entry_links_source ON entry_links(source_type, source_id);
+CREATE INDEX IF NOT EXISTS idx_entry_links_target ON entry_links(target_type, target_id);
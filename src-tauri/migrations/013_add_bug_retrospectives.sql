@@ -0,0 +1,23 @@
+-- LLM retrospectives of bugs: root pattern, blocker pattern, what helped and follow-ups
+-- One retrospective per bug; generating again replaces it.
+-- `blocker_pattern` is an id from the discovery chat taxonomy (e.g. 'circular_reasoning').
+-- `what_helped` and `follow_ups` are JSON arrays of strings.
+--
+-- ASSUMES: bugs table exists from migration 001
+-- ASSUMES: bug_retrospectives table does NOT exist
+
+CREATE TABLE IF NOT EXISTS bug_retrospectives (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    bug_id INTEGER NOT NULL UNIQUE,
+    root_pattern TEXT NOT NULL,
+    blocker_pattern TEXT NOT NULL,
+    what_helped TEXT NOT NULL,
+    follow_ups TEXT NOT NULL,
+    provider TEXT,
+    model TEXT,
+    prompt_version TEXT,
+    temperature REAL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (bug_id) REFERENCES bugs(id) ON DELETE CASCADE
+);
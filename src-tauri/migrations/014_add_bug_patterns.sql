@@ -0,0 +1,22 @@
+-- Blocker patterns detected in each bug, with the model's confidence
+-- `pattern` is an id from the discovery chat taxonomy (e.g. 'circular_reasoning').
+-- Classifying a bug again replaces all of its rows.
+--
+-- ASSUMES: bugs table exists from migration 001
+-- ASSUMES: bug_patterns table does NOT exist
+
+CREATE TABLE IF NOT EXISTS bug_patterns (
+    bug_id INTEGER NOT NULL,
+    pattern TEXT NOT NULL,
+    confidence REAL NOT NULL,
+    evidence TEXT,
+    provider TEXT,
+    model TEXT,
+    prompt_version TEXT,
+    temperature REAL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (bug_id, pattern),
+    FOREIGN KEY (bug_id) REFERENCES bugs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_bug_patterns_pattern ON bug_patterns(pattern);
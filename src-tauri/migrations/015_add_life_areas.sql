@@ -0,0 +1,28 @@
+-- Life areas a bug can belong to: the built-in areas of the card deck plus custom ones
+-- `key` is the stable identifier used in cards.json `life_area_insights` for built-in areas
+-- and a slug of the name for custom areas.
+-- Built-in areas (is_builtin = 1) cannot be deleted.
+--
+-- ASSUMES: bugs table exists from migration 001
+-- ASSUMES: life_areas table does NOT exist
+-- ASSUMES: bugs.life_area_id column does NOT exist
+
+CREATE TABLE IF NOT EXISTS life_areas (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    key TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    description TEXT,
+    is_builtin INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL
+);
+
+INSERT OR IGNORE INTO life_areas (key, name, description, is_builtin, created_at) VALUES
+    ('creative', 'Creative', 'Creative projects, art and self-expression', 1, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')),
+    ('work', 'Work', 'Work, career and professional life', 1, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')),
+    ('life', 'Life', 'Everyday life, health and home', 1, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')),
+    ('relationship', 'Relationship', 'Partners, family and friends', 1, strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now'));
+
+-- Nullable: bugs created before life areas were stored have none
+ALTER TABLE bugs ADD COLUMN life_area_id INTEGER REFERENCES life_areas(id);
+
+CREATE INDEX IF NOT EXISTS idx_bugs_life_area_id ON bugs(life_area_id);
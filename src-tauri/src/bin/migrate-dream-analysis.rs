@@ -1,24 +1,29 @@
-/// Dream Analysis Migration Script
-///
-/// This script generates dream analysis for all dreams that don't have one yet.
-/// It reads LLM configuration from environment variables.
-///
-/// Usage:
-///   # Set environment variables for your LLM provider
-///   export LLM_PROVIDER=ollama  # or openai, anthropic
-///   export OLLAMA_URL=http://localhost:11434
-///   export OLLAMA_MODEL=llama3.2
-///
-///   # Run the script
-///   cargo run --bin migrate-dream-analysis
-///
-///   # Or with options
-///   cargo run --bin migrate-dream-analysis -- --limit 5
-///   cargo run --bin migrate-dream-analysis -- --dry-run
+//! Dream Analysis Migration Script
+//!
+//! This script generates dream analysis for all dreams that don't have one yet.
+//! It reads LLM configuration from environment variables.
+//!
+//! Usage:
+//!   # Set environment variables for your LLM provider
+//!   export LLM_PROVIDER=ollama  # or openai, anthropic
+//!   export OLLAMA_URL=http://localhost:11434
+//!   export OLLAMA_MODEL=llama3.2
+//!
+//!   # Run the script
+//!   cargo run --bin migrate-dream-analysis
+//!
+//!   # Or with options
+//!   cargo run --bin migrate-dream-analysis -- --limit 5
+//!   cargo run --bin migrate-dream-analysis -- --dry-run
+//!   cargo run --bin migrate-dream-analysis -- --outdated
 
 use std::env;
-use lmnl_app_lib::db::{Database, models::CreateDreamAnalysisInput};
-use lmnl_app_lib::llm::{LLMConfig, LLMProvider};
+use lmnl_app_lib::db::{Database, models::{AnalysisCardInput, CreateDreamAnalysisInput}};
+use lmnl_app_lib::llm::{client, LLMConfig, LLMProvider, SymbolCard};
+use lmnl_app_lib::llm::http::LlmHttp;
+use lmnl_app_lib::llm::profile;
+use lmnl_app_lib::llm::usage;
+use lmnl_app_lib::llm::templates::{PromptRegistry, PromptTemplateId};
 
 fn print_usage() {
     println!(
@@ -33,6 +38,8 @@ Usage:
 Options:
   --dry-run         Show which dreams would be analyzed without actually running
   --limit N         Only process N dreams (useful for testing)
+  --outdated        Also regenerate analyses made with a different provider,
+                    model or prompt version than the current configuration
   --help            Show this help message
 
 Environment Variables (LLM Configuration):
@@ -61,6 +68,11 @@ Examples:
   export OPENAI_API_KEY=sk-...
   cargo run --bin migrate-dream-analysis -- --limit 5
 
+  # Regenerate analyses made by an older model or prompt
+  export LLM_PROVIDER=ollama
+  export OLLAMA_MODEL=llama3.2
+  cargo run --bin migrate-dream-analysis -- --outdated
+
   # Process all dreams with Anthropic
   export LLM_PROVIDER=anthropic
   export ANTHROPIC_API_KEY=sk-ant-...
@@ -69,17 +81,35 @@ Examples:
     );
 }
 
+/// The stored cards for an analysis's symbols, and the names that matched no card
+fn resolve_symbol_cards(db: &Database, symbol_cards: &[SymbolCard]) -> rusqlite::Result<(Vec<AnalysisCardInput>, Vec<String>)> {
+    let mut cards = Vec::new();
+    let mut unresolved = Vec::new();
+    for symbol in symbol_cards {
+        match db.get_card_by_name(&symbol.card_name)?.and_then(|card| card.id) {
+            Some(card_id) => cards.push(AnalysisCardInput {
+                card_id,
+                relevance_note: Some(symbol.relevance_note.clone()),
+            }),
+            None => unresolved.push(symbol.card_name.clone()),
+        }
+    }
+    Ok((cards, unresolved))
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
     let mut dry_run = false;
     let mut limit: Option<usize> = None;
+    let mut include_outdated = false;
 
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
             "--dry-run" => dry_run = true,
+            "--outdated" => include_outdated = true,
             "--limit" => {
                 if i + 1 >= args.len() {
                     eprintln!("Error: --limit requires a number argument");
@@ -131,6 +161,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 openai_model: String::new(),
                 anthropic_api_key: String::new(),
                 anthropic_model: String::new(),
+                ..Default::default()
             })
         }
         "openai" => {
@@ -146,6 +177,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 openai_model: model,
                 anthropic_api_key: String::new(),
                 anthropic_model: String::new(),
+                ..Default::default()
             })
         }
         "anthropic" => {
@@ -162,6 +194,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 openai_model: String::new(),
                 anthropic_api_key: api_key,
                 anthropic_model: model,
+                ..Default::default()
             })
         }
         _ => {
@@ -177,9 +210,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let db = Database::new()?;
     println!("Database connected\n");
 
+    // Use the same prompt templates as the app, including user overrides and profile
+    let overrides = db
+        .list_prompt_template_overrides()?
+        .into_iter()
+        .filter_map(|o| PromptTemplateId::from_id(&o.template_id).map(|id| (id, o.template_text)))
+        .collect();
+    let prompts = PromptRegistry::new(overrides).with_profile(profile::load_profile(&db)?);
+    let http = LlmHttp::new();
+
+    // Provenance recorded on every analysis this run creates
+    let provenance = client::generation_provenance(
+        PromptTemplateId::DreamAnalysis,
+        client::DREAM_ANALYSIS_TEMPERATURE,
+        &prompts,
+        &config,
+    );
+
     // Get all dreams
     println!("Fetching all dreams...");
-    let dreams = db.list_dreams(None, None)?;
+    let dreams = db.list_all_dreams()?;
     println!("Found {} total dreams\n", dreams.len());
 
     // Find dreams without analysis
@@ -204,8 +254,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         dreams_needing_analysis.len()
     );
 
+    // Find dreams whose analysis was made with other settings
+    if include_outdated {
+        let outdated = db.list_outdated_dream_analyses(
+            provenance.provider.as_deref().unwrap_or_default(),
+            provenance.model.as_deref().unwrap_or_default(),
+            provenance.prompt_version.as_deref().unwrap_or_default(),
+        )?;
+        println!(
+            "Found {} outdated analyses (current: {} / {})\n",
+            outdated.len(),
+            provenance.model.as_deref().unwrap_or_default(),
+            provenance.prompt_version.as_deref().unwrap_or_default()
+        );
+
+        for analysis in outdated {
+            if let Some(dream) = dreams.iter().find(|d| d.id == Some(analysis.dream_id)) {
+                dreams_needing_analysis.push(dream.clone());
+            }
+        }
+    }
+
     if dreams_needing_analysis.is_empty() {
-        println!("All dreams already have analysis! Nothing to do.");
+        println!("All dreams already have up-to-date analysis! Nothing to do.");
         return Ok(());
     }
 
@@ -272,40 +343,46 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         println!("{} Analyzing dream #{}: \"{}\"", progress, dream_id, dream.title);
 
-        match lmnl_app_lib::llm::client::generate_dream_analysis(
+        let analysis = lmnl_app_lib::llm::client::generate_dream_analysis(
             &dream.title,
             &dream.content,
             dream.sleep_quality,
+            None,
+            &prompts,
+            &http,
             &config,
         )
-        .await
-        {
+        .await;
+        // Record the tokens spent so the backfill shows up in usage reports
+        usage::flush(&db, &http);
+
+        match analysis {
             Ok(analysis_response) => {
-                println!("  Saving analysis to database...");
-                // Save to database using Database methods
-                match db.create_dream_analysis(CreateDreamAnalysisInput {
-                    dream_id,
-                    themes_patterns: analysis_response.themes_patterns.clone(),
-                    emotional_analysis: analysis_response.emotional_analysis.clone(),
-                    narrative_summary: analysis_response.narrative_summary.clone(),
-                }) {
-                    Ok(analysis) => {
-                        println!("  Analysis saved. Linking {} symbol cards...", analysis_response.symbol_cards.len());
-                        // Link cards
-                        for symbol in &analysis_response.symbol_cards {
-                            println!("    Looking up card: {}", symbol.card_name);
-                            if let Ok(Some(card)) = db.get_card_by_name(&symbol.card_name) {
-                                println!("    Linking card {} to analysis", symbol.card_name);
-                                let _ = db.link_card_to_dream_analysis(
-                                    analysis.id.unwrap_or(0),
-                                    card.id.unwrap_or(0),
-                                    Some(symbol.relevance_note.clone()),
-                                );
-                            } else {
-                                println!("    Card not found: {}", symbol.card_name);
-                            }
-                        }
+                let (cards, unresolved) = match resolve_symbol_cards(&db, &analysis_response.symbol_cards) {
+                    Ok(resolved) => resolved,
+                    Err(e) => {
+                        error_count += 1;
+                        eprintln!("{} ✗ Failed to look up symbol cards: {}\n", progress, e);
+                        continue;
+                    }
+                };
+                if !unresolved.is_empty() {
+                    eprintln!("  Warning: no card named {}; saving without them", unresolved.join(", "));
+                }
 
+                println!("  Saving analysis with {} symbol cards...", cards.len());
+                // Save as a new version together with its cards; earlier versions are kept
+                match db.create_dream_analysis_with_cards(
+                    CreateDreamAnalysisInput {
+                        dream_id,
+                        themes_patterns: analysis_response.themes_patterns.clone(),
+                        emotional_analysis: analysis_response.emotional_analysis.clone(),
+                        narrative_summary: analysis_response.narrative_summary.clone(),
+                        provenance: provenance.clone(),
+                    },
+                    &cards,
+                ) {
+                    Ok(_) => {
                         success_count += 1;
                         println!("{} ✓ Success\n", progress);
                     }
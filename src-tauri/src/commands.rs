@@ -1,26 +1,148 @@
 use crate::db::{models::*, Database};
+use crate::secrets::Secrets;
 use crate::llm::{
-    client, GenerateTitleRequest, GenerateTitleResponse, OptimizeDescriptionRequest,
+    client, discovery, AvailableModel, ConnectionTestResult, LLMConfig, LLMProvider, LlmSettingsView, LlmError, GenerateTitleRequest, GenerateTitleResponse, OptimizeDescriptionRequest,
     OptimizeDescriptionResponse, CardCommentaryResponse, GenerateDreamAnalysisRequest,
-    GenerateCreativePromptsRequest,
+    GenerateCreativePromptsRequest, AskJournalResponse, SymbolCard,
 };
-use tauri::State;
+use crate::llm::http::LlmHttp;
+use crate::llm::settings as llm_settings;
+use crate::llm::profile::{self, UserProfile};
+use crate::llm::cache::ResponseCache;
+use crate::llm::embeddings::{self, JournalEntry};
+use crate::llm::similar_dreams;
+use crate::llm::journal_chat;
+use crate::llm::dream_history;
+use crate::llm::digest;
+use crate::llm::bug_from_entry;
+use crate::llm::bug_retrospective;
+use crate::llm::bug_patterns;
+use crate::llm::usage::{self, UsageRecorder};
+use crate::llm::templates::{self, PromptRegistry, PromptTemplateId, PromptTemplateInfo};
+use tauri::{AppHandle, Manager, State};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Build the prompt registry from the built-in templates, the user's overrides and their profile
+fn load_prompt_registry(db: &Database) -> Result<PromptRegistry, String> {
+    let overrides = db
+        .list_prompt_template_overrides()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|o| PromptTemplateId::from_id(&o.template_id).map(|id| (id, o.template_text)))
+        .collect();
+
+    Ok(PromptRegistry::new(overrides).with_profile(profile::load_profile(db)?))
+}
+
+/// The config passed with a request (e.g. unsaved settings), or the stored one
+fn load_llm_config(db: &Database, secrets: &Secrets, config: Option<LLMConfig>) -> Result<LLMConfig, String> {
+    llm_settings::resolve_config(db, secrets, config)
+}
+
+/// `config` from a JSON request body, if the frontend sent one
+fn request_llm_config(request: &serde_json::Value) -> Result<Option<LLMConfig>, String> {
+    request
+        .get("config")
+        .filter(|config| !config.is_null())
+        .map(|config| serde_json::from_value(config.clone()).map_err(|e| format!("Invalid config: {}", e)))
+        .transpose()
+}
+
+/// Attach an entry's links in both directions for `get_*` responses
+fn with_links<T>(db: &Database, entry_type: EntryType, id: i64, entry: Option<T>) -> Result<Option<WithLinks<T>>, String> {
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+    let links = db.list_linked_entries(entry_type, id).map_err(|e| e.to_string())?;
+    Ok(Some(WithLinks { entry, links }))
+}
+
+/// The life area a commentary request is about: `life_area_id` when given,
+/// otherwise `life_area` matched against area keys and names
+fn resolve_life_area(db: &Database, request: &serde_json::Value) -> Result<LifeArea, String> {
+    if let Some(id) = request.get("life_area_id").and_then(|v| v.as_i64()) {
+        return db
+            .get_life_area(id)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Life area {} not found", id));
+    }
+    let key = request
+        .get("life_area")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing life_area")?;
+    db.get_life_area_by_key(key)
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Unknown life area '{}'", key))
+}
+
+/// Rejects a bug input naming a life area that does not exist
+fn check_life_area(db: &Database, id: Option<i64>) -> Result<(), String> {
+    match id {
+        Some(id) if db.get_life_area(id).map_err(|e| e.to_string())?.is_none() => {
+            Err(format!("Life area {} not found", id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The stored cards an analysis suggested, with their relevance notes
+fn resolve_symbol_cards(db: &Database, symbol_cards: &[SymbolCard]) -> Result<Vec<AnalysisCardInput>, String> {
+    symbol_cards
+        .iter()
+        .map(|symbol_card| {
+            let card = db.get_card_by_name(&symbol_card.card_name)
+                .map_err(|e| e.to_string())?
+                .ok_or(format!("Card '{}' not found", symbol_card.card_name))?;
+            Ok(AnalysisCardInput {
+                card_id: card.id.ok_or("Card ID not found")?,
+                relevance_note: Some(symbol_card.relevance_note.clone()),
+            })
+        })
+        .collect()
+}
+
+/// Re-embed a saved entry in the background when embeddings are enabled.
+/// Best effort: the save never waits on the provider, and `sync_embeddings` catches up later.
+fn refresh_embedding(app: &AppHandle, entry: Option<JournalEntry>) {
+    let Some(entry) = entry else {
+        return;
+    };
+    let app = app.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let db = app.state::<Database>();
+        let config = match load_llm_config(&db, &app.state::<Secrets>(), None) {
+            Ok(config) if embeddings::enabled(&config) => config,
+            _ => return,
+        };
+
+        let http = UsageRecorder::new(&db, &app.state::<LlmHttp>());
+        if let Err(e) = embeddings::index_entry(&db, &http, &config, &entry).await {
+            eprintln!("Failed to embed {} {}: {}", entry.entry_type.as_str(), entry.id, e);
+        }
+    });
+}
+
 #[tauri::command]
 pub fn create_dream(
+    app: AppHandle,
     db: State<Database>,
     input: CreateDreamInput,
 ) -> Result<Dream, String> {
-    db.create_dream(input).map_err(|e| e.to_string())
+    let dream = db.create_dream(input).map_err(|e| e.to_string())?;
+    refresh_embedding(&app, JournalEntry::from_dream(&dream));
+    Ok(dream)
 }
 
 #[tauri::command]
 pub fn get_dream(
     db: State<Database>,
     id: i64,
-) -> Result<Option<Dream>, String> {
-    db.get_dream(id).map_err(|e| e.to_string())
+) -> Result<Option<WithLinks<Dream>>, String> {
+    let dream = db.get_dream(id).map_err(|e| e.to_string())?;
+    with_links(&db, EntryType::Dream, id, dream)
 }
 
 #[tauri::command]
@@ -34,10 +156,13 @@ pub fn list_dreams(
 
 #[tauri::command]
 pub fn update_dream(
+    app: AppHandle,
     db: State<Database>,
     input: UpdateDreamInput,
 ) -> Result<Option<Dream>, String> {
-    db.update_dream(input).map_err(|e| e.to_string())
+    let dream = db.update_dream(input).map_err(|e| e.to_string())?;
+    refresh_embedding(&app, dream.as_ref().and_then(JournalEntry::from_dream));
+    Ok(dream)
 }
 
 #[tauri::command]
@@ -58,66 +183,173 @@ pub fn search_dreams(
 
 #[tauri::command]
 pub async fn generate_dream_title(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: GenerateTitleRequest,
-) -> Result<GenerateTitleResponse, String> {
-    let title = client::generate_title(&request.content, &request.config).await?;
+) -> Result<GenerateTitleResponse, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, request.config)?;
+    let prompts = load_prompt_registry(&db)?;
+    let title = client::generate_title(&request.content, &prompts, &http, &ResponseCache::new(&db, &config.cache), &config).await?;
     Ok(GenerateTitleResponse { title })
 }
 
 #[tauri::command]
 pub async fn optimize_dream_description(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: OptimizeDescriptionRequest,
-) -> Result<OptimizeDescriptionResponse, String> {
-    let optimized = client::optimize_description(&request.content, &request.config).await?;
+) -> Result<OptimizeDescriptionResponse, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, request.config)?;
+    let prompts = load_prompt_registry(&db)?;
+    let optimized = client::optimize_description(&request.content, &prompts, &http, &config).await?;
     Ok(OptimizeDescriptionResponse { optimized })
 }
 
 // Dream analysis commands
-#[tauri::command]
-pub async fn generate_dream_analysis(
-    db: State<'_, Database>,
-    request: GenerateDreamAnalysisRequest,
-) -> Result<DreamAnalysisWithCards, String> {
+
+/// Generate an analysis, store it as a new version and link its symbol cards.
+/// `with_history` adds the stored dream's metadata and related earlier dreams to the prompt.
+#[allow(clippy::too_many_arguments)]
+async fn create_dream_analysis_version(
+    db: &Database,
+    http: &LlmHttp,
+    dream_id: i64,
+    dream_title: &str,
+    dream_content: &str,
+    sleep_quality: Option<i32>,
+    with_history: bool,
+    config: &LLMConfig,
+) -> Result<DreamAnalysisWithCards, LlmError> {
+    let history = if with_history {
+        let dream = db.get_dream(dream_id)
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Dream {} not found", dream_id))?;
+        Some(dream_history::analysis_history(&dream, db, config)?)
+    } else {
+        None
+    };
+
     // Call LLM to generate analysis
+    let prompts = load_prompt_registry(db)?;
     let llm_response = client::generate_dream_analysis(
-        &request.dream_title,
-        &request.dream_content,
-        request.sleep_quality,
-        &request.config
+        dream_title,
+        dream_content,
+        sleep_quality,
+        history.as_deref(),
+        &prompts,
+        http,
+        config
     ).await?;
 
     // Create the analysis in the database
     let analysis_input = CreateDreamAnalysisInput {
-        dream_id: request.dream_id,
+        dream_id,
         themes_patterns: llm_response.themes_patterns.clone(),
         emotional_analysis: llm_response.emotional_analysis.clone(),
         narrative_summary: llm_response.narrative_summary.clone(),
+        provenance: client::generation_provenance(
+            PromptTemplateId::DreamAnalysis,
+            client::DREAM_ANALYSIS_TEMPERATURE,
+            &prompts,
+            config,
+        ),
     };
 
-    let analysis = db.create_dream_analysis(analysis_input)
+    // Look up every card first so a missing one fails before anything is stored
+    let cards = resolve_symbol_cards(db, &llm_response.symbol_cards)?;
+    let analysis = db.create_dream_analysis_with_cards(analysis_input, &cards)
         .map_err(|e| e.to_string())?;
 
     let analysis_id = analysis.id.ok_or("Analysis ID not found")?;
 
-    // Link the identified cards to the analysis
-    for symbol_card in &llm_response.symbol_cards {
-        // Get the card by name
-        let card = db.get_card_by_name(&symbol_card.card_name)
-            .map_err(|e| e.to_string())?
-            .ok_or(format!("Card '{}' not found", symbol_card.card_name))?;
-
-        // Link the card to the analysis
-        db.link_card_to_dream_analysis(
-            analysis_id,
-            card.id.ok_or("Card ID not found")?,
-            Some(symbol_card.relevance_note.clone())
-        ).map_err(|e| e.to_string())?;
-    }
+    // Return the new version with cards, even if a pinned version stays current
+    db.get_dream_analysis_version_with_cards(analysis_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| LlmError::Other("Failed to retrieve analysis with cards".to_string()))
+}
+
+#[tauri::command]
+pub async fn generate_dream_analysis(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    request: GenerateDreamAnalysisRequest,
+) -> Result<DreamAnalysisWithCards, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    create_dream_analysis_version(
+        &db,
+        &http,
+        request.dream_id,
+        &request.dream_title,
+        &request.dream_content,
+        request.sleep_quality,
+        request.with_history,
+        &load_llm_config(&db, &secrets, request.config)?,
+    ).await
+}
 
-    // Return the complete analysis with cards
-    db.get_dream_analysis_with_cards(analysis.dream_id)
+/// Analyse a stored dream again, keeping earlier versions
+#[tauri::command]
+pub async fn regenerate_dream_analysis(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    dream_id: i64,
+    with_history: Option<bool>,
+    config: Option<LLMConfig>,
+) -> Result<DreamAnalysisWithCards, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    let dream = db.get_dream(dream_id)
         .map_err(|e| e.to_string())?
-        .ok_or("Failed to retrieve analysis with cards".to_string())
+        .ok_or(format!("Dream {} not found", dream_id))?;
+
+    create_dream_analysis_version(
+        &db,
+        &http,
+        dream_id,
+        &dream.title,
+        &dream.content,
+        dream.sleep_quality,
+        with_history.unwrap_or(false),
+        &config,
+    ).await
+}
+
+#[tauri::command]
+pub fn list_dream_analysis_versions(
+    db: State<Database>,
+    dream_id: i64,
+) -> Result<Vec<DreamAnalysisWithCards>, String> {
+    db.list_dream_analysis_versions_with_cards(dream_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn pin_dream_analysis(
+    db: State<Database>,
+    analysis_id: i64,
+) -> Result<bool, String> {
+    db.pin_dream_analysis(analysis_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unpin_dream_analysis(
+    db: State<Database>,
+    dream_id: i64,
+) -> Result<bool, String> {
+    db.unpin_dream_analysis(dream_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_dream_analysis_version(
+    db: State<Database>,
+    analysis_id: i64,
+) -> Result<bool, String> {
+    db.delete_dream_analysis_version(analysis_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -128,18 +360,50 @@ pub fn get_dream_analysis_with_cards(
     db.get_dream_analysis_with_cards(dream_id).map_err(|e| e.to_string())
 }
 
+/// Analyses produced by a different provider, model or prompt version than the current settings
+#[tauri::command]
+pub fn find_outdated_dream_analyses(
+    db: State<Database>,
+    secrets: State<Secrets>,
+    config: Option<LLMConfig>,
+) -> Result<Vec<DreamAnalysis>, String> {
+    let config = load_llm_config(&db, &secrets, config)?;
+    let prompts = load_prompt_registry(&db)?;
+    let current = client::generation_provenance(
+        PromptTemplateId::DreamAnalysis,
+        client::DREAM_ANALYSIS_TEMPERATURE,
+        &prompts,
+        &config,
+    );
+    let model = current.model.ok_or("LLM is disabled")?;
+
+    db.list_outdated_dream_analyses(
+        config.provider.as_str(),
+        &model,
+        &current.prompt_version.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
+}
+
 // Dream creative prompts commands
 #[tauri::command]
 pub async fn generate_dream_creative_prompts(
     db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: GenerateCreativePromptsRequest,
-) -> Result<DreamCreativePrompts, String> {
+) -> Result<DreamCreativePrompts, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
     // Call LLM to generate creative prompts
+    let config = load_llm_config(&db, &secrets, request.config)?;
+    let prompts = load_prompt_registry(&db)?;
     let llm_response = client::generate_creative_prompts(
         &request.themes_patterns,
         &request.emotional_analysis,
         &request.narrative_summary,
-        &request.config
+        &prompts,
+        &http,
+        &config
     ).await?;
 
     // Convert arrays to JSON strings
@@ -156,10 +420,16 @@ pub async fn generate_dream_creative_prompts(
         image_prompts: image_prompts_json,
         music_prompts: music_prompts_json,
         story_prompts: story_prompts_json,
+        provenance: client::generation_provenance(
+            PromptTemplateId::CreativePrompts,
+            client::CREATIVE_PROMPTS_TEMPERATURE,
+            &prompts,
+            &config,
+        ),
     };
 
     db.create_dream_creative_prompts(prompts_input)
-        .map_err(|e| e.to_string())
+        .map_err(|e| LlmError::Other(e.to_string()))
 }
 
 #[tauri::command]
@@ -173,34 +443,44 @@ pub fn get_dream_creative_prompts(
 // Bug tracking commands
 #[tauri::command]
 pub fn create_bug(
+    app: AppHandle,
     db: State<Database>,
     input: CreateBugInput,
 ) -> Result<Bug, String> {
-    db.create_bug(input).map_err(|e| e.to_string())
+    check_life_area(&db, input.life_area_id)?;
+    let bug = db.create_bug(input).map_err(|e| e.to_string())?;
+    refresh_embedding(&app, JournalEntry::from_bug(&bug));
+    Ok(bug)
 }
 
 #[tauri::command]
 pub fn get_bug(
     db: State<Database>,
     id: i64,
-) -> Result<Option<Bug>, String> {
-    db.get_bug(id).map_err(|e| e.to_string())
+) -> Result<Option<WithLinks<Bug>>, String> {
+    let bug = db.get_bug(id).map_err(|e| e.to_string())?;
+    with_links(&db, EntryType::Bug, id, bug)
 }
 
 #[tauri::command]
 pub fn list_bugs(
     db: State<Database>,
     status: Option<String>,
+    life_area_id: Option<i64>,
 ) -> Result<Vec<Bug>, String> {
-    db.list_bugs(status).map_err(|e| e.to_string())
+    db.list_bugs(status, life_area_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn update_bug(
+    app: AppHandle,
     db: State<Database>,
     input: UpdateBugInput,
 ) -> Result<Option<Bug>, String> {
-    db.update_bug(input).map_err(|e| e.to_string())
+    check_life_area(&db, input.life_area_id)?;
+    let bug = db.update_bug(input).map_err(|e| e.to_string())?;
+    refresh_embedding(&app, bug.as_ref().and_then(JournalEntry::from_bug));
+    Ok(bug)
 }
 
 #[tauri::command]
@@ -214,24 +494,111 @@ pub fn delete_bug(
 // Bug LLM commands
 #[tauri::command]
 pub async fn optimize_bug_description(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: OptimizeDescriptionRequest,
-) -> Result<OptimizeDescriptionResponse, String> {
-    let optimized = client::optimize_description(&request.content, &request.config).await?;
+) -> Result<OptimizeDescriptionResponse, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, request.config)?;
+    let prompts = load_prompt_registry(&db)?;
+    let optimized = client::optimize_description(&request.content, &prompts, &http, &config).await?;
     Ok(OptimizeDescriptionResponse { optimized })
 }
 
 #[tauri::command]
 pub async fn generate_bug_title(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: GenerateTitleRequest,
-) -> Result<GenerateTitleResponse, String> {
-    let title = client::generate_title(&request.content, &request.config).await?;
+) -> Result<GenerateTitleResponse, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, request.config)?;
+    let prompts = load_prompt_registry(&db)?;
+    let title = client::generate_title(&request.content, &prompts, &http, &ResponseCache::new(&db, &config.cache), &config).await?;
     Ok(GenerateTitleResponse { title })
 }
 
+#[tauri::command]
+pub async fn create_bug_from_entry(
+    app: AppHandle,
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    entry_type: EntryType,
+    entry_id: i64,
+    draw_cards: Option<bool>,
+) -> Result<BugFromEntry, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, None)?;
+    let prompts = load_prompt_registry(&db)?;
+    let cache = ResponseCache::new(&db, &config.cache);
+    let created = bug_from_entry::create_bug_from_entry(entry_type, entry_id, draw_cards.unwrap_or(false), &db, &http, &cache, &prompts, &config).await?;
+    refresh_embedding(&app, JournalEntry::from_bug(&created.bug));
+    Ok(created)
+}
+
+#[tauri::command]
+pub async fn generate_bug_retrospective(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    bug_id: i64,
+    config: Option<LLMConfig>,
+) -> Result<BugRetrospective, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    let prompts = load_prompt_registry(&db)?;
+    bug_retrospective::generate_bug_retrospective(bug_id, &db, &http, &prompts, &config).await
+}
+
+#[tauri::command]
+pub fn get_bug_retrospective(
+    db: State<Database>,
+    bug_id: i64,
+) -> Result<Option<BugRetrospective>, String> {
+    db.get_bug_retrospective(bug_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn classify_bug_patterns(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    bug_id: i64,
+    config: Option<LLMConfig>,
+) -> Result<Vec<BugPattern>, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    let prompts = load_prompt_registry(&db)?;
+    bug_patterns::classify_bug_patterns(bug_id, &db, &http, &prompts, &config).await
+}
+
+#[tauri::command]
+pub fn get_bug_patterns(
+    db: State<Database>,
+    bug_id: i64,
+) -> Result<Vec<BugPattern>, String> {
+    db.get_bug_patterns(bug_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_pattern_frequencies(
+    db: State<Database>,
+    min_confidence: Option<f64>,
+) -> Result<Vec<PatternFrequency>, String> {
+    bug_patterns::pattern_frequencies(&db, min_confidence.unwrap_or(bug_patterns::DEFAULT_MIN_CONFIDENCE))
+}
+
 #[tauri::command]
 pub async fn comment_on_card(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: serde_json::Value,
-) -> Result<CardCommentaryResponse, String> {
+) -> Result<CardCommentaryResponse, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
     let card_name = request
         .get("card_name")
         .and_then(|v| v.as_str())
@@ -244,50 +611,52 @@ pub async fn comment_on_card(
         .get("card_meaning")
         .and_then(|v| v.as_str())
         .ok_or("Missing card_meaning")?;
-    let life_area = request
-        .get("life_area")
-        .and_then(|v| v.as_str())
-        .ok_or("Missing life_area")?;
+    let life_area = resolve_life_area(&db, &request)?;
     let empty_cards = vec![];
     let selected_cards = request
         .get("selected_cards")
         .and_then(|v| v.as_array())
         .unwrap_or(&empty_cards);
-    let config = serde_json::from_value(request.get("config").ok_or("Missing config")?.clone())
-        .map_err(|e| format!("Invalid config: {}", e))?;
+    let config = load_llm_config(&db, &secrets, request_llm_config(&request)?)?;
 
-    let commentary = client::comment_on_card_with_context(card_name, card_question, card_meaning, life_area, selected_cards, &config).await?;
+    let prompts = load_prompt_registry(&db)?;
+    let commentary = client::comment_on_card_with_context(card_name, card_question, card_meaning, &life_area, selected_cards, &prompts, &http, &ResponseCache::new(&db, &config.cache), &config).await?;
     Ok(CardCommentaryResponse { commentary })
 }
 
 #[tauri::command]
 pub async fn comment_on_multiple_cards(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: serde_json::Value,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
     let cards = request
         .get("cards")
         .and_then(|v| v.as_array())
         .ok_or("Missing cards array")?;
-    let life_area = request
-        .get("life_area")
-        .and_then(|v| v.as_str())
-        .ok_or("Missing life_area")?;
+    let life_area = resolve_life_area(&db, &request)?;
     let empty_cards = vec![];
     let selected_cards = request
         .get("selected_cards")
         .and_then(|v| v.as_array())
         .unwrap_or(&empty_cards);
-    let config = serde_json::from_value(request.get("config").ok_or("Missing config")?.clone())
-        .map_err(|e| format!("Invalid config: {}", e))?;
+    let config = load_llm_config(&db, &secrets, request_llm_config(&request)?)?;
 
-    let commentaries = client::comment_on_multiple_cards_with_context(cards, life_area, selected_cards, &config).await?;
+    let prompts = load_prompt_registry(&db)?;
+    let commentaries = client::comment_on_multiple_cards_with_context(cards, &life_area, selected_cards, &prompts, &http, &ResponseCache::new(&db, &config.cache), &config).await?;
     Ok(serde_json::json!({ "commentaries": commentaries }))
 }
 
 #[tauri::command]
 pub async fn chat_with_history(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
     request: serde_json::Value,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
     let user_message = request
         .get("user_message")
         .and_then(|v| v.as_str())
@@ -312,41 +681,26 @@ pub async fn chat_with_history(
         .get("card_insights")
         .and_then(|v| v.as_str())
         .unwrap_or("");
-    let life_area = request
-        .get("life_area")
-        .and_then(|v| v.as_str())
-        .ok_or("Missing life_area")?;
-    let config = serde_json::from_value(request.get("config").ok_or("Missing config")?.clone())
-        .map_err(|e| format!("Invalid config: {}", e))?;
-
-    let user_name = request
-        .get("user_name")
-        .and_then(|v| v.as_str())
-        .unwrap_or("");
-    let zodiac_sign = request
-        .get("zodiac_sign")
-        .and_then(|v| v.as_str());
-    let mbti_type = request
-        .get("mbti_type")
-        .and_then(|v| v.as_str());
+    let life_area = resolve_life_area(&db, &request)?;
+    let config = load_llm_config(&db, &secrets, request_llm_config(&request)?)?;
     let selected_cards = request
         .get("selected_cards")
         .and_then(|v| v.as_array())
         .cloned()
         .unwrap_or_default();
 
-    let response = client::chat_with_history_with_profile(
+    let prompts = load_prompt_registry(&db)?;
+    let response = client::chat_with_history(
         user_message,
         messages,
         card_name,
         card_question,
         card_meaning,
         card_insights,
-        life_area,
-        user_name,
-        zodiac_sign,
-        mbti_type,
+        &life_area.name,
         &selected_cards,
+        &prompts,
+        &http,
         &config,
     )
     .await?;
@@ -356,18 +710,22 @@ pub async fn chat_with_history(
 // Mind dump commands
 #[tauri::command]
 pub fn create_mind_dump(
+    app: AppHandle,
     db: State<Database>,
     input: CreateMindDumpInput,
 ) -> Result<MindDump, String> {
-    db.create_mind_dump(input).map_err(|e| e.to_string())
+    let mind_dump = db.create_mind_dump(input).map_err(|e| e.to_string())?;
+    refresh_embedding(&app, JournalEntry::from_mind_dump(&mind_dump));
+    Ok(mind_dump)
 }
 
 #[tauri::command]
 pub fn get_mind_dump(
     db: State<Database>,
     id: i64,
-) -> Result<Option<MindDump>, String> {
-    db.get_mind_dump(id).map_err(|e| e.to_string())
+) -> Result<Option<WithLinks<MindDump>>, String> {
+    let mind_dump = db.get_mind_dump(id).map_err(|e| e.to_string())?;
+    with_links(&db, EntryType::MindDump, id, mind_dump)
 }
 
 #[tauri::command]
@@ -381,10 +739,13 @@ pub fn list_mind_dumps(
 
 #[tauri::command]
 pub fn update_mind_dump(
+    app: AppHandle,
     db: State<Database>,
     input: UpdateMindDumpInput,
 ) -> Result<Option<MindDump>, String> {
-    db.update_mind_dump(input).map_err(|e| e.to_string())
+    let mind_dump = db.update_mind_dump(input).map_err(|e| e.to_string())?;
+    refresh_embedding(&app, mind_dump.as_ref().and_then(JournalEntry::from_mind_dump));
+    Ok(mind_dump)
 }
 
 #[tauri::command]
@@ -403,6 +764,59 @@ pub fn search_mind_dumps(
     db.search_mind_dumps(&query).map_err(|e| e.to_string())
 }
 
+/// Summarise a mind dump, extract its worries and action items and link suggested cards.
+/// Replaces any earlier analysis of the same mind dump.
+#[tauri::command]
+pub async fn analyze_mind_dump(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    mind_dump_id: i64,
+    config: Option<LLMConfig>,
+) -> Result<MindDumpAnalysisWithCards, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    let prompts = load_prompt_registry(&db)?;
+    let mind_dump = db.get_mind_dump(mind_dump_id)
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Mind dump {} not found", mind_dump_id))?;
+
+    let llm_response = client::generate_mind_dump_analysis(
+        mind_dump.title.as_deref(),
+        &mind_dump.content,
+        &prompts,
+        &http,
+        &config,
+    ).await?;
+
+    // Resolve every card before touching the stored analysis
+    let cards = resolve_symbol_cards(&db, &llm_response.suggested_cards)?;
+    db.create_mind_dump_analysis_with_cards(CreateMindDumpAnalysisInput {
+        mind_dump_id,
+        summary: llm_response.summary,
+        worries: llm_response.worries,
+        action_items: llm_response.action_items,
+        provenance: client::generation_provenance(
+            PromptTemplateId::MindDumpAnalysis,
+            client::MIND_DUMP_ANALYSIS_TEMPERATURE,
+            &prompts,
+            &config,
+        ),
+    }, &cards).map_err(|e| e.to_string())?;
+
+    db.get_mind_dump_analysis_with_cards(mind_dump_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| LlmError::Other("Failed to retrieve mind dump analysis".to_string()))
+}
+
+#[tauri::command]
+pub fn get_mind_dump_analysis_with_cards(
+    db: State<Database>,
+    mind_dump_id: i64,
+) -> Result<Option<MindDumpAnalysisWithCards>, String> {
+    db.get_mind_dump_analysis_with_cards(mind_dump_id).map_err(|e| e.to_string())
+}
+
 // Card commands (read-only - cards are a fixed deck like tarot)
 #[tauri::command]
 pub fn get_card(
@@ -434,14 +848,45 @@ pub fn list_cards_by_usage(
     db.list_cards_by_usage().map_err(|e| e.to_string())
 }
 
+// Life area commands
+#[tauri::command]
+pub fn list_life_areas(
+    db: State<Database>,
+) -> Result<Vec<LifeArea>, String> {
+    db.list_life_areas().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_life_area(
+    db: State<Database>,
+    input: CreateLifeAreaInput,
+) -> Result<LifeArea, String> {
+    if !input.name.chars().any(char::is_alphanumeric) {
+        return Err("Life area name must contain a letter or digit".to_string());
+    }
+    db.create_life_area(input).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_life_area(
+    db: State<Database>,
+    id: i64,
+) -> Result<bool, String> {
+    db.delete_life_area(id).map_err(|e| e.to_string())
+}
+
 // Bug-Card relationship commands
 #[tauri::command]
 pub fn create_bug_with_cards(
+    app: AppHandle,
     db: State<Database>,
     input: CreateBugInput,
     card_names: Vec<String>,
 ) -> Result<Bug, String> {
-    db.create_bug_with_cards(input, card_names).map_err(|e| e.to_string())
+    check_life_area(&db, input.life_area_id)?;
+    let bug = db.create_bug_with_cards(input, card_names).map_err(|e| e.to_string())?;
+    refresh_embedding(&app, JournalEntry::from_bug(&bug));
+    Ok(bug)
 }
 
 #[tauri::command]
@@ -487,6 +932,356 @@ pub fn get_card_bugs(
     db.get_card_bugs(card_id).map_err(|e| e.to_string())
 }
 
+// Prompt template commands
+#[tauri::command]
+pub fn list_prompt_templates(
+    db: State<Database>,
+) -> Result<Vec<PromptTemplateInfo>, String> {
+    let prompts = load_prompt_registry(&db)?;
+    Ok(PromptTemplateId::ALL.iter().map(|id| prompts.info(*id)).collect())
+}
+
+#[tauri::command]
+pub fn get_prompt_template(
+    db: State<Database>,
+    template_id: PromptTemplateId,
+) -> Result<PromptTemplateInfo, String> {
+    let prompts = load_prompt_registry(&db)?;
+    Ok(prompts.info(template_id))
+}
+
+#[tauri::command]
+pub fn set_prompt_template_override(
+    db: State<Database>,
+    template_id: PromptTemplateId,
+    template_text: String,
+) -> Result<PromptTemplateInfo, String> {
+    templates::validate_template_text(template_id, &template_text)?;
+    db.upsert_prompt_template_override(template_id.as_str(), &template_text)
+        .map_err(|e| e.to_string())?;
+
+    let prompts = load_prompt_registry(&db)?;
+    Ok(prompts.info(template_id))
+}
+
+#[tauri::command]
+pub fn reset_prompt_template(
+    db: State<Database>,
+    template_id: PromptTemplateId,
+) -> Result<bool, String> {
+    db.delete_prompt_template_override(template_id.as_str())
+        .map_err(|e| e.to_string())
+}
+
+/// Render a template with the given variables so the user can see exactly what would be sent.
+/// `template_text` previews an unsaved draft instead of the active template.
+#[tauri::command]
+pub fn preview_prompt(
+    db: State<Database>,
+    template_id: PromptTemplateId,
+    variables: HashMap<String, String>,
+    template_text: Option<String>,
+) -> Result<String, String> {
+    let variables: Vec<(&str, &str)> = variables
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    match template_text {
+        Some(text) => {
+            templates::validate_template_text(template_id, &text)?;
+            templates::render(&text, &variables)
+        }
+        None => load_prompt_registry(&db)?.render(template_id, &variables),
+    }
+}
+
+// LLM connection commands
+
+/// Minimal round-trip to the configured provider; failures are part of the result
+#[tauri::command]
+pub async fn test_llm_connection(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    config: Option<LLMConfig>,
+) -> Result<ConnectionTestResult, LlmError> {
+    let config = load_llm_config(&db, &secrets, config)?;
+    Ok(discovery::test_connection(&http, &config).await)
+}
+
+#[tauri::command]
+pub async fn list_available_models(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    config: Option<LLMConfig>,
+) -> Result<Vec<AvailableModel>, LlmError> {
+    let config = load_llm_config(&db, &secrets, config)?;
+    discovery::list_models(&http, &config).await
+}
+
+// LLM settings commands
+#[tauri::command]
+pub fn get_llm_config(
+    db: State<Database>,
+    secrets: State<Secrets>,
+) -> Result<LlmSettingsView, String> {
+    llm_settings::settings_view(&db, &secrets)
+}
+
+/// Save the LLM settings; an empty API key leaves the stored key unchanged
+#[tauri::command]
+pub fn set_llm_config(
+    db: State<Database>,
+    secrets: State<Secrets>,
+    config: LLMConfig,
+) -> Result<LlmSettingsView, String> {
+    llm_settings::save_config(&db, &secrets, config)?;
+    llm_settings::settings_view(&db, &secrets)
+}
+
+#[tauri::command]
+pub fn delete_llm_api_key(
+    secrets: State<Secrets>,
+    provider: LLMProvider,
+) -> Result<bool, String> {
+    match llm_settings::api_key_name(&provider) {
+        Some(key) => secrets.delete(key),
+        None => Ok(false),
+    }
+}
+
+// User profile commands
+#[tauri::command]
+pub fn get_user_profile(
+    db: State<Database>,
+) -> Result<UserProfile, String> {
+    profile::load_profile(&db)
+}
+
+/// Save the profile used to personalize prompts, returning it as stored
+#[tauri::command]
+pub fn update_user_profile(
+    db: State<Database>,
+    profile: UserProfile,
+) -> Result<UserProfile, String> {
+    profile::save_profile(&db, profile)
+}
+
+// LLM usage commands
+
+/// Token totals and estimated cost grouped by day, model or operation
+#[tauri::command]
+pub fn get_llm_usage(
+    db: State<Database>,
+    group_by: UsageGrouping,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<LlmUsageSummary>, String> {
+    let totals = db.llm_usage_totals(group_by, from, to).map_err(|e| e.to_string())?;
+    Ok(usage::summarize(totals))
+}
+
+/// Drop every cached LLM response, returning how many were removed
+#[tauri::command]
+pub fn clear_llm_cache(
+    db: State<Database>,
+) -> Result<usize, String> {
+    db.clear_response_cache().map_err(|e| e.to_string())
+}
+
+// Semantic search commands
+
+/// Dreams, mind dumps and bugs ranked by meaning and keyword overlap with `query`.
+/// Uses the stored vectors; keyword-only when embeddings are off or the query can't be embedded.
+#[tauri::command]
+pub async fn semantic_search(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    query: String,
+    entry_types: Option<Vec<EntryType>>,
+    limit: Option<usize>,
+    config: Option<LLMConfig>,
+) -> Result<Vec<SemanticSearchResult>, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    let entry_types = entry_types.unwrap_or_else(|| EntryType::ALL.to_vec());
+
+    embeddings::semantic_search(&query, &entry_types, limit.unwrap_or(20), &db, &http, &config).await
+}
+
+/// Embed every entry that is new or changed, returning how many were embedded
+#[tauri::command]
+pub async fn sync_embeddings(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    config: Option<LLMConfig>,
+) -> Result<usize, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    if !embeddings::enabled(&config) {
+        return Ok(0);
+    }
+
+    let entries = embeddings::journal_entries(&db, &EntryType::ALL)?;
+    embeddings::sync_index(&db, &http, &config, &entries).await
+}
+
+/// Answer a question from the journal, citing the dreams, mind dumps and resolved bugs it drew on.
+/// `messages` is the earlier conversation as `{role, content}` objects.
+#[tauri::command]
+pub async fn ask_journal(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    question: String,
+    messages: Option<Vec<serde_json::Value>>,
+    limit: Option<usize>,
+    config: Option<LLMConfig>,
+) -> Result<AskJournalResponse, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    let prompts = load_prompt_registry(&db)?;
+
+    journal_chat::ask_journal(&question, &messages.unwrap_or_default(), limit.unwrap_or(6), &db, &http, &prompts, &config).await
+}
+
+/// Earlier dreams most like this one, with a recurrence suggestion when one matches closely.
+/// With `apply`, the suggestion is written to a dream not already marked recurring.
+#[tauri::command]
+pub fn find_similar_dreams(
+    db: State<Database>,
+    secrets: State<Secrets>,
+    dream_id: i64,
+    limit: Option<usize>,
+    apply: Option<bool>,
+    config: Option<LLMConfig>,
+) -> Result<SimilarDreamsResult, LlmError> {
+    let config = load_llm_config(&db, &secrets, config)?;
+
+    let similar = similar_dreams::find_similar_dreams(dream_id, limit.unwrap_or(5), &db, &config)?;
+
+    let dream = db
+        .get_dream(dream_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Dream {} not found", dream_id))?;
+    let suggestion = similar_dreams::recurrence_suggestion(&dream, &similar);
+
+    let mut applied = false;
+    if let (Some(suggestion), true) = (&suggestion, apply.unwrap_or(false)) {
+        if dream.is_recurring != Some(true) {
+            db.update_dream(UpdateDreamInput {
+                id: dream_id,
+                date_occurred: None,
+                title: None,
+                content: None,
+                emotions_tags: None,
+                sleep_quality: None,
+                is_recurring: Some(true),
+                last_occurrence_period: Some(suggestion.last_occurrence_period.clone()),
+                is_lucid: None,
+            })
+            .map_err(|e| e.to_string())?;
+            applied = true;
+        }
+    }
+
+    Ok(SimilarDreamsResult {
+        dream_id,
+        similar,
+        suggestion,
+        applied,
+    })
+}
+
+// Entry link commands
+#[tauri::command]
+pub fn link_entries(
+    db: State<Database>,
+    source_type: EntryType,
+    source_id: i64,
+    target_type: EntryType,
+    target_id: i64,
+    relation: EntryRelation,
+) -> Result<EntryLink, String> {
+    if source_type == target_type && source_id == target_id {
+        return Err("An entry cannot be linked to itself".to_string());
+    }
+    for (entry_type, id) in [(source_type, source_id), (target_type, target_id)] {
+        if !db.entry_exists(entry_type, id).map_err(|e| e.to_string())? {
+            return Err(format!("{} {} not found", entry_type.as_str(), id));
+        }
+    }
+
+    db.create_entry_link(source_type, source_id, target_type, target_id, relation)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unlink_entries(
+    db: State<Database>,
+    link_id: i64,
+) -> Result<bool, String> {
+    db.delete_entry_link(link_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_entry_links(
+    db: State<Database>,
+    entry_type: EntryType,
+    entry_id: i64,
+) -> Result<Vec<LinkedEntry>, String> {
+    db.list_linked_entries(entry_type, entry_id).map_err(|e| e.to_string())
+}
+
+// Digest commands
+
+/// Reflect on a week, month or custom range of dreams, mind dumps and bug activity and store the digest.
+/// Week and month ranges start at `start_date`; custom ranges need `end_date` (exclusive).
+#[tauri::command]
+pub async fn generate_period_digest(
+    db: State<'_, Database>,
+    http: State<'_, LlmHttp>,
+    secrets: State<'_, Secrets>,
+    period_type: DigestPeriod,
+    start_date: DateTime<Utc>,
+    end_date: Option<DateTime<Utc>>,
+    config: Option<LLMConfig>,
+) -> Result<Digest, LlmError> {
+    let http = UsageRecorder::new(&db, &http);
+    let config = load_llm_config(&db, &secrets, config)?;
+    let prompts = load_prompt_registry(&db)?;
+
+    digest::generate_period_digest(period_type, start_date, end_date, &db, &http, &prompts, &config).await
+}
+
+#[tauri::command]
+pub fn list_digests(
+    db: State<Database>,
+    period_type: Option<DigestPeriod>,
+) -> Result<Vec<Digest>, String> {
+    db.list_digests(period_type).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_digest(
+    db: State<Database>,
+    id: i64,
+) -> Result<Option<Digest>, String> {
+    db.get_digest(id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_digest(
+    db: State<Database>,
+    id: i64,
+) -> Result<bool, String> {
+    db.delete_digest(id).map_err(|e| e.to_string())
+}
+
 // Database backup command
 #[tauri::command]
 pub fn backup_database(
@@ -0,0 +1,206 @@
+use super::models::{BugPattern, CreateBugPatternInput, GenerationProvenance, PatternFrequency};
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+
+const BUG_PATTERN_COLUMNS: &str =
+    "bug_id, pattern, confidence, evidence, provider, model, prompt_version, temperature, created_at";
+
+fn bug_pattern_from_row(row: &Row) -> SqlResult<BugPattern> {
+    Ok(BugPattern {
+        bug_id: row.get(0)?,
+        pattern: row.get(1)?,
+        confidence: row.get(2)?,
+        evidence: row.get(3)?,
+        provenance: GenerationProvenance {
+            provider: row.get(4)?,
+            model: row.get(5)?,
+            prompt_version: row.get(6)?,
+            temperature: row.get(7)?,
+        },
+        created_at: row.get::<_, String>(8)?.parse().unwrap(),
+    })
+}
+
+/// Confidence the blocker a retrospective names counts with; it was judged with hindsight
+pub const RETROSPECTIVE_CONFIDENCE: f64 = 1.0;
+
+/// Remove a bug's detected patterns; called when the bug is deleted or classified again
+pub(super) fn delete_pattern_rows(conn: &Connection, bug_id: i64) -> SqlResult<usize> {
+    conn.execute("DELETE FROM bug_patterns WHERE bug_id = ?1", params![bug_id])
+}
+
+impl Database {
+    /// Replace the patterns detected in a bug
+    pub fn replace_bug_patterns(
+        &self,
+        bug_id: i64,
+        patterns: Vec<CreateBugPatternInput>,
+        provenance: GenerationProvenance,
+    ) -> SqlResult<Vec<BugPattern>> {
+        let mut conn = self.get_connection();
+        let now = Utc::now();
+
+        let tx = conn.transaction()?;
+        delete_pattern_rows(&tx, bug_id)?;
+        for pattern in &patterns {
+            tx.execute(
+                "INSERT OR REPLACE INTO bug_patterns
+                 (bug_id, pattern, confidence, evidence, provider, model, prompt_version, temperature, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    bug_id,
+                    pattern.pattern,
+                    pattern.confidence,
+                    pattern.evidence,
+                    provenance.provider,
+                    provenance.model,
+                    provenance.prompt_version,
+                    provenance.temperature,
+                    now.to_rfc3339(),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        drop(conn);
+
+        self.get_bug_patterns(bug_id)
+    }
+
+    /// Patterns detected in a bug, most confident first
+    pub fn get_bug_patterns(&self, bug_id: i64) -> SqlResult<Vec<BugPattern>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM bug_patterns WHERE bug_id = ?1 ORDER BY confidence DESC, pattern ASC",
+            BUG_PATTERN_COLUMNS
+        ))?;
+
+        let patterns = stmt
+            .query_map(params![bug_id], bug_pattern_from_row)?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(patterns)
+    }
+
+    /// How many bugs each pattern was detected in with at least `min_confidence`, most frequent first.
+    /// The blocker a bug's retrospective names counts too; a bug counts once per pattern,
+    /// at the higher of its classifier and retrospective confidence.
+    pub fn get_pattern_frequencies(&self, min_confidence: f64) -> SqlResult<Vec<PatternFrequency>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT pattern, COUNT(*), AVG(confidence)
+             FROM (
+                 SELECT bug_id, pattern, MAX(confidence) AS confidence
+                 FROM (
+                     SELECT bug_id, pattern, confidence FROM bug_patterns
+                     UNION ALL
+                     SELECT bug_id, blocker_pattern, ?2 FROM bug_retrospectives
+                 )
+                 GROUP BY bug_id, pattern
+             )
+             WHERE confidence >= ?1
+             GROUP BY pattern
+             ORDER BY COUNT(*) DESC, AVG(confidence) DESC, pattern ASC",
+        )?;
+
+        let frequencies = stmt
+            .query_map(params![min_confidence, RETROSPECTIVE_CONFIDENCE], |row| {
+                Ok(PatternFrequency {
+                    pattern: row.get(0)?,
+                    group: None,
+                    bug_count: row.get(1)?,
+                    average_confidence: row.get(2)?,
+                })
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(frequencies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{CreateBugInput, CreateBugPatternInput, CreateBugRetrospectiveInput, GenerationProvenance};
+    use super::super::Database;
+    use crate::db::test_support::setup_test_db;
+
+    fn create_bug(db: &Database) -> i64 {
+        db.create_bug(CreateBugInput {
+            title: "Bug".to_string(),
+            description: String::new(),
+            cards_drawn: None,
+            conversation_history: None,
+            notes: None,
+            life_area_id: None,
+        })
+        .unwrap()
+        .id
+        .unwrap()
+    }
+
+    fn tag(pattern: &str, confidence: f64) -> CreateBugPatternInput {
+        CreateBugPatternInput { pattern: pattern.to_string(), confidence, evidence: None }
+    }
+
+    #[test]
+    fn test_frequencies_count_bugs_above_confidence() {
+        let db = setup_test_db();
+        let first = create_bug(&db);
+        let second = create_bug(&db);
+        let provenance = GenerationProvenance::default();
+
+        db.replace_bug_patterns(first, vec![tag("sunk_cost_fallacy", 0.4)], provenance.clone()).unwrap();
+        let stored = db
+            .replace_bug_patterns(first, vec![tag("circular_reasoning", 0.9), tag("avoidance_pattern", 0.3)], provenance.clone())
+            .unwrap();
+        assert_eq!(stored.iter().map(|p| p.pattern.as_str()).collect::<Vec<_>>(), vec!["circular_reasoning", "avoidance_pattern"]);
+        db.replace_bug_patterns(second, vec![tag("circular_reasoning", 0.7)], provenance).unwrap();
+
+        let frequencies = db.get_pattern_frequencies(0.5).unwrap();
+        assert_eq!(frequencies.len(), 1);
+        assert_eq!(frequencies[0].pattern, "circular_reasoning");
+        assert_eq!(frequencies[0].bug_count, 2);
+        assert!((frequencies[0].average_confidence - 0.8).abs() < 1e-9);
+
+        db.delete_bug(second).unwrap();
+        assert_eq!(db.get_pattern_frequencies(0.0).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_retrospective_blocker_counts_without_touching_classifier_tags() {
+        let db = setup_test_db();
+        let bug_id = create_bug(&db);
+        let retrospective = |blocker_pattern: &str| CreateBugRetrospectiveInput {
+            bug_id,
+            root_pattern: "Waiting for certainty before acting".to_string(),
+            blocker_pattern: blocker_pattern.to_string(),
+            what_helped: Vec::new(),
+            follow_ups: Vec::new(),
+            provenance: GenerationProvenance::default(),
+        };
+        let frequency = |db: &Database, pattern: &str| {
+            let frequencies = db.get_pattern_frequencies(0.5).unwrap();
+            frequencies.into_iter().find(|f| f.pattern == pattern).map(|f| (f.bug_count, f.average_confidence))
+        };
+        let classified = CreateBugPatternInput {
+            pattern: "circular_reasoning".to_string(),
+            confidence: 0.6,
+            evidence: Some("Keeps revisiting the same options".to_string()),
+        };
+
+        db.replace_bug_patterns(bug_id, vec![classified], GenerationProvenance::default()).unwrap();
+        db.create_bug_retrospective(retrospective("circular_reasoning")).unwrap();
+        assert_eq!(frequency(&db, "circular_reasoning"), Some((1, 1.0)));
+
+        db.create_bug_retrospective(retrospective("false_precision")).unwrap();
+        assert_eq!(frequency(&db, "circular_reasoning"), Some((1, 0.6)));
+        assert_eq!(frequency(&db, "false_precision"), Some((1, 1.0)));
+
+        let stored = db.get_bug_patterns(bug_id).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].confidence, 0.6);
+        assert_eq!(stored[0].evidence.as_deref(), Some("Keeps revisiting the same options"));
+    }
+}
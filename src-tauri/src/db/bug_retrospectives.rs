@@ -0,0 +1,138 @@
+use super::models::{BugRetrospective, CreateBugRetrospectiveInput, GenerationProvenance};
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+
+const BUG_RETROSPECTIVE_COLUMNS: &str = "id, bug_id, root_pattern, blocker_pattern, what_helped, follow_ups, provider, model, prompt_version, temperature, created_at, updated_at";
+
+fn bug_retrospective_from_row(row: &Row) -> SqlResult<BugRetrospective> {
+    Ok(BugRetrospective {
+        id: Some(row.get(0)?),
+        bug_id: row.get(1)?,
+        root_pattern: row.get(2)?,
+        blocker_pattern: row.get(3)?,
+        what_helped: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+        follow_ups: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+        provenance: GenerationProvenance {
+            provider: row.get(6)?,
+            model: row.get(7)?,
+            prompt_version: row.get(8)?,
+            temperature: row.get(9)?,
+        },
+        created_at: row.get::<_, String>(10)?.parse().unwrap(),
+        updated_at: row.get::<_, String>(11)?.parse().unwrap(),
+    })
+}
+
+fn to_json(items: &[String]) -> SqlResult<String> {
+    serde_json::to_string(items).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Remove a bug's retrospective; called when the bug is deleted
+pub(super) fn delete_retrospective_rows(conn: &Connection, bug_id: i64) -> SqlResult<usize> {
+    conn.execute("DELETE FROM bug_retrospectives WHERE bug_id = ?1", params![bug_id])
+}
+
+impl Database {
+    /// Store the retrospective of a bug, replacing any earlier one
+    pub fn create_bug_retrospective(&self, input: CreateBugRetrospectiveInput) -> SqlResult<BugRetrospective> {
+        let mut conn = self.get_connection();
+        let now = Utc::now();
+
+        let tx = conn.transaction()?;
+        delete_retrospective_rows(&tx, input.bug_id)?;
+        tx.execute(
+            "INSERT INTO bug_retrospectives
+             (bug_id, root_pattern, blocker_pattern, what_helped, follow_ups, provider, model, prompt_version, temperature, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                input.bug_id,
+                input.root_pattern,
+                input.blocker_pattern,
+                to_json(&input.what_helped)?,
+                to_json(&input.follow_ups)?,
+                input.provenance.provider,
+                input.provenance.model,
+                input.provenance.prompt_version,
+                input.provenance.temperature,
+                now.to_rfc3339(),
+                now.to_rfc3339(),
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        tx.commit()?;
+
+        Ok(BugRetrospective {
+            id: Some(id),
+            bug_id: input.bug_id,
+            root_pattern: input.root_pattern,
+            blocker_pattern: input.blocker_pattern,
+            what_helped: input.what_helped,
+            follow_ups: input.follow_ups,
+            provenance: input.provenance,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn get_bug_retrospective(&self, bug_id: i64) -> SqlResult<Option<BugRetrospective>> {
+        let conn = self.get_connection();
+
+        let retrospective = conn.query_row(
+            &format!("SELECT {} FROM bug_retrospectives WHERE bug_id = ?1", BUG_RETROSPECTIVE_COLUMNS),
+            params![bug_id],
+            bug_retrospective_from_row,
+        );
+
+        match retrospective {
+            Ok(r) => Ok(Some(r)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{CreateBugInput, CreateBugRetrospectiveInput, GenerationProvenance};
+    use crate::db::test_support::setup_test_db;
+
+    fn retrospective(bug_id: i64, blocker_pattern: &str) -> CreateBugRetrospectiveInput {
+        CreateBugRetrospectiveInput {
+            bug_id,
+            root_pattern: "Waiting for certainty before acting".to_string(),
+            blocker_pattern: blocker_pattern.to_string(),
+            what_helped: vec!["Drawing The Fool".to_string()],
+            follow_ups: Vec::new(),
+            provenance: GenerationProvenance::default(),
+        }
+    }
+
+    #[test]
+    fn test_retrospective_is_replaced_and_removed_with_bug() {
+        let db = setup_test_db();
+        let bug_id = db
+            .create_bug(CreateBugInput {
+                title: "Stalled project".to_string(),
+                description: "I keep not starting".to_string(),
+                cards_drawn: None,
+                conversation_history: None,
+                notes: None,
+                life_area_id: None,
+            })
+            .unwrap()
+            .id
+            .unwrap();
+
+        db.create_bug_retrospective(retrospective(bug_id, "false_precision")).unwrap();
+        let second = db.create_bug_retrospective(retrospective(bug_id, "choice_overload_paralysis")).unwrap();
+
+        let stored = db.get_bug_retrospective(bug_id).unwrap().unwrap();
+        assert_eq!(stored.id, second.id);
+        assert_eq!(stored.blocker_pattern, "choice_overload_paralysis");
+        assert_eq!(stored.what_helped, vec!["Drawing The Fool"]);
+
+        assert!(db.delete_bug(bug_id).unwrap());
+        assert!(db.get_bug_retrospective(bug_id).unwrap().is_none());
+    }
+}
@@ -1,43 +1,52 @@
-use super::models::{Bug, CreateBugInput, UpdateBugInput};
+use super::cards::insert_bug_card;
+use super::entry_links::insert_entry_link;
+use super::models::{Bug, CreateBugInput, EntryLink, EntryRelation, EntryType, UpdateBugInput};
 use super::Database;
 use chrono::Utc;
-use rusqlite::{params, Result as SqlResult};
+use rusqlite::{params, Connection, Result as SqlResult};
+
+/// `create_bug` on a connection the caller holds, so it can join a transaction
+fn insert_bug(conn: &Connection, input: CreateBugInput) -> SqlResult<Bug> {
+    let now = Utc::now();
+
+    // Note: cards_drawn is kept for backward compatibility but should use bug_cards table instead
+    conn.execute(
+        "INSERT INTO bugs (title, description, status, cards_drawn, conversation_history, notes, life_area_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            input.title,
+            input.description,
+            "active",
+            input.cards_drawn,
+            input.conversation_history,
+            input.notes,
+            input.life_area_id,
+            now.to_rfc3339(),
+            now.to_rfc3339(),
+        ],
+    )?;
+
+    let id = conn.last_insert_rowid();
+
+    Ok(Bug {
+        id: Some(id),
+        title: input.title,
+        description: input.description,
+        status: "active".to_string(),
+        cards_drawn: input.cards_drawn,
+        conversation_history: input.conversation_history,
+        notes: input.notes,
+        life_area_id: input.life_area_id,
+        created_at: now,
+        updated_at: now,
+        resolved_at: None,
+    })
+}
 
 impl Database {
     pub fn create_bug(&self, input: CreateBugInput) -> SqlResult<Bug> {
         let conn = self.get_connection();
-        let now = Utc::now();
-
-        // Note: cards_drawn is kept for backward compatibility but should use bug_cards table instead
-        conn.execute(
-            "INSERT INTO bugs (title, description, status, cards_drawn, conversation_history, notes, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                input.title,
-                input.description,
-                "active",
-                input.cards_drawn,
-                input.conversation_history,
-                input.notes,
-                now.to_rfc3339(),
-                now.to_rfc3339(),
-            ],
-        )?;
-
-        let id = conn.last_insert_rowid();
-
-        Ok(Bug {
-            id: Some(id),
-            title: input.title,
-            description: input.description,
-            status: "active".to_string(),
-            cards_drawn: input.cards_drawn,
-            conversation_history: input.conversation_history,
-            notes: input.notes,
-            created_at: now,
-            updated_at: now,
-            resolved_at: None,
-        })
+        insert_bug(&conn, input)
     }
 
     /// Create a bug with cards using the new card tables
@@ -65,11 +74,34 @@ impl Database {
         Ok(bug)
     }
 
+    /// Create a bug promoted from a dream or mind dump, with its `promoted_from` link
+    /// and cards drawn in order, all in one transaction
+    pub fn create_bug_from_entry(
+        &self,
+        input: CreateBugInput,
+        entry_type: EntryType,
+        entry_id: i64,
+        card_ids: &[i64],
+    ) -> SqlResult<(Bug, EntryLink)> {
+        let mut conn = self.get_connection();
+
+        let tx = conn.transaction()?;
+        let bug = insert_bug(&tx, input)?;
+        let bug_id = bug.id.unwrap();
+        let link = insert_entry_link(&tx, EntryType::Bug, bug_id, entry_type, entry_id, EntryRelation::PromotedFrom)?;
+        for (position, card_id) in card_ids.iter().enumerate() {
+            insert_bug_card(&tx, bug_id, *card_id, Some((position + 1) as i32))?;
+        }
+        tx.commit()?;
+
+        Ok((bug, link))
+    }
+
     pub fn get_bug(&self, id: i64) -> SqlResult<Option<Bug>> {
         let conn = self.get_connection();
 
         let mut stmt = conn.prepare(
-            "SELECT id, title, description, status, cards_drawn, conversation_history, notes, created_at, updated_at, resolved_at
+            "SELECT id, title, description, status, cards_drawn, conversation_history, notes, created_at, updated_at, resolved_at, life_area_id
              FROM bugs WHERE id = ?1",
         )?;
 
@@ -82,6 +114,7 @@ impl Database {
                 cards_drawn: row.get(4)?,
                 conversation_history: row.get(5)?,
                 notes: row.get(6)?,
+                life_area_id: row.get(10)?,
                 created_at: row.get::<_, String>(7)?.parse().unwrap(),
                 updated_at: row.get::<_, String>(8)?.parse().unwrap(),
                 resolved_at: row.get::<_, Option<String>>(9)?.map(|s| s.parse().unwrap()),
@@ -95,30 +128,19 @@ impl Database {
         }
     }
 
-    pub fn list_bugs(&self, status: Option<String>) -> SqlResult<Vec<Bug>> {
+    /// Bugs newest first, optionally only those with a status and/or in a life area
+    pub fn list_bugs(&self, status: Option<String>, life_area_id: Option<i64>) -> SqlResult<Vec<Bug>> {
         let conn = self.get_connection();
 
-        let (query, params) = if let Some(status_filter) = status {
-            (
-                "SELECT id, title, description, status, cards_drawn, conversation_history, notes, created_at, updated_at, resolved_at
-                 FROM bugs
-                 WHERE status = ?1
-                 ORDER BY created_at DESC".to_string(),
-                vec![status_filter],
-            )
-        } else {
-            (
-                "SELECT id, title, description, status, cards_drawn, conversation_history, notes, created_at, updated_at, resolved_at
-                 FROM bugs
-                 ORDER BY created_at DESC".to_string(),
-                vec![],
-            )
-        };
-
-        let mut stmt = conn.prepare(&query)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, title, description, status, cards_drawn, conversation_history, notes, created_at, updated_at, resolved_at, life_area_id
+             FROM bugs
+             WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR life_area_id = ?2)
+             ORDER BY created_at DESC",
+        )?;
 
         let bugs = stmt
-            .query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            .query_map(params![status, life_area_id], |row| {
                 Ok(Bug {
                     id: Some(row.get(0)?),
                     title: row.get(1)?,
@@ -127,6 +149,7 @@ impl Database {
                     cards_drawn: row.get(4)?,
                     conversation_history: row.get(5)?,
                     notes: row.get(6)?,
+                    life_area_id: row.get(10)?,
                     created_at: row.get::<_, String>(7)?.parse().unwrap(),
                     updated_at: row.get::<_, String>(8)?.parse().unwrap(),
                     resolved_at: row.get::<_, Option<String>>(9)?.map(|s| s.parse().unwrap()),
@@ -138,8 +161,6 @@ impl Database {
     }
 
     pub fn update_bug(&self, input: UpdateBugInput) -> SqlResult<Option<Bug>> {
-        let conn = self.get_connection();
-
         // First, get the existing bug
         let existing = self.get_bug(input.id)?;
         if existing.is_none() {
@@ -148,6 +169,7 @@ impl Database {
 
         let mut existing = existing.unwrap();
         let now = Utc::now();
+        let conn = self.get_connection();
 
         // Update only provided fields
         if let Some(title) = input.title {
@@ -168,6 +190,11 @@ impl Database {
         if let Some(notes) = input.notes {
             existing.notes = Some(notes);
         }
+        if let Some(life_area_id) = input.life_area_id {
+            existing.life_area_id = Some(life_area_id);
+        } else if input.clear_life_area == Some(true) {
+            existing.life_area_id = None;
+        }
         if let Some(resolved_at) = input.resolved_at {
             existing.resolved_at = Some(resolved_at);
         }
@@ -176,8 +203,8 @@ impl Database {
 
         conn.execute(
             "UPDATE bugs
-             SET title = ?1, description = ?2, status = ?3, cards_drawn = ?4, conversation_history = ?5, notes = ?6, updated_at = ?7, resolved_at = ?8
-             WHERE id = ?9",
+             SET title = ?1, description = ?2, status = ?3, cards_drawn = ?4, conversation_history = ?5, notes = ?6, updated_at = ?7, resolved_at = ?8, life_area_id = ?9
+             WHERE id = ?10",
             params![
                 existing.title,
                 existing.description,
@@ -187,6 +214,7 @@ impl Database {
                 existing.notes,
                 existing.updated_at.to_rfc3339(),
                 existing.resolved_at.map(|dt| dt.to_rfc3339()),
+                existing.life_area_id,
                 input.id,
             ],
         )?;
@@ -195,9 +223,15 @@ impl Database {
     }
 
     pub fn delete_bug(&self, id: i64) -> SqlResult<bool> {
-        let conn = self.get_connection();
-
-        let rows_affected = conn.execute("DELETE FROM bugs WHERE id = ?1", params![id])?;
+        let mut conn = self.get_connection();
+
+        let tx = conn.transaction()?;
+        let rows_affected = tx.execute("DELETE FROM bugs WHERE id = ?1", params![id])?;
+        super::embeddings::delete_embedding_rows(&tx, EntryType::Bug, id)?;
+        super::entry_links::delete_links_for(&tx, EntryType::Bug, id)?;
+        super::bug_retrospectives::delete_retrospective_rows(&tx, id)?;
+        super::bug_patterns::delete_pattern_rows(&tx, id)?;
+        tx.commit()?;
 
         Ok(rows_affected > 0)
     }
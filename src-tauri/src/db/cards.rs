@@ -1,7 +1,25 @@
 use super::models::{BugCard, Bug, Card, CardWithCount, CreateCardInput};
 use super::Database;
 use chrono::Utc;
-use rusqlite::{params, Result as SqlResult};
+use rusqlite::{params, Connection, Result as SqlResult};
+
+/// `link_card_to_bug` on a connection the caller holds, so it can join a transaction
+pub(super) fn insert_bug_card(conn: &Connection, bug_id: i64, card_id: i64, position: Option<i32>) -> SqlResult<BugCard> {
+    let now = Utc::now();
+
+    conn.execute(
+        "INSERT OR REPLACE INTO bug_cards (bug_id, card_id, position, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![bug_id, card_id, position, now.to_rfc3339()],
+    )?;
+
+    Ok(BugCard {
+        bug_id,
+        card_id,
+        position,
+        created_at: now,
+    })
+}
 
 impl Database {
     /// Internal function to create a card (used for seeding the deck)
@@ -134,20 +152,7 @@ impl Database {
     /// Link a card to a bug
     pub fn link_card_to_bug(&self, bug_id: i64, card_id: i64, position: Option<i32>) -> SqlResult<BugCard> {
         let conn = self.get_connection();
-        let now = Utc::now();
-
-        conn.execute(
-            "INSERT OR REPLACE INTO bug_cards (bug_id, card_id, position, created_at)
-             VALUES (?1, ?2, ?3, ?4)",
-            params![bug_id, card_id, position, now.to_rfc3339()],
-        )?;
-
-        Ok(BugCard {
-            bug_id,
-            card_id,
-            position,
-            created_at: now,
-        })
+        insert_bug_card(&conn, bug_id, card_id, position)
     }
 
     /// Get all cards for a specific bug
@@ -204,7 +209,7 @@ impl Database {
         let conn = self.get_connection();
 
         let mut stmt = conn.prepare(
-            "SELECT b.id, b.title, b.description, b.status, b.cards_drawn, b.conversation_history, b.notes, b.created_at, b.updated_at, b.resolved_at
+            "SELECT b.id, b.title, b.description, b.status, b.cards_drawn, b.conversation_history, b.notes, b.created_at, b.updated_at, b.resolved_at, b.life_area_id
              FROM bugs b
              INNER JOIN bug_cards bc ON b.id = bc.bug_id
              WHERE bc.card_id = ?1
@@ -221,6 +226,7 @@ impl Database {
                     cards_drawn: row.get(4)?,
                     conversation_history: row.get(5)?,
                     notes: row.get(6)?,
+                    life_area_id: row.get(10)?,
                     created_at: row.get::<_, String>(7)?.parse().unwrap(),
                     updated_at: row.get::<_, String>(8)?.parse().unwrap(),
                     resolved_at: row.get::<_, Option<String>>(9)?.map(|s| s.parse().unwrap()),
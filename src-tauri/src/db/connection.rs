@@ -32,12 +32,16 @@ impl Database {
         Ok(db)
     }
 
-    fn get_database_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    /// Directory holding the database and other app data files
+    pub fn data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
         let proj_dirs = ProjectDirs::from("com", "limnl", "limnl-journal")
             .ok_or("Failed to determine project directories")?;
 
-        let data_dir = proj_dirs.data_dir();
-        Ok(data_dir.join("dreams.db"))
+        Ok(proj_dirs.data_dir().to_path_buf())
+    }
+
+    fn get_database_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Self::data_dir()?.join("dreams.db"))
     }
 
     fn seed_cards_from_json(&self) -> SqlResult<()> {
@@ -1,4 +1,4 @@
-use super::models::{CreateDreamCreativePromptsInput, DreamCreativePrompts};
+use super::models::{CreateDreamCreativePromptsInput, DreamCreativePrompts, GenerationProvenance};
 use super::Database;
 use chrono::Utc;
 use rusqlite::{params, Result as SqlResult};
@@ -24,13 +24,17 @@ impl Database {
         }
 
         conn.execute(
-            "INSERT INTO dream_creative_prompts (dream_analysis_id, image_prompts, music_prompts, story_prompts, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO dream_creative_prompts (dream_analysis_id, image_prompts, music_prompts, story_prompts, provider, model, prompt_version, temperature, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 input.dream_analysis_id,
                 input.image_prompts,
                 input.music_prompts,
                 input.story_prompts,
+                input.provenance.provider,
+                input.provenance.model,
+                input.provenance.prompt_version,
+                input.provenance.temperature,
                 now.to_rfc3339(),
                 now.to_rfc3339(),
             ],
@@ -44,6 +48,7 @@ impl Database {
             image_prompts: input.image_prompts,
             music_prompts: input.music_prompts,
             story_prompts: input.story_prompts,
+            provenance: input.provenance,
             created_at: now,
             updated_at: now,
         })
@@ -53,7 +58,7 @@ impl Database {
         let conn = self.get_connection();
 
         let mut stmt = conn.prepare(
-            "SELECT id, dream_analysis_id, image_prompts, music_prompts, story_prompts, created_at, updated_at
+            "SELECT id, dream_analysis_id, image_prompts, music_prompts, story_prompts, provider, model, prompt_version, temperature, created_at, updated_at
              FROM dream_creative_prompts WHERE dream_analysis_id = ?1",
         )?;
 
@@ -64,8 +69,14 @@ impl Database {
                 image_prompts: row.get(2)?,
                 music_prompts: row.get(3)?,
                 story_prompts: row.get(4)?,
-                created_at: row.get::<_, String>(5)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(6)?.parse().unwrap(),
+                provenance: GenerationProvenance {
+                    provider: row.get(5)?,
+                    model: row.get(6)?,
+                    prompt_version: row.get(7)?,
+                    temperature: row.get(8)?,
+                },
+                created_at: row.get::<_, String>(9)?.parse().unwrap(),
+                updated_at: row.get::<_, String>(10)?.parse().unwrap(),
             })
         });
 
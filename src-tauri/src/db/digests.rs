@@ -0,0 +1,148 @@
+use super::models::{CreateDigestInput, Digest, DigestPeriod, GenerationProvenance};
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Result as SqlResult, Row};
+
+const DIGEST_COLUMNS: &str =
+    "id, period_type, start_date, end_date, stats, summary, provider, model, prompt_version, temperature, created_at";
+
+fn digest_from_row(row: &Row) -> SqlResult<Digest> {
+    Ok(Digest {
+        id: Some(row.get(0)?),
+        period_type: DigestPeriod::parse(&row.get::<_, String>(1)?).unwrap_or(DigestPeriod::Custom),
+        start_date: row.get::<_, String>(2)?.parse().unwrap(),
+        end_date: row.get::<_, String>(3)?.parse().unwrap(),
+        stats: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+        summary: row.get(5)?,
+        provenance: GenerationProvenance {
+            provider: row.get(6)?,
+            model: row.get(7)?,
+            prompt_version: row.get(8)?,
+            temperature: row.get(9)?,
+        },
+        created_at: row.get::<_, String>(10)?.parse().unwrap(),
+    })
+}
+
+impl Database {
+    pub fn create_digest(&self, input: CreateDigestInput) -> SqlResult<Digest> {
+        let conn = self.get_connection();
+        let now = Utc::now();
+        let stats = serde_json::to_string(&input.stats)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+
+        conn.execute(
+            "INSERT INTO digests
+             (period_type, start_date, end_date, stats, summary, provider, model, prompt_version, temperature, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                input.period_type.as_str(),
+                input.start_date.to_rfc3339(),
+                input.end_date.to_rfc3339(),
+                stats,
+                input.summary,
+                input.provenance.provider,
+                input.provenance.model,
+                input.provenance.prompt_version,
+                input.provenance.temperature,
+                now.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(Digest {
+            id: Some(conn.last_insert_rowid()),
+            period_type: input.period_type,
+            start_date: input.start_date,
+            end_date: input.end_date,
+            stats: input.stats,
+            summary: input.summary,
+            provenance: input.provenance,
+            created_at: now,
+        })
+    }
+
+    pub fn get_digest(&self, id: i64) -> SqlResult<Option<Digest>> {
+        let conn = self.get_connection();
+
+        let digest = conn.query_row(
+            &format!("SELECT {} FROM digests WHERE id = ?1", DIGEST_COLUMNS),
+            params![id],
+            digest_from_row,
+        );
+
+        match digest {
+            Ok(d) => Ok(Some(d)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Digests newest period first, optionally only one period type
+    pub fn list_digests(&self, period_type: Option<DigestPeriod>) -> SqlResult<Vec<Digest>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM digests WHERE ?1 IS NULL OR period_type = ?1 ORDER BY start_date DESC, created_at DESC",
+            DIGEST_COLUMNS
+        ))?;
+
+        let digests = stmt
+            .query_map(params![period_type.map(|p| p.as_str())], digest_from_row)?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(digests)
+    }
+
+    pub fn delete_digest(&self, id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+        let rows_affected = conn.execute("DELETE FROM digests WHERE id = ?1", params![id])?;
+        Ok(rows_affected > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{CreateDigestInput, DigestPeriod, DigestStats, GenerationProvenance, TagCount};
+    use crate::db::test_support::setup_test_db;
+    use chrono::{Duration, Utc};
+
+    fn digest_input(period_type: DigestPeriod, weeks_ago: i64) -> CreateDigestInput {
+        let start_date = Utc::now() - Duration::weeks(weeks_ago);
+        CreateDigestInput {
+            period_type,
+            start_date,
+            end_date: start_date + Duration::weeks(1),
+            stats: DigestStats {
+                dream_count: 3,
+                top_emotions: vec![TagCount { name: "fear".to_string(), count: 2 }],
+                ..Default::default()
+            },
+            summary: "A restless week".to_string(),
+            provenance: GenerationProvenance::default(),
+        }
+    }
+
+    #[test]
+    fn test_digest_round_trip_and_listing() {
+        let db = setup_test_db();
+        let older = db.create_digest(digest_input(DigestPeriod::Week, 2)).unwrap();
+        let newer = db.create_digest(digest_input(DigestPeriod::Week, 1)).unwrap();
+        db.create_digest(digest_input(DigestPeriod::Month, 0)).unwrap();
+
+        let stored = db.get_digest(older.id.unwrap()).unwrap().unwrap();
+        assert_eq!(stored.stats, older.stats);
+        assert_eq!(stored.summary, "A restless week");
+
+        let weekly: Vec<i64> = db
+            .list_digests(Some(DigestPeriod::Week))
+            .unwrap()
+            .into_iter()
+            .filter_map(|d| d.id)
+            .collect();
+        assert_eq!(weekly, vec![newer.id.unwrap(), older.id.unwrap()]);
+        assert_eq!(db.list_digests(None).unwrap().len(), 3);
+
+        assert!(db.delete_digest(older.id.unwrap()).unwrap());
+        assert!(db.get_digest(older.id.unwrap()).unwrap().is_none());
+    }
+}
@@ -1,44 +1,95 @@
-use super::models::{CreateDreamAnalysisInput, DreamAnalysis, DreamAnalysisCard, DreamAnalysisWithCards};
+use super::models::{
+    AnalysisCardInput, CreateDreamAnalysisInput, DreamAnalysis, DreamAnalysisCard, DreamAnalysisWithCards,
+    GenerationProvenance,
+};
 use super::Database;
 use chrono::Utc;
-use rusqlite::{params, Result as SqlResult};
+use rusqlite::{params, Result as SqlResult, Row};
+use std::collections::HashMap;
+
+const DREAM_ANALYSIS_COLUMNS: &str =
+    "id, dream_id, themes_patterns, emotional_analysis, narrative_summary, provider, model, prompt_version, temperature, is_current, is_pinned, created_at, updated_at";
+
+fn dream_analysis_from_row(row: &Row) -> SqlResult<DreamAnalysis> {
+    Ok(DreamAnalysis {
+        id: Some(row.get(0)?),
+        dream_id: row.get(1)?,
+        themes_patterns: row.get(2)?,
+        emotional_analysis: row.get(3)?,
+        narrative_summary: row.get(4)?,
+        provenance: GenerationProvenance {
+            provider: row.get(5)?,
+            model: row.get(6)?,
+            prompt_version: row.get(7)?,
+            temperature: row.get(8)?,
+        },
+        is_current: row.get::<_, i32>(9)? != 0,
+        is_pinned: row.get::<_, i32>(10)? != 0,
+        created_at: row.get::<_, String>(11)?.parse().unwrap(),
+        updated_at: row.get::<_, String>(12)?.parse().unwrap(),
+    })
+}
 
 impl Database {
+    /// Add a new analysis version for a dream.
+    /// The new version becomes current unless the user has pinned another one.
     pub fn create_dream_analysis(&self, input: CreateDreamAnalysisInput) -> SqlResult<DreamAnalysis> {
+        self.create_dream_analysis_with_cards(input, &[])
+    }
+
+    /// Add a new analysis version and link its cards in one transaction, so a
+    /// failure never leaves a current version with only some of its cards
+    pub fn create_dream_analysis_with_cards(
+        &self,
+        input: CreateDreamAnalysisInput,
+        cards: &[AnalysisCardInput],
+    ) -> SqlResult<DreamAnalysis> {
         let now = Utc::now();
 
-        // Get connection once and do all operations within this scope
-        let conn = self.get_connection();
+        let mut conn = self.get_connection();
+        let tx = conn.transaction()?;
 
-        // Check if analysis already exists for this dream (inline query to avoid nested lock)
-        let existing_count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM dream_analyses WHERE dream_id = ?1",
+        let has_pinned: bool = tx.query_row(
+            "SELECT COUNT(*) FROM dream_analyses WHERE dream_id = ?1 AND is_pinned = 1",
             params![input.dream_id],
-            |row| row.get(0),
-        )?;
+            |row| row.get::<_, i64>(0),
+        )? > 0;
 
-        if existing_count > 0 {
-            // Delete existing analysis before creating new one
-            conn.execute(
-                "DELETE FROM dream_analyses WHERE dream_id = ?1",
+        if !has_pinned {
+            tx.execute(
+                "UPDATE dream_analyses SET is_current = 0 WHERE dream_id = ?1",
                 params![input.dream_id],
             )?;
         }
 
-        conn.execute(
-            "INSERT INTO dream_analyses (dream_id, themes_patterns, emotional_analysis, narrative_summary, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        tx.execute(
+            "INSERT INTO dream_analyses (dream_id, themes_patterns, emotional_analysis, narrative_summary, provider, model, prompt_version, temperature, is_current, is_pinned, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 0, ?10, ?11)",
             params![
                 input.dream_id,
                 input.themes_patterns,
                 input.emotional_analysis,
                 input.narrative_summary,
+                input.provenance.provider,
+                input.provenance.model,
+                input.provenance.prompt_version,
+                input.provenance.temperature,
+                !has_pinned,
                 now.to_rfc3339(),
                 now.to_rfc3339(),
             ],
         )?;
 
-        let id = conn.last_insert_rowid();
+        let id = tx.last_insert_rowid();
+
+        for card in cards {
+            tx.execute(
+                "INSERT OR REPLACE INTO dream_analysis_cards (dream_analysis_id, card_id, relevance_note, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, card.card_id, card.relevance_note, now.to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
 
         Ok(DreamAnalysis {
             id: Some(id),
@@ -46,30 +97,42 @@ impl Database {
             themes_patterns: input.themes_patterns,
             emotional_analysis: input.emotional_analysis,
             narrative_summary: input.narrative_summary,
+            provenance: input.provenance,
+            is_current: !has_pinned,
+            is_pinned: false,
             created_at: now,
             updated_at: now,
         })
     }
 
+    /// The current analysis version for a dream
     pub fn get_dream_analysis(&self, dream_id: i64) -> SqlResult<Option<DreamAnalysis>> {
         let conn = self.get_connection();
 
-        let mut stmt = conn.prepare(
-            "SELECT id, dream_id, themes_patterns, emotional_analysis, narrative_summary, created_at, updated_at
-             FROM dream_analyses WHERE dream_id = ?1",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM dream_analyses WHERE dream_id = ?1 AND is_current = 1",
+            DREAM_ANALYSIS_COLUMNS
+        ))?;
+
+        let analysis = stmt.query_row(params![dream_id], dream_analysis_from_row);
+
+        match analysis {
+            Ok(a) => Ok(Some(a)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
-        let analysis = stmt.query_row(params![dream_id], |row| {
-            Ok(DreamAnalysis {
-                id: Some(row.get(0)?),
-                dream_id: row.get(1)?,
-                themes_patterns: row.get(2)?,
-                emotional_analysis: row.get(3)?,
-                narrative_summary: row.get(4)?,
-                created_at: row.get::<_, String>(5)?.parse().unwrap(),
-                updated_at: row.get::<_, String>(6)?.parse().unwrap(),
-            })
-        });
+    /// A specific analysis version by its id
+    pub fn get_dream_analysis_by_id(&self, analysis_id: i64) -> SqlResult<Option<DreamAnalysis>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM dream_analyses WHERE id = ?1",
+            DREAM_ANALYSIS_COLUMNS
+        ))?;
+
+        let analysis = stmt.query_row(params![analysis_id], dream_analysis_from_row);
 
         match analysis {
             Ok(a) => Ok(Some(a)),
@@ -78,6 +141,87 @@ impl Database {
         }
     }
 
+    /// Every analysis version of a dream, newest first
+    pub fn list_dream_analysis_versions(&self, dream_id: i64) -> SqlResult<Vec<DreamAnalysis>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM dream_analyses WHERE dream_id = ?1 ORDER BY created_at DESC, id DESC",
+            DREAM_ANALYSIS_COLUMNS
+        ))?;
+
+        let analyses = stmt
+            .query_map(params![dream_id], dream_analysis_from_row)?
+            .collect::<SqlResult<Vec<DreamAnalysis>>>()?;
+
+        Ok(analyses)
+    }
+
+    /// List current analyses that were not produced by the given provider, model and prompt version.
+    /// Analyses created before provenance was recorded are always included; pinned ones never are.
+    pub fn list_outdated_dream_analyses(
+        &self,
+        provider: &str,
+        model: &str,
+        prompt_version: &str,
+    ) -> SqlResult<Vec<DreamAnalysis>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM dream_analyses
+             WHERE is_current = 1 AND is_pinned = 0
+               AND (provider IS NULL OR model IS NULL OR prompt_version IS NULL
+                    OR provider != ?1 OR model != ?2 OR prompt_version != ?3)
+             ORDER BY created_at ASC",
+            DREAM_ANALYSIS_COLUMNS
+        ))?;
+
+        let analyses = stmt
+            .query_map(params![provider, model, prompt_version], dream_analysis_from_row)?
+            .collect::<SqlResult<Vec<DreamAnalysis>>>()?;
+
+        Ok(analyses)
+    }
+
+    /// Pin an analysis version, making it the current one for its dream.
+    /// Any other pinned version of the same dream is unpinned.
+    pub fn pin_dream_analysis(&self, analysis_id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+
+        let dream_id: i64 = match conn.query_row(
+            "SELECT dream_id FROM dream_analyses WHERE id = ?1",
+            params![analysis_id],
+            |row| row.get(0),
+        ) {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        conn.execute(
+            "UPDATE dream_analyses
+             SET is_current = (id = ?1), is_pinned = (id = ?1)
+             WHERE dream_id = ?2",
+            params![analysis_id, dream_id],
+        )?;
+
+        Ok(true)
+    }
+
+    /// Remove the pin from a dream's analyses. The current version stays current
+    /// until a new one is generated.
+    pub fn unpin_dream_analysis(&self, dream_id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+
+        let rows_affected = conn.execute(
+            "UPDATE dream_analyses SET is_pinned = 0 WHERE dream_id = ?1 AND is_pinned = 1",
+            params![dream_id],
+        )?;
+
+        Ok(rows_affected > 0)
+    }
+
+    /// Delete every analysis version of a dream
     pub fn delete_dream_analysis(&self, dream_id: i64) -> SqlResult<bool> {
         let conn = self.get_connection();
 
@@ -89,6 +233,40 @@ impl Database {
         Ok(rows_affected > 0)
     }
 
+    /// Delete one analysis version. If it was current, the newest remaining version takes over.
+    pub fn delete_dream_analysis_version(&self, analysis_id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+
+        let dream_id: i64 = match conn.query_row(
+            "SELECT dream_id FROM dream_analyses WHERE id = ?1",
+            params![analysis_id],
+            |row| row.get(0),
+        ) {
+            Ok(id) => id,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        conn.execute(
+            "DELETE FROM dream_analysis_cards WHERE dream_analysis_id = ?1",
+            params![analysis_id],
+        )?;
+        conn.execute(
+            "DELETE FROM dream_creative_prompts WHERE dream_analysis_id = ?1",
+            params![analysis_id],
+        )?;
+        conn.execute("DELETE FROM dream_analyses WHERE id = ?1", params![analysis_id])?;
+
+        conn.execute(
+            "UPDATE dream_analyses SET is_current = 1
+             WHERE id = (SELECT id FROM dream_analyses WHERE dream_id = ?1 ORDER BY created_at DESC, id DESC LIMIT 1)
+               AND NOT EXISTS (SELECT 1 FROM dream_analyses WHERE dream_id = ?1 AND is_current = 1)",
+            params![dream_id],
+        )?;
+
+        Ok(true)
+    }
+
     pub fn link_card_to_dream_analysis(
         &self,
         dream_analysis_id: i64,
@@ -125,6 +303,31 @@ impl Database {
         }
     }
 
+    pub fn get_dream_analysis_version_with_cards(&self, analysis_id: i64) -> SqlResult<Option<DreamAnalysisWithCards>> {
+        let analysis = self.get_dream_analysis_by_id(analysis_id)?;
+
+        if let Some(analysis) = analysis {
+            let cards = self.get_dream_analysis_cards(analysis_id)?;
+
+            Ok(Some(DreamAnalysisWithCards { analysis, cards }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Every analysis version of a dream with its cards, newest first, for side-by-side comparison
+    pub fn list_dream_analysis_versions_with_cards(&self, dream_id: i64) -> SqlResult<Vec<DreamAnalysisWithCards>> {
+        let analyses = self.list_dream_analysis_versions(dream_id)?;
+
+        let mut versions = Vec::with_capacity(analyses.len());
+        for analysis in analyses {
+            let cards = self.get_dream_analysis_cards(analysis.id.unwrap_or(0))?;
+            versions.push(DreamAnalysisWithCards { analysis, cards });
+        }
+
+        Ok(versions)
+    }
+
     pub fn get_dream_analysis_cards(&self, dream_analysis_id: i64) -> SqlResult<Vec<DreamAnalysisCard>> {
         let conn = self.get_connection();
 
@@ -161,4 +364,187 @@ impl Database {
 
         Ok(())
     }
+
+    /// Card names in each dream's current analysis, keyed by dream id
+    pub fn current_analysis_card_names(&self) -> SqlResult<HashMap<i64, Vec<String>>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT da.dream_id, c.name
+             FROM dream_analyses da
+             JOIN dream_analysis_cards dac ON dac.dream_analysis_id = da.id
+             JOIN cards c ON dac.card_id = c.id
+             WHERE da.is_current = 1
+             ORDER BY da.dream_id, dac.created_at ASC",
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        let mut cards: HashMap<i64, Vec<String>> = HashMap::new();
+        for (dream_id, name) in rows {
+            cards.entry(dream_id).or_default().push(name);
+        }
+
+        Ok(cards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{create_card, setup_test_db};
+
+    fn create_dream(db: &Database, title: &str) -> i64 {
+        crate::db::test_support::create_dream(db, title, "Content").id.unwrap()
+    }
+
+    fn analysis_input(dream_id: i64, provenance: GenerationProvenance) -> CreateDreamAnalysisInput {
+        CreateDreamAnalysisInput {
+            dream_id,
+            themes_patterns: "Themes".to_string(),
+            emotional_analysis: "Emotions".to_string(),
+            narrative_summary: "Narrative".to_string(),
+            provenance,
+        }
+    }
+
+    fn provenance(model: &str, prompt_version: &str) -> GenerationProvenance {
+        GenerationProvenance {
+            provider: Some("ollama".to_string()),
+            model: Some(model.to_string()),
+            prompt_version: Some(prompt_version.to_string()),
+            temperature: Some(0.7),
+        }
+    }
+
+    #[test]
+    fn test_provenance_is_stored_and_returned() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "Dream");
+
+        db.create_dream_analysis(analysis_input(dream_id, provenance("llama3.2", "dream_analysis@v1")))
+            .unwrap();
+
+        let stored = db.get_dream_analysis_with_cards(dream_id).unwrap().unwrap();
+        assert_eq!(stored.analysis.provenance, provenance("llama3.2", "dream_analysis@v1"));
+    }
+
+    #[test]
+    fn test_list_outdated_dream_analyses() {
+        let db = setup_test_db();
+        let current = create_dream(&db, "Current");
+        let old_model = create_dream(&db, "Old model");
+        let old_prompt = create_dream(&db, "Old prompt");
+        let unknown = create_dream(&db, "Unknown");
+
+        db.create_dream_analysis(analysis_input(current, provenance("llama3.2", "dream_analysis@v1"))).unwrap();
+        db.create_dream_analysis(analysis_input(old_model, provenance("mistral", "dream_analysis@v1"))).unwrap();
+        db.create_dream_analysis(analysis_input(old_prompt, provenance("llama3.2", "dream_analysis@custom-1234abcd"))).unwrap();
+        db.create_dream_analysis(analysis_input(unknown, GenerationProvenance::default())).unwrap();
+
+        let outdated: Vec<i64> = db
+            .list_outdated_dream_analyses("ollama", "llama3.2", "dream_analysis@v1")
+            .unwrap()
+            .into_iter()
+            .map(|a| a.dream_id)
+            .collect();
+
+        assert_eq!(outdated, vec![old_model, old_prompt, unknown]);
+    }
+
+    #[test]
+    fn test_new_versions_become_current() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "Dream");
+
+        let first = db.create_dream_analysis(analysis_input(dream_id, provenance("mistral", "dream_analysis@v1"))).unwrap();
+        let second = db.create_dream_analysis(analysis_input(dream_id, provenance("llama3.2", "dream_analysis@v1"))).unwrap();
+
+        let versions = db.list_dream_analysis_versions(dream_id).unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].id, second.id);
+        assert!(versions[0].is_current);
+        assert!(!versions[1].is_current);
+
+        let current = db.get_dream_analysis(dream_id).unwrap().unwrap();
+        assert_eq!(current.id, second.id);
+        assert!(db.get_dream_analysis_by_id(first.id.unwrap()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_pinned_version_stays_current() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "Dream");
+
+        let first = db.create_dream_analysis(analysis_input(dream_id, provenance("mistral", "dream_analysis@v1"))).unwrap();
+        db.create_dream_analysis(analysis_input(dream_id, provenance("llama3.2", "dream_analysis@v1"))).unwrap();
+
+        assert!(db.pin_dream_analysis(first.id.unwrap()).unwrap());
+        let third = db.create_dream_analysis(analysis_input(dream_id, provenance("phi3", "dream_analysis@v1"))).unwrap();
+        assert!(!third.is_current);
+
+        let current = db.get_dream_analysis(dream_id).unwrap().unwrap();
+        assert_eq!(current.id, first.id);
+        assert!(current.is_pinned);
+
+        // Pinned analyses are the user's choice and never reported as outdated
+        assert!(db.list_outdated_dream_analyses("ollama", "phi3", "dream_analysis@v1").unwrap().is_empty());
+
+        assert!(db.unpin_dream_analysis(dream_id).unwrap());
+        let fourth = db.create_dream_analysis(analysis_input(dream_id, provenance("phi3", "dream_analysis@v1"))).unwrap();
+        assert_eq!(db.get_dream_analysis(dream_id).unwrap().unwrap().id, fourth.id);
+    }
+
+    #[test]
+    fn test_deleting_current_version_promotes_newest() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "Dream");
+
+        let first = db.create_dream_analysis(analysis_input(dream_id, provenance("mistral", "dream_analysis@v1"))).unwrap();
+        let second = db.create_dream_analysis(analysis_input(dream_id, provenance("llama3.2", "dream_analysis@v1"))).unwrap();
+
+        assert!(db.delete_dream_analysis_version(second.id.unwrap()).unwrap());
+        assert_eq!(db.get_dream_analysis(dream_id).unwrap().unwrap().id, first.id);
+        assert!(!db.delete_dream_analysis_version(second.id.unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_current_analysis_card_names_ignores_old_versions() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "Dream");
+        let fool = create_card(&db, "The Fool");
+        let tower = create_card(&db, "The Tower");
+
+        let old = db.create_dream_analysis(analysis_input(dream_id, GenerationProvenance::default())).unwrap();
+        db.link_card_to_dream_analysis(old.id.unwrap(), fool, None).unwrap();
+        let current = db.create_dream_analysis(analysis_input(dream_id, GenerationProvenance::default())).unwrap();
+        db.link_card_to_dream_analysis(current.id.unwrap(), tower, None).unwrap();
+
+        let cards = db.current_analysis_card_names().unwrap();
+        assert_eq!(cards.get(&dream_id), Some(&vec!["The Tower".to_string()]));
+    }
+
+    #[test]
+    fn test_version_with_cards_is_all_or_nothing() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "Dream");
+        let fool = create_card(&db, "The Fool");
+        let first = db.create_dream_analysis(analysis_input(dream_id, GenerationProvenance::default())).unwrap();
+
+        let card = |card_id| AnalysisCardInput { card_id, relevance_note: None };
+        assert!(db
+            .create_dream_analysis_with_cards(analysis_input(dream_id, GenerationProvenance::default()), &[card(fool), card(999)])
+            .is_err());
+        assert_eq!(db.get_dream_analysis(dream_id).unwrap().unwrap().id, first.id);
+        assert_eq!(db.list_dream_analysis_versions(dream_id).unwrap().len(), 1);
+
+        let second = db
+            .create_dream_analysis_with_cards(analysis_input(dream_id, GenerationProvenance::default()), &[card(fool)])
+            .unwrap();
+        let current = db.get_dream_analysis_with_cards(dream_id).unwrap().unwrap();
+        assert_eq!(current.analysis.id, second.id);
+        assert_eq!(current.cards.len(), 1);
+    }
 }
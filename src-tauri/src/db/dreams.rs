@@ -1,4 +1,4 @@
-use super::models::{CreateDreamInput, Dream, UpdateDreamInput};
+use super::models::{CreateDreamInput, Dream, EntryType, UpdateDreamInput};
 use super::Database;
 use chrono::Utc;
 use rusqlite::{params, Result as SqlResult};
@@ -77,15 +77,23 @@ impl Database {
     }
 
     pub fn list_dreams(&self, limit: Option<i64>, offset: Option<i64>) -> SqlResult<Vec<Dream>> {
+        self.select_dreams(&format!("LIMIT {} OFFSET {}", limit.unwrap_or(100), offset.unwrap_or(0)))
+    }
+
+    /// Every one of the dreams, newest first, for work that scans the whole journal
+    pub fn list_all_dreams(&self) -> SqlResult<Vec<Dream>> {
+        self.select_dreams("")
+    }
+
+    fn select_dreams(&self, paging: &str) -> SqlResult<Vec<Dream>> {
         let conn = self.get_connection();
 
         let query = format!(
             "SELECT id, date_recorded, date_occurred, title, content, emotions_tags, sleep_quality, is_recurring, last_occurrence_period, is_lucid, created_at, updated_at
              FROM dreams
              ORDER BY date_occurred DESC
-             LIMIT {} OFFSET {}",
-            limit.unwrap_or(100),
-            offset.unwrap_or(0)
+             {}",
+            paging
         );
 
         let mut stmt = conn.prepare(&query)?;
@@ -113,8 +121,6 @@ impl Database {
     }
 
     pub fn update_dream(&self, input: UpdateDreamInput) -> SqlResult<Option<Dream>> {
-        let conn = self.get_connection();
-
         // First, get the existing dream
         let existing = self.get_dream(input.id)?;
         if existing.is_none() {
@@ -123,6 +129,7 @@ impl Database {
 
         let mut existing = existing.unwrap();
         let now = Utc::now();
+        let conn = self.get_connection();
 
         // Update only provided fields
         if let Some(date_occurred) = input.date_occurred {
@@ -174,9 +181,13 @@ impl Database {
     }
 
     pub fn delete_dream(&self, id: i64) -> SqlResult<bool> {
-        let conn = self.get_connection();
+        let mut conn = self.get_connection();
 
-        let rows_affected = conn.execute("DELETE FROM dreams WHERE id = ?1", params![id])?;
+        let tx = conn.transaction()?;
+        let rows_affected = tx.execute("DELETE FROM dreams WHERE id = ?1", params![id])?;
+        super::embeddings::delete_embedding_rows(&tx, EntryType::Dream, id)?;
+        super::entry_links::delete_links_for(&tx, EntryType::Dream, id)?;
+        tx.commit()?;
 
         Ok(rows_affected > 0)
     }
@@ -219,19 +230,8 @@ impl Database {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::super::Database;
-    use rusqlite::Connection;
+    use crate::db::test_support::{create_dream, setup_test_db};
     use chrono::Utc;
-    use std::sync::Mutex;
-
-    fn setup_test_db() -> Database {
-        // Use in-memory database for testing
-        let conn = Connection::open_in_memory().unwrap();
-        crate::db::migrations::run_migrations(&conn).unwrap();
-        
-        // Create Database with in-memory connection using test-only constructor
-        Database::from_connection(conn)
-    }
 
     #[test]
     fn test_create_dream_with_metadata() {
@@ -335,4 +335,15 @@ mod tests {
         assert_eq!(retrieved.last_occurrence_period, Some("last_week".to_string()));
         assert_eq!(retrieved.is_lucid, Some(true));
     }
+
+    #[test]
+    fn test_list_all_dreams_is_not_paged() {
+        let db = setup_test_db();
+        for i in 0..101 {
+            create_dream(&db, &format!("Dream {}", i), "Content");
+        }
+
+        assert_eq!(db.list_dreams(None, None).unwrap().len(), 100);
+        assert_eq!(db.list_all_dreams().unwrap().len(), 101);
+    }
 }
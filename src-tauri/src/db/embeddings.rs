@@ -0,0 +1,153 @@
+use super::models::{EntryType, StoredEmbedding};
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+
+/// Remove an entry's stored vector; called when the entry is deleted
+pub(super) fn delete_embedding_rows(conn: &Connection, entity_type: EntryType, entity_id: i64) -> SqlResult<usize> {
+    conn.execute(
+        "DELETE FROM embeddings WHERE entity_type = ?1 AND entity_id = ?2",
+        params![entity_type.as_str(), entity_id],
+    )
+}
+
+impl Database {
+    /// Store the vector for an entry, replacing any earlier one
+    pub fn upsert_embedding(&self, embedding: &StoredEmbedding) -> SqlResult<()> {
+        let conn = self.get_connection();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO embeddings
+             (entity_type, entity_id, provider, model, content_hash, dimensions, vector, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                embedding.entity_type.as_str(),
+                embedding.entity_id,
+                embedding.provider,
+                embedding.model,
+                embedding.content_hash,
+                embedding.vector.len() as i64,
+                encode_vector(&embedding.vector),
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_embedding(&self, entity_type: EntryType, entity_id: i64) -> SqlResult<Option<StoredEmbedding>> {
+        let conn = self.get_connection();
+
+        let result = conn.query_row(
+            "SELECT entity_type, entity_id, provider, model, content_hash, vector
+             FROM embeddings
+             WHERE entity_type = ?1 AND entity_id = ?2",
+            params![entity_type.as_str(), entity_id],
+            row_to_embedding,
+        );
+
+        match result {
+            Ok(embedding) => Ok(embedding),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Every vector produced by one provider and model; vectors from other models are not comparable
+    pub fn list_embeddings(&self, provider: &str, model: &str) -> SqlResult<Vec<StoredEmbedding>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT entity_type, entity_id, provider, model, content_hash, vector
+             FROM embeddings
+             WHERE provider = ?1 AND model = ?2",
+        )?;
+
+        let embeddings = stmt
+            .query_map(params![provider, model], row_to_embedding)?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(embeddings.into_iter().flatten().collect())
+    }
+
+    pub fn delete_embedding(&self, entity_type: EntryType, entity_id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+
+        let rows_affected = delete_embedding_rows(&conn, entity_type, entity_id)?;
+
+        Ok(rows_affected > 0)
+    }
+}
+
+/// None for rows with an entity type this version does not know
+fn row_to_embedding(row: &Row) -> SqlResult<Option<StoredEmbedding>> {
+    let Some(entity_type) = EntryType::parse(&row.get::<_, String>(0)?) else {
+        return Ok(None);
+    };
+
+    Ok(Some(StoredEmbedding {
+        entity_type,
+        entity_id: row.get(1)?,
+        provider: row.get(2)?,
+        model: row.get(3)?,
+        content_hash: row.get(4)?,
+        vector: decode_vector(&row.get::<_, Vec<u8>>(5)?),
+    }))
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{EntryType, StoredEmbedding};
+    use crate::db::test_support::{create_dream, setup_test_db};
+
+    fn embedding(entity_type: EntryType, entity_id: i64, model: &str) -> StoredEmbedding {
+        StoredEmbedding {
+            entity_type,
+            entity_id,
+            provider: "ollama".to_string(),
+            model: model.to_string(),
+            content_hash: "abc".to_string(),
+            vector: vec![0.5, -1.25, 3.0],
+        }
+    }
+
+    #[test]
+    fn test_embedding_round_trip_and_model_filter() {
+        let db = setup_test_db();
+
+        db.upsert_embedding(&embedding(EntryType::Dream, 1, "nomic-embed-text")).unwrap();
+        db.upsert_embedding(&embedding(EntryType::Bug, 1, "nomic-embed-text")).unwrap();
+        db.upsert_embedding(&embedding(EntryType::MindDump, 2, "other-model")).unwrap();
+
+        let stored = db.get_embedding(EntryType::Dream, 1).unwrap().unwrap();
+        assert_eq!(stored.vector, vec![0.5, -1.25, 3.0]);
+        assert!(db.get_embedding(EntryType::MindDump, 1).unwrap().is_none());
+
+        assert_eq!(db.list_embeddings("ollama", "nomic-embed-text").unwrap().len(), 2);
+
+        assert!(db.delete_embedding(EntryType::Bug, 1).unwrap());
+        assert_eq!(db.list_embeddings("ollama", "nomic-embed-text").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_deleting_entry_removes_its_embedding() {
+        let db = setup_test_db();
+        let id = create_dream(&db, "Chase", "Running from a shadow").id.unwrap();
+
+        db.upsert_embedding(&embedding(EntryType::Dream, id, "nomic-embed-text")).unwrap();
+        db.delete_dream(id).unwrap();
+
+        assert!(db.get_embedding(EntryType::Dream, id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,237 @@
+use super::models::{EntryLink, EntryRelation, EntryType, LinkDirection, LinkedEntry};
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult, Row};
+
+const ENTRY_LINK_COLUMNS: &str = "id, source_type, source_id, target_type, target_id, relation, created_at";
+
+/// None for rows with an entry type or relation this version does not know
+fn entry_link_from_row(row: &Row) -> SqlResult<Option<EntryLink>> {
+    let (Some(source_type), Some(target_type), Some(relation)) = (
+        EntryType::parse(&row.get::<_, String>(1)?),
+        EntryType::parse(&row.get::<_, String>(3)?),
+        EntryRelation::parse(&row.get::<_, String>(5)?),
+    ) else {
+        return Ok(None);
+    };
+
+    Ok(Some(EntryLink {
+        id: Some(row.get(0)?),
+        source_type,
+        source_id: row.get(2)?,
+        target_type,
+        target_id: row.get(4)?,
+        relation,
+        created_at: row.get::<_, String>(6)?.parse().unwrap(),
+    }))
+}
+
+/// Title shown for an entry at the other end of a link; untitled mind dumps use their opening text
+fn entry_title(conn: &Connection, entity_type: EntryType, entity_id: i64) -> SqlResult<Option<String>> {
+    let sql = match entity_type {
+        EntryType::Dream => "SELECT title FROM dreams WHERE id = ?1",
+        EntryType::MindDump => "SELECT COALESCE(NULLIF(title, ''), substr(content, 1, 60)) FROM mind_dumps WHERE id = ?1",
+        EntryType::Bug => "SELECT title FROM bugs WHERE id = ?1",
+    };
+
+    conn.query_row(sql, params![entity_id], |row| row.get(0)).optional()
+}
+
+/// Remove every link to or from an entry; called when the entry is deleted
+pub(super) fn delete_links_for(conn: &Connection, entity_type: EntryType, entity_id: i64) -> SqlResult<()> {
+    conn.execute(
+        "DELETE FROM entry_links
+         WHERE (source_type = ?1 AND source_id = ?2) OR (target_type = ?1 AND target_id = ?2)",
+        params![entity_type.as_str(), entity_id],
+    )?;
+    Ok(())
+}
+
+/// `create_entry_link` on a connection the caller holds, so it can join a transaction
+pub(super) fn insert_entry_link(
+    conn: &Connection,
+    source_type: EntryType,
+    source_id: i64,
+    target_type: EntryType,
+    target_id: i64,
+    relation: EntryRelation,
+) -> SqlResult<EntryLink> {
+    conn.execute(
+        "INSERT OR IGNORE INTO entry_links (source_type, source_id, target_type, target_id, relation, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            source_type.as_str(),
+            source_id,
+            target_type.as_str(),
+            target_id,
+            relation.as_str(),
+            Utc::now().to_rfc3339(),
+        ],
+    )?;
+
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM entry_links
+             WHERE source_type = ?1 AND source_id = ?2 AND target_type = ?3 AND target_id = ?4 AND relation = ?5",
+            ENTRY_LINK_COLUMNS
+        ),
+        params![source_type.as_str(), source_id, target_type.as_str(), target_id, relation.as_str()],
+        entry_link_from_row,
+    )
+    .map(|link| link.expect("link was written with known types"))
+}
+
+impl Database {
+    pub fn entry_exists(&self, entity_type: EntryType, entity_id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+        Ok(entry_title(&conn, entity_type, entity_id)?.is_some())
+    }
+
+    /// Link two entries; linking the same pair with the same relation again returns the existing link
+    pub fn create_entry_link(
+        &self,
+        source_type: EntryType,
+        source_id: i64,
+        target_type: EntryType,
+        target_id: i64,
+        relation: EntryRelation,
+    ) -> SqlResult<EntryLink> {
+        let conn = self.get_connection();
+        insert_entry_link(&conn, source_type, source_id, target_type, target_id, relation)
+    }
+
+    /// Links in either direction that involve the entry, oldest first
+    pub fn list_entry_links(&self, entity_type: EntryType, entity_id: i64) -> SqlResult<Vec<EntryLink>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM entry_links
+             WHERE (source_type = ?1 AND source_id = ?2) OR (target_type = ?1 AND target_id = ?2)
+             ORDER BY created_at ASC, id ASC",
+            ENTRY_LINK_COLUMNS
+        ))?;
+
+        let links = stmt
+            .query_map(params![entity_type.as_str(), entity_id], entry_link_from_row)?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(links.into_iter().flatten().collect())
+    }
+
+    /// The entry's links in both directions, each with the entry at the other end
+    pub fn list_linked_entries(&self, entity_type: EntryType, entity_id: i64) -> SqlResult<Vec<LinkedEntry>> {
+        let links = self.list_entry_links(entity_type, entity_id)?;
+        let conn = self.get_connection();
+
+        let mut linked = Vec::with_capacity(links.len());
+        for link in links {
+            let outgoing = link.source_type == entity_type && link.source_id == entity_id;
+            let (direction, other_type, other_id) = if outgoing {
+                (LinkDirection::Outgoing, link.target_type, link.target_id)
+            } else {
+                (LinkDirection::Incoming, link.source_type, link.source_id)
+            };
+
+            // Skip links left behind by entries deleted before links were cleaned up
+            let Some(title) = entry_title(&conn, other_type, other_id)? else {
+                continue;
+            };
+
+            linked.push(LinkedEntry {
+                link_id: link.id.expect("stored link has an id"),
+                relation: link.relation,
+                direction,
+                entry_type: other_type,
+                entry_id: other_id,
+                title,
+                created_at: link.created_at,
+            });
+        }
+
+        Ok(linked)
+    }
+
+    pub fn delete_entry_link(&self, id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+        let rows_affected = conn.execute("DELETE FROM entry_links WHERE id = ?1", params![id])?;
+        Ok(rows_affected > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{CreateBugInput, EntryRelation, EntryType, LinkDirection};
+    use crate::db::test_support::{create_dream, setup_test_db};
+
+    #[test]
+    fn test_links_are_unique_and_listed_from_both_ends() {
+        let db = setup_test_db();
+
+        let link = db
+            .create_entry_link(EntryType::Bug, 1, EntryType::Dream, 7, EntryRelation::PromotedFrom)
+            .unwrap();
+        let again = db
+            .create_entry_link(EntryType::Bug, 1, EntryType::Dream, 7, EntryRelation::PromotedFrom)
+            .unwrap();
+        assert_eq!(link.id, again.id);
+
+        assert_eq!(db.list_entry_links(EntryType::Bug, 1).unwrap(), vec![link.clone()]);
+        assert_eq!(db.list_entry_links(EntryType::Dream, 7).unwrap(), vec![link]);
+        assert!(db.list_entry_links(EntryType::Dream, 1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_linked_entries_show_backlinks_and_are_removed_with_the_entry() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "The flood", "Water rose through the floor").id.unwrap();
+        let bug_id = db
+            .create_bug(CreateBugInput {
+                title: "Ignoring the leak".to_string(),
+                description: String::new(),
+                cards_drawn: None,
+                conversation_history: None,
+                notes: None,
+                life_area_id: None,
+            })
+            .unwrap()
+            .id
+            .unwrap();
+        let link = db
+            .create_entry_link(EntryType::Bug, bug_id, EntryType::Dream, dream_id, EntryRelation::CausedBy)
+            .unwrap();
+
+        let from_bug = db.list_linked_entries(EntryType::Bug, bug_id).unwrap();
+        assert_eq!(from_bug.len(), 1);
+        assert_eq!(from_bug[0].direction, LinkDirection::Outgoing);
+        assert_eq!(from_bug[0].title, "The flood");
+
+        let from_dream = db.list_linked_entries(EntryType::Dream, dream_id).unwrap();
+        assert_eq!(from_dream[0].direction, LinkDirection::Incoming);
+        assert_eq!(from_dream[0].entry_type, EntryType::Bug);
+        assert_eq!(from_dream[0].relation, EntryRelation::CausedBy);
+
+        assert!(db.delete_dream(dream_id).unwrap());
+        assert!(db.list_entry_links(EntryType::Bug, bug_id).unwrap().is_empty());
+        assert!(!db.delete_entry_link(link.id.unwrap()).unwrap());
+    }
+    #[test]
+    fn test_bug_from_entry_is_all_or_nothing() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "The flood", "Water rose through the floor").id.unwrap();
+        let input = CreateBugInput {
+            title: "Ignoring the leak".to_string(),
+            description: String::new(),
+            cards_drawn: None,
+            conversation_history: None,
+            notes: None,
+            life_area_id: None,
+        };
+
+        assert!(db.create_bug_from_entry(input.clone(), EntryType::Dream, dream_id, &[999]).is_err());
+        assert!(db.list_bugs(None, None).unwrap().is_empty());
+        assert!(db.list_entry_links(EntryType::Dream, dream_id).unwrap().is_empty());
+
+        let (bug, link) = db.create_bug_from_entry(input, EntryType::Dream, dream_id, &[]).unwrap();
+        assert_eq!((link.source_id, link.relation), (bug.id.unwrap(), EntryRelation::PromotedFrom));
+    }
+}
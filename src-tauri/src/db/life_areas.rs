@@ -0,0 +1,190 @@
+use super::models::{CreateLifeAreaInput, LifeArea};
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Result as SqlResult, Row};
+
+const LIFE_AREA_COLUMNS: &str = "id, key, name, description, is_builtin, created_at";
+
+fn life_area_from_row(row: &Row) -> SqlResult<LifeArea> {
+    Ok(LifeArea {
+        id: Some(row.get(0)?),
+        key: row.get(1)?,
+        name: row.get(2)?,
+        description: row.get(3)?,
+        is_builtin: row.get(4)?,
+        created_at: row.get::<_, String>(5)?.parse().unwrap(),
+    })
+}
+
+/// Lowercase words joined by underscores, e.g. "Side Projects" -> "side_projects"
+fn life_area_key(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+impl Database {
+    /// Built-in areas first, then custom ones by name
+    pub fn list_life_areas(&self) -> SqlResult<Vec<LifeArea>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM life_areas ORDER BY is_builtin DESC, CASE WHEN is_builtin = 1 THEN id END, name COLLATE NOCASE",
+            LIFE_AREA_COLUMNS
+        ))?;
+
+        let areas = stmt
+            .query_map([], life_area_from_row)?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        Ok(areas)
+    }
+
+    pub fn get_life_area(&self, id: i64) -> SqlResult<Option<LifeArea>> {
+        self.find_life_area("id = ?1", params![id])
+    }
+
+    /// Look up an area by its key or, ignoring case, its name
+    pub fn get_life_area_by_key(&self, key: &str) -> SqlResult<Option<LifeArea>> {
+        self.find_life_area("key = ?1 OR name = ?1 COLLATE NOCASE", params![key])
+    }
+
+    fn find_life_area(&self, condition: &str, params: &[&dyn rusqlite::ToSql]) -> SqlResult<Option<LifeArea>> {
+        let conn = self.get_connection();
+
+        let area = conn.query_row(
+            &format!("SELECT {} FROM life_areas WHERE {} ORDER BY is_builtin DESC LIMIT 1", LIFE_AREA_COLUMNS, condition),
+            params,
+            life_area_from_row,
+        );
+
+        match area {
+            Ok(a) => Ok(Some(a)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Add a custom area; fails if its key clashes with an existing area.
+    /// The name must contain at least one letter or digit.
+    pub fn create_life_area(&self, input: CreateLifeAreaInput) -> SqlResult<LifeArea> {
+        let conn = self.get_connection();
+        let now = Utc::now();
+        let name = input.name.trim().to_string();
+        let key = life_area_key(&name);
+
+        conn.execute(
+            "INSERT INTO life_areas (key, name, description, is_builtin, created_at) VALUES (?1, ?2, ?3, 0, ?4)",
+            params![key, name, input.description, now.to_rfc3339()],
+        )?;
+
+        Ok(LifeArea {
+            id: Some(conn.last_insert_rowid()),
+            key,
+            name,
+            description: input.description,
+            is_builtin: false,
+            created_at: now,
+        })
+    }
+
+    /// Delete a custom area and clear it from its bugs; built-in areas are kept
+    pub fn delete_life_area(&self, id: i64) -> SqlResult<bool> {
+        let mut conn = self.get_connection();
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE bugs SET life_area_id = NULL
+             WHERE life_area_id IN (SELECT id FROM life_areas WHERE id = ?1 AND is_builtin = 0)",
+            params![id],
+        )?;
+        let rows_affected = tx.execute("DELETE FROM life_areas WHERE id = ?1 AND is_builtin = 0", params![id])?;
+        tx.commit()?;
+
+        Ok(rows_affected > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{CreateBugInput, CreateLifeAreaInput, UpdateBugInput};
+    use super::super::Database;
+    use crate::db::test_support::setup_test_db;
+
+    fn create_bug(db: &Database, life_area_id: Option<i64>) -> i64 {
+        db.create_bug(CreateBugInput {
+            title: "Bug".to_string(),
+            description: String::new(),
+            cards_drawn: None,
+            conversation_history: None,
+            notes: None,
+            life_area_id,
+        })
+        .unwrap()
+        .id
+        .unwrap()
+    }
+
+    #[test]
+    fn test_builtin_areas_are_seeded_and_kept() {
+        let db = setup_test_db();
+        let keys: Vec<String> = db.list_life_areas().unwrap().into_iter().map(|a| a.key).collect();
+        assert_eq!(keys, vec!["creative", "work", "life", "relationship"]);
+
+        let work = db.get_life_area_by_key("Work").unwrap().unwrap();
+        assert!(work.is_builtin);
+        assert!(!db.delete_life_area(work.id.unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_custom_area_filters_bugs_and_is_cleared_on_delete() {
+        let db = setup_test_db();
+        let area = db
+            .create_life_area(CreateLifeAreaInput { name: " Side Projects ".to_string(), description: None })
+            .unwrap();
+        assert_eq!(area.key, "side_projects");
+        assert!(db
+            .create_life_area(CreateLifeAreaInput { name: "side projects".to_string(), description: None })
+            .is_err());
+
+        let area_id = area.id.unwrap();
+        let in_area = create_bug(&db, Some(area_id));
+        create_bug(&db, None);
+
+        let filtered: Vec<i64> = db.list_bugs(None, Some(area_id)).unwrap().into_iter().filter_map(|b| b.id).collect();
+        assert_eq!(filtered, vec![in_area]);
+        assert_eq!(db.list_bugs(Some("active".to_string()), None).unwrap().len(), 2);
+
+        assert!(db.delete_life_area(area_id).unwrap());
+        assert_eq!(db.get_bug(in_area).unwrap().unwrap().life_area_id, None);
+    }
+
+    #[test]
+    fn test_update_bug_moves_and_clears_the_area() {
+        let db = setup_test_db();
+        let work = db.get_life_area_by_key("work").unwrap().unwrap().id;
+        let life = db.get_life_area_by_key("life").unwrap().unwrap().id;
+        let bug_id = create_bug(&db, work);
+        let update = |life_area_id, clear_life_area| UpdateBugInput {
+            id: bug_id,
+            title: None,
+            description: None,
+            status: None,
+            cards_drawn: None,
+            conversation_history: None,
+            notes: None,
+            life_area_id,
+            clear_life_area,
+            resolved_at: None,
+        };
+
+        let moved = db.update_bug(update(life, None)).unwrap().unwrap();
+        assert_eq!(moved.life_area_id, life);
+        let kept = db.update_bug(update(None, None)).unwrap().unwrap();
+        assert_eq!(kept.life_area_id, life);
+        db.update_bug(update(None, Some(true))).unwrap();
+        assert_eq!(db.get_bug(bug_id).unwrap().unwrap().life_area_id, None);
+    }
+}
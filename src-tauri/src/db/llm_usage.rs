@@ -0,0 +1,105 @@
+use super::models::{CreateLlmUsageInput, LlmUsageTotals, UsageGrouping};
+use super::Database;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Result as SqlResult};
+
+impl Database {
+    pub fn record_llm_usage(&self, input: &CreateLlmUsageInput) -> SqlResult<i64> {
+        let conn = self.get_connection();
+
+        conn.execute(
+            "INSERT INTO llm_usage (provider, model, operation, prompt_tokens, completion_tokens, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                input.provider,
+                input.model,
+                input.operation,
+                input.prompt_tokens,
+                input.completion_tokens,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Token totals per group and model between `from` and `to` (inclusive, both optional).
+    /// Rows are split by provider and model even when grouping by day or operation so costs can be priced per model.
+    pub fn llm_usage_totals(
+        &self,
+        grouping: UsageGrouping,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> SqlResult<Vec<LlmUsageTotals>> {
+        let conn = self.get_connection();
+
+        let key = match grouping {
+            UsageGrouping::Day => "substr(created_at, 1, 10)",
+            UsageGrouping::Model => "model",
+            UsageGrouping::Operation => "operation",
+        };
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {key}, provider, model, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens)
+             FROM llm_usage
+             WHERE (?1 IS NULL OR created_at >= ?1) AND (?2 IS NULL OR created_at <= ?2)
+             GROUP BY 1, 2, 3
+             ORDER BY 1 ASC, 2 ASC, 3 ASC"
+        ))?;
+
+        let totals = stmt
+            .query_map(
+                params![from.map(|d| d.to_rfc3339()), to.map(|d| d.to_rfc3339())],
+                |row| {
+                    Ok(LlmUsageTotals {
+                        key: row.get(0)?,
+                        provider: row.get(1)?,
+                        model: row.get(2)?,
+                        calls: row.get(3)?,
+                        prompt_tokens: row.get(4)?,
+                        completion_tokens: row.get(5)?,
+                    })
+                },
+            )?
+            .collect::<SqlResult<Vec<LlmUsageTotals>>>()?;
+
+        Ok(totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{CreateLlmUsageInput, UsageGrouping};
+    use crate::db::test_support::setup_test_db;
+
+    fn usage(model: &str, operation: &str, prompt_tokens: i64, completion_tokens: i64) -> CreateLlmUsageInput {
+        CreateLlmUsageInput {
+            provider: "openai".to_string(),
+            model: model.to_string(),
+            operation: operation.to_string(),
+            prompt_tokens,
+            completion_tokens,
+        }
+    }
+
+    #[test]
+    fn test_usage_totals_by_operation_split_by_model() {
+        let db = setup_test_db();
+
+        db.record_llm_usage(&usage("gpt-4o", "chat", 100, 20)).unwrap();
+        db.record_llm_usage(&usage("gpt-4o", "chat", 50, 10)).unwrap();
+        db.record_llm_usage(&usage("gpt-4o-mini", "chat", 10, 5)).unwrap();
+        db.record_llm_usage(&usage("gpt-4o", "title", 30, 3)).unwrap();
+
+        let totals = db.llm_usage_totals(UsageGrouping::Operation, None, None).unwrap();
+        assert_eq!(totals.len(), 3);
+        assert_eq!((totals[0].key.as_str(), totals[0].model.as_str()), ("chat", "gpt-4o"));
+        assert_eq!((totals[0].calls, totals[0].prompt_tokens, totals[0].completion_tokens), (2, 150, 30));
+
+        let by_day = db.llm_usage_totals(UsageGrouping::Day, None, None).unwrap();
+        assert_eq!(by_day[0].key.len(), "2026-01-01".len());
+
+        let future = chrono::Utc::now() + chrono::Duration::days(1);
+        assert!(db.llm_usage_totals(UsageGrouping::Model, Some(future), None).unwrap().is_empty());
+    }
+}
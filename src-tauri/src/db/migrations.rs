@@ -5,6 +5,19 @@ use rusqlite::{Connection, Result as SqlResult, params};
 const MIGRATIONS: &[&str] = &[
     include_str!("../../migrations/001_initial.sql"),
     include_str!("../../migrations/002_add_dream_metadata.sql"),
+    include_str!("../../migrations/003_add_prompt_template_overrides.sql"),
+    include_str!("../../migrations/004_add_generation_provenance.sql"),
+    include_str!("../../migrations/005_version_dream_analyses.sql"),
+    include_str!("../../migrations/006_add_app_settings.sql"),
+    include_str!("../../migrations/007_add_llm_usage.sql"),
+    include_str!("../../migrations/008_add_llm_response_cache.sql"),
+    include_str!("../../migrations/009_add_embeddings.sql"),
+    include_str!("../../migrations/010_add_digests.sql"),
+    include_str!("../../migrations/011_add_mind_dump_analyses.sql"),
+    include_str!("../../migrations/012_add_entry_links.sql"),
+    include_str!("../../migrations/013_add_bug_retrospectives.sql"),
+    include_str!("../../migrations/014_add_bug_patterns.sql"),
+    include_str!("../../migrations/015_add_life_areas.sql"),
 ];
 
 /// Get the current schema version from the database
@@ -131,6 +144,18 @@ mod tests {
             "dream_analyses",
             "dream_analysis_cards",
             "dream_creative_prompts",
+            "prompt_template_overrides",
+            "app_settings",
+            "llm_usage",
+            "llm_response_cache",
+            "embeddings",
+            "digests",
+            "mind_dump_analyses",
+            "mind_dump_analysis_cards",
+            "entry_links",
+            "bug_retrospectives",
+            "bug_patterns",
+            "life_areas",
         ];
 
         for table in tables {
@@ -268,4 +293,65 @@ mod tests {
         ).unwrap();
         assert_eq!(is_lucid_nullable, 0, "is_lucid should allow NULL");
     }
+
+    #[test]
+    fn test_migration_005_allows_multiple_analyses_per_dream() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        conn.execute(
+            "INSERT INTO dreams (date_recorded, date_occurred, title, content, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)",
+            params!["2025-01-01", "2025-01-01", "Test Dream", "Content", "2025-01-01T00:00:00Z", "2025-01-01T00:00:00Z"]
+        ).unwrap();
+
+        for _ in 0..2 {
+            conn.execute(
+                "INSERT INTO dream_analyses (dream_id, themes_patterns, emotional_analysis, narrative_summary, created_at, updated_at)
+                 VALUES (1, 'Themes', 'Emotions', 'Narrative', '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z')",
+                [],
+            ).unwrap();
+        }
+
+        let count: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM dream_analyses WHERE dream_id = 1",
+            [],
+            |row| row.get(0)
+        ).unwrap();
+        assert_eq!(count, 2, "A dream should be able to have several analysis versions");
+    }
+
+    #[test]
+    fn test_migration_005_keeps_analysis_cards_and_creative_prompts() {
+        let conn = Connection::open_in_memory().unwrap();
+        for migration in &MIGRATIONS[..4] {
+            conn.execute_batch(migration).unwrap();
+        }
+        conn.execute_batch(
+            "CREATE TABLE schema_version (version INTEGER PRIMARY KEY, applied_at INTEGER NOT NULL);
+             INSERT INTO schema_version (version, applied_at) VALUES (4, 0);
+             INSERT INTO dreams (date_recorded, date_occurred, title, content, created_at, updated_at)
+             VALUES ('2025-01-01', '2025-01-01', 'Test Dream', 'Content', '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');
+             INSERT INTO dream_analyses (dream_id, themes_patterns, emotional_analysis, narrative_summary, created_at, updated_at)
+             VALUES (1, 'Themes', 'Emotions', 'Narrative', '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');
+             INSERT INTO cards (name, created_at) VALUES ('The Tower', '2025-01-01T00:00:00Z');
+             INSERT INTO dream_analysis_cards (dream_analysis_id, card_id, created_at) VALUES (1, 1, '2025-01-01T00:00:00Z');
+             INSERT INTO dream_creative_prompts (dream_analysis_id, image_prompts, music_prompts, story_prompts, created_at, updated_at)
+             VALUES (1, '[]', '[]', '[]', '2025-01-01T00:00:00Z', '2025-01-01T00:00:00Z');",
+        ).unwrap();
+
+        run_migrations(&conn).unwrap();
+
+        for table in ["dream_analysis_cards", "dream_creative_prompts"] {
+            let count: i32 = conn.query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE dream_analysis_id = 1", table),
+                [],
+                |row| row.get(0)
+            ).unwrap();
+            assert_eq!(count, 1, "{} rows should survive the rebuild", table);
+        }
+        let is_current: bool = conn.query_row("SELECT is_current FROM dream_analyses WHERE id = 1", [], |row| row.get(0)).unwrap();
+        assert!(is_current);
+        let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0)).unwrap();
+        assert!(foreign_keys);
+    }
 }
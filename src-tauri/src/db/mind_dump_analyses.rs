@@ -0,0 +1,243 @@
+use super::models::{
+    AnalysisCardInput, CreateMindDumpAnalysisInput, GenerationProvenance, MindDumpAnalysis, MindDumpAnalysisCard,
+    MindDumpAnalysisWithCards,
+};
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Connection, Result as SqlResult, Row};
+
+const MIND_DUMP_ANALYSIS_COLUMNS: &str =
+    "id, mind_dump_id, summary, worries, action_items, provider, model, prompt_version, temperature, created_at, updated_at";
+
+fn mind_dump_analysis_from_row(row: &Row) -> SqlResult<MindDumpAnalysis> {
+    Ok(MindDumpAnalysis {
+        id: Some(row.get(0)?),
+        mind_dump_id: row.get(1)?,
+        summary: row.get(2)?,
+        worries: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+        action_items: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+        provenance: GenerationProvenance {
+            provider: row.get(5)?,
+            model: row.get(6)?,
+            prompt_version: row.get(7)?,
+            temperature: row.get(8)?,
+        },
+        created_at: row.get::<_, String>(9)?.parse().unwrap(),
+        updated_at: row.get::<_, String>(10)?.parse().unwrap(),
+    })
+}
+
+fn to_json(items: &[String]) -> SqlResult<String> {
+    serde_json::to_string(items).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Remove a mind dump's analysis and its card links
+pub(super) fn delete_analysis_rows(conn: &Connection, mind_dump_id: i64) -> SqlResult<usize> {
+    conn.execute(
+        "DELETE FROM mind_dump_analysis_cards WHERE mind_dump_analysis_id IN
+         (SELECT id FROM mind_dump_analyses WHERE mind_dump_id = ?1)",
+        params![mind_dump_id],
+    )?;
+    conn.execute("DELETE FROM mind_dump_analyses WHERE mind_dump_id = ?1", params![mind_dump_id])
+}
+
+impl Database {
+    /// Store the analysis of a mind dump, replacing any earlier one and its cards
+    pub fn create_mind_dump_analysis(&self, input: CreateMindDumpAnalysisInput) -> SqlResult<MindDumpAnalysis> {
+        self.create_mind_dump_analysis_with_cards(input, &[])
+    }
+
+    /// Replace the analysis and link its cards in one transaction, so a failed
+    /// link leaves the earlier analysis in place
+    pub fn create_mind_dump_analysis_with_cards(
+        &self,
+        input: CreateMindDumpAnalysisInput,
+        cards: &[AnalysisCardInput],
+    ) -> SqlResult<MindDumpAnalysis> {
+        let mut conn = self.get_connection();
+        let now = Utc::now();
+
+        let tx = conn.transaction()?;
+        delete_analysis_rows(&tx, input.mind_dump_id)?;
+        tx.execute(
+            "INSERT INTO mind_dump_analyses
+             (mind_dump_id, summary, worries, action_items, provider, model, prompt_version, temperature, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                input.mind_dump_id,
+                input.summary,
+                to_json(&input.worries)?,
+                to_json(&input.action_items)?,
+                input.provenance.provider,
+                input.provenance.model,
+                input.provenance.prompt_version,
+                input.provenance.temperature,
+                now.to_rfc3339(),
+                now.to_rfc3339(),
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        for card in cards {
+            tx.execute(
+                "INSERT OR REPLACE INTO mind_dump_analysis_cards (mind_dump_analysis_id, card_id, relevance_note, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, card.card_id, card.relevance_note, now.to_rfc3339()],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(MindDumpAnalysis {
+            id: Some(id),
+            mind_dump_id: input.mind_dump_id,
+            summary: input.summary,
+            worries: input.worries,
+            action_items: input.action_items,
+            provenance: input.provenance,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    pub fn get_mind_dump_analysis(&self, mind_dump_id: i64) -> SqlResult<Option<MindDumpAnalysis>> {
+        let conn = self.get_connection();
+
+        let analysis = conn.query_row(
+            &format!("SELECT {} FROM mind_dump_analyses WHERE mind_dump_id = ?1", MIND_DUMP_ANALYSIS_COLUMNS),
+            params![mind_dump_id],
+            mind_dump_analysis_from_row,
+        );
+
+        match analysis {
+            Ok(a) => Ok(Some(a)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn delete_mind_dump_analysis(&self, mind_dump_id: i64) -> SqlResult<bool> {
+        let conn = self.get_connection();
+        Ok(delete_analysis_rows(&conn, mind_dump_id)? > 0)
+    }
+
+    pub fn link_card_to_mind_dump_analysis(
+        &self,
+        mind_dump_analysis_id: i64,
+        card_id: i64,
+        relevance_note: Option<String>,
+    ) -> SqlResult<()> {
+        let conn = self.get_connection();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO mind_dump_analysis_cards (mind_dump_analysis_id, card_id, relevance_note, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![mind_dump_analysis_id, card_id, relevance_note, Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_mind_dump_analysis_cards(&self, mind_dump_analysis_id: i64) -> SqlResult<Vec<MindDumpAnalysisCard>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT mac.mind_dump_analysis_id, mac.card_id, c.name, mac.relevance_note, mac.created_at
+             FROM mind_dump_analysis_cards mac
+             JOIN cards c ON mac.card_id = c.id
+             WHERE mac.mind_dump_analysis_id = ?1
+             ORDER BY mac.created_at ASC",
+        )?;
+
+        let cards = stmt
+            .query_map(params![mind_dump_analysis_id], |row| {
+                Ok(MindDumpAnalysisCard {
+                    mind_dump_analysis_id: row.get(0)?,
+                    card_id: row.get(1)?,
+                    card_name: row.get(2)?,
+                    relevance_note: row.get(3)?,
+                    created_at: row.get::<_, String>(4)?.parse().unwrap(),
+                })
+            })?
+            .collect::<SqlResult<Vec<MindDumpAnalysisCard>>>()?;
+
+        Ok(cards)
+    }
+
+    pub fn get_mind_dump_analysis_with_cards(&self, mind_dump_id: i64) -> SqlResult<Option<MindDumpAnalysisWithCards>> {
+        let Some(analysis) = self.get_mind_dump_analysis(mind_dump_id)? else {
+            return Ok(None);
+        };
+        let cards = self.get_mind_dump_analysis_cards(analysis.id.unwrap_or(0))?;
+
+        Ok(Some(MindDumpAnalysisWithCards { analysis, cards }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::{AnalysisCardInput, CreateMindDumpAnalysisInput, CreateMindDumpInput, GenerationProvenance};
+    use crate::db::test_support::{create_card, setup_test_db};
+
+    fn analysis_input(mind_dump_id: i64, summary: &str) -> CreateMindDumpAnalysisInput {
+        CreateMindDumpAnalysisInput {
+            mind_dump_id,
+            summary: summary.to_string(),
+            worries: vec!["Rent is due".to_string()],
+            action_items: vec!["Call the landlord".to_string()],
+            provenance: GenerationProvenance::default(),
+        }
+    }
+
+    #[test]
+    fn test_reanalysis_replaces_and_delete_cleans_up() {
+        let db = setup_test_db();
+        let mind_dump = db
+            .create_mind_dump(CreateMindDumpInput {
+                title: None,
+                content: "Rent is due and I have not called the landlord".to_string(),
+                word_count: 10,
+            })
+            .unwrap();
+        let mind_dump_id = mind_dump.id.unwrap();
+        let card_id = create_card(&db, "Fox");
+
+        let first = db.create_mind_dump_analysis(analysis_input(mind_dump_id, "First")).unwrap();
+        db.link_card_to_mind_dump_analysis(first.id.unwrap(), card_id, Some("Cunning".to_string())).unwrap();
+        assert_eq!(db.get_mind_dump_analysis_cards(first.id.unwrap()).unwrap()[0].card_name, "Fox");
+
+        let second = db.create_mind_dump_analysis(analysis_input(mind_dump_id, "Second")).unwrap();
+        let stored = db.get_mind_dump_analysis_with_cards(mind_dump_id).unwrap().unwrap();
+        assert_eq!(stored.analysis.id, second.id);
+        assert_eq!(stored.analysis.action_items, vec!["Call the landlord"]);
+        assert!(stored.cards.is_empty());
+        assert!(db.get_mind_dump_analysis_cards(first.id.unwrap()).unwrap().is_empty());
+
+        db.link_card_to_mind_dump_analysis(second.id.unwrap(), card_id, None).unwrap();
+        db.delete_mind_dump(mind_dump_id).unwrap();
+        assert!(db.get_mind_dump_analysis(mind_dump_id).unwrap().is_none());
+        assert!(db.get_mind_dump_analysis_cards(second.id.unwrap()).unwrap().is_empty());
+    }
+    #[test]
+    fn test_analysis_with_cards_is_all_or_nothing() {
+        let db = setup_test_db();
+        let mind_dump_id = db
+            .create_mind_dump(CreateMindDumpInput { title: None, content: "Too much to do".to_string(), word_count: 4 })
+            .unwrap()
+            .id
+            .unwrap();
+        let fox = create_card(&db, "Fox");
+        let first = db.create_mind_dump_analysis(analysis_input(mind_dump_id, "First")).unwrap();
+
+        let card = |card_id| AnalysisCardInput { card_id, relevance_note: None };
+        assert!(db
+            .create_mind_dump_analysis_with_cards(analysis_input(mind_dump_id, "Second"), &[card(fox), card(999)])
+            .is_err());
+        assert_eq!(db.get_mind_dump_analysis(mind_dump_id).unwrap().unwrap().id, first.id);
+
+        let second = db
+            .create_mind_dump_analysis_with_cards(analysis_input(mind_dump_id, "Second"), &[card(fox)])
+            .unwrap();
+        let stored = db.get_mind_dump_analysis_with_cards(mind_dump_id).unwrap().unwrap();
+        assert_eq!(stored.analysis.id, second.id);
+        assert_eq!(stored.cards[0].card_name, "Fox");
+    }
+}
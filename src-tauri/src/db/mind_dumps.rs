@@ -1,4 +1,4 @@
-use super::models::{CreateMindDumpInput, MindDump, UpdateMindDumpInput};
+use super::models::{CreateMindDumpInput, EntryType, MindDump, UpdateMindDumpInput};
 use super::Database;
 use chrono::Utc;
 use rusqlite::{params, Result as SqlResult};
@@ -59,15 +59,23 @@ impl Database {
     }
 
     pub fn list_mind_dumps(&self, limit: Option<i64>, offset: Option<i64>) -> SqlResult<Vec<MindDump>> {
+        self.select_mind_dumps(&format!("LIMIT {} OFFSET {}", limit.unwrap_or(100), offset.unwrap_or(0)))
+    }
+
+    /// Every one of the mind dumps, newest first, for work that scans the whole journal
+    pub fn list_all_mind_dumps(&self) -> SqlResult<Vec<MindDump>> {
+        self.select_mind_dumps("")
+    }
+
+    fn select_mind_dumps(&self, paging: &str) -> SqlResult<Vec<MindDump>> {
         let conn = self.get_connection();
 
         let query = format!(
             "SELECT id, title, content, word_count, created_at, updated_at
              FROM mind_dumps
              ORDER BY created_at DESC
-             LIMIT {} OFFSET {}",
-            limit.unwrap_or(100),
-            offset.unwrap_or(0)
+             {}",
+            paging
         );
 
         let mut stmt = conn.prepare(&query)?;
@@ -89,8 +97,6 @@ impl Database {
     }
 
     pub fn update_mind_dump(&self, input: UpdateMindDumpInput) -> SqlResult<Option<MindDump>> {
-        let conn = self.get_connection();
-
         // First, get the existing entry
         let existing = self.get_mind_dump(input.id)?;
         if existing.is_none() {
@@ -99,6 +105,7 @@ impl Database {
 
         let mut existing = existing.unwrap();
         let now = Utc::now();
+        let conn = self.get_connection();
 
         // Update only provided fields
         if let Some(title) = input.title {
@@ -130,9 +137,14 @@ impl Database {
     }
 
     pub fn delete_mind_dump(&self, id: i64) -> SqlResult<bool> {
-        let conn = self.get_connection();
-
-        let rows_affected = conn.execute("DELETE FROM mind_dumps WHERE id = ?1", params![id])?;
+        let mut conn = self.get_connection();
+
+        let tx = conn.transaction()?;
+        let rows_affected = tx.execute("DELETE FROM mind_dumps WHERE id = ?1", params![id])?;
+        super::embeddings::delete_embedding_rows(&tx, EntryType::MindDump, id)?;
+        super::entry_links::delete_links_for(&tx, EntryType::MindDump, id)?;
+        super::mind_dump_analyses::delete_analysis_rows(&tx, id)?;
+        tx.commit()?;
 
         Ok(rows_affected > 0)
     }
@@ -7,5 +7,18 @@ pub mod creative_prompts;
 pub mod bugs;
 pub mod mind_dumps;
 pub mod cards;
+pub mod prompt_templates;
+pub mod settings;
+pub mod llm_usage;
+pub mod response_cache;
+pub mod embeddings;
+pub mod digests;
+pub mod mind_dump_analyses;
+pub mod entry_links;
+pub mod bug_retrospectives;
+pub mod bug_patterns;
+pub mod life_areas;
+#[cfg(test)]
+pub mod test_support;
 
 pub use connection::Database;
@@ -42,6 +42,16 @@ pub struct UpdateDreamInput {
     pub is_lucid: Option<bool>,
 }
 
+// Provenance of LLM-generated artifacts
+// All fields are None for rows generated before provenance was recorded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationProvenance {
+    pub provider: Option<String>,       // "ollama", "openai", "anthropic"
+    pub model: Option<String>,          // Resolved model name sent to the provider
+    pub prompt_version: Option<String>, // e.g. "dream_analysis@v1" or "dream_analysis@custom-1a2b3c4d5e6f7a8b"
+    pub temperature: Option<f64>,
+}
+
 // Dream analysis models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DreamAnalysis {
@@ -50,6 +60,12 @@ pub struct DreamAnalysis {
     pub themes_patterns: String,
     pub emotional_analysis: String,
     pub narrative_summary: String,
+    #[serde(flatten)]
+    pub provenance: GenerationProvenance,
+    /// The version shown for the dream; exactly one per dream
+    pub is_current: bool,
+    /// Chosen by the user; stays current when new versions are generated
+    pub is_pinned: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -60,6 +76,15 @@ pub struct CreateDreamAnalysisInput {
     pub themes_patterns: String,
     pub emotional_analysis: String,
     pub narrative_summary: String,
+    #[serde(default, flatten)]
+    pub provenance: GenerationProvenance,
+}
+
+/// A card to link to a new analysis, resolved before the analysis is stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisCardInput {
+    pub card_id: i64,
+    pub relevance_note: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +102,44 @@ pub struct DreamAnalysisWithCards {
     pub cards: Vec<DreamAnalysisCard>,
 }
 
+// Mind dump analysis models
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MindDumpAnalysis {
+    pub id: Option<i64>,
+    pub mind_dump_id: i64,
+    pub summary: String,
+    pub worries: Vec<String>,
+    pub action_items: Vec<String>,
+    #[serde(flatten)]
+    pub provenance: GenerationProvenance,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateMindDumpAnalysisInput {
+    pub mind_dump_id: i64,
+    pub summary: String,
+    pub worries: Vec<String>,
+    pub action_items: Vec<String>,
+    pub provenance: GenerationProvenance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MindDumpAnalysisCard {
+    pub mind_dump_analysis_id: i64,
+    pub card_id: i64,
+    pub card_name: String,
+    pub relevance_note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MindDumpAnalysisWithCards {
+    pub analysis: MindDumpAnalysis,
+    pub cards: Vec<MindDumpAnalysisCard>,
+}
+
 // Dream creative prompts models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DreamCreativePrompts {
@@ -85,6 +148,8 @@ pub struct DreamCreativePrompts {
     pub image_prompts: String, // JSON array of image prompts
     pub music_prompts: String, // JSON array of music prompts
     pub story_prompts: String, // JSON array of story prompts
+    #[serde(flatten)]
+    pub provenance: GenerationProvenance,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -95,6 +160,8 @@ pub struct CreateDreamCreativePromptsInput {
     pub image_prompts: String,
     pub music_prompts: String,
     pub story_prompts: String,
+    #[serde(default, flatten)]
+    pub provenance: GenerationProvenance,
 }
 
 // Bug tracking models
@@ -107,6 +174,7 @@ pub struct Bug {
     pub cards_drawn: Option<String>, // JSON string of card IDs
     pub conversation_history: Option<String>, // JSON string of conversation
     pub notes: Option<String>, // JSON string of notes array
+    pub life_area_id: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub resolved_at: Option<DateTime<Utc>>,
@@ -119,6 +187,7 @@ pub struct CreateBugInput {
     pub cards_drawn: Option<String>,
     pub conversation_history: Option<String>,
     pub notes: Option<String>,
+    pub life_area_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,9 +199,86 @@ pub struct UpdateBugInput {
     pub cards_drawn: Option<String>,
     pub conversation_history: Option<String>,
     pub notes: Option<String>,
+    pub life_area_id: Option<i64>,
+    /// Remove the bug from its life area; ignored when `life_area_id` is set
+    pub clear_life_area: Option<bool>,
     pub resolved_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BugRetrospective {
+    pub id: Option<i64>,
+    pub bug_id: i64,
+    pub root_pattern: String,
+    /// Id from the discovery chat's blocker pattern taxonomy
+    pub blocker_pattern: String,
+    pub what_helped: Vec<String>,
+    pub follow_ups: Vec<String>,
+    #[serde(flatten)]
+    pub provenance: GenerationProvenance,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateBugRetrospectiveInput {
+    pub bug_id: i64,
+    pub root_pattern: String,
+    pub blocker_pattern: String,
+    pub what_helped: Vec<String>,
+    pub follow_ups: Vec<String>,
+    pub provenance: GenerationProvenance,
+}
+
+/// A blocker pattern detected in a bug
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BugPattern {
+    pub bug_id: i64,
+    /// Id from the discovery chat's blocker pattern taxonomy
+    pub pattern: String,
+    /// 0.0 to 1.0, as estimated by the model
+    pub confidence: f64,
+    pub evidence: Option<String>,
+    #[serde(flatten)]
+    pub provenance: GenerationProvenance,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateBugPatternInput {
+    pub pattern: String,
+    pub confidence: f64,
+    pub evidence: Option<String>,
+}
+
+/// How often a blocker pattern appears across bugs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternFrequency {
+    pub pattern: String,
+    /// Taxonomy group, e.g. "Cognitive Biases"; None for ids no longer in the taxonomy
+    pub group: Option<String>,
+    pub bug_count: i64,
+    pub average_confidence: f64,
+}
+
+// Life area models
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifeArea {
+    pub id: Option<i64>,
+    /// "creative", "work", "life", "relationship" (the cards.json insight keys) or a custom slug
+    pub key: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_builtin: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLifeAreaInput {
+    pub name: String,
+    pub description: Option<String>,
+}
+
 // Mind dump models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MindDump {
@@ -187,3 +333,304 @@ pub struct CardWithCount {
     pub bug_count: i32,
     pub created_at: DateTime<Utc>,
 }
+
+// Prompt template models
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateOverride {
+    pub template_id: String,
+    pub template_text: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// LLM usage models
+/// Tokens consumed by one successful provider call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateLlmUsageInput {
+    pub provider: String,
+    pub model: String,
+    pub operation: String, // e.g. "dream_analysis", "chat"
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+}
+
+/// Usage totals for one day, model or operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmUsageSummary {
+    pub key: String,
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub estimated_cost_usd: Option<f64>, // None when a model in the group has no known price
+}
+
+/// How `get_llm_usage` groups its totals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGrouping {
+    Day,
+    Model,
+    Operation,
+}
+
+/// Raw token totals per group and model, before cost estimation
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmUsageTotals {
+    pub key: String,
+    pub provider: String,
+    pub model: String,
+    pub calls: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+}
+
+// LLM response cache models
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCachedResponseInput {
+    pub cache_key: String,
+    pub operation: String,
+    pub provider: String,
+    pub model: String,
+    pub response: String, // JSON-encoded result
+}
+
+// Embedding models
+/// The kinds of journal entry that can be embedded and searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryType {
+    Dream,
+    MindDump,
+    Bug,
+}
+
+impl EntryType {
+    pub const ALL: [EntryType; 3] = [EntryType::Dream, EntryType::MindDump, EntryType::Bug];
+
+    /// Name stored in `entity_type` columns
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryType::Dream => "dream",
+            EntryType::MindDump => "mind_dump",
+            EntryType::Bug => "bug",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<EntryType> {
+        EntryType::ALL.into_iter().find(|entry_type| entry_type.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredEmbedding {
+    pub entity_type: EntryType,
+    pub entity_id: i64,
+    pub provider: String,
+    pub model: String,
+    pub content_hash: String,
+    pub vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticSearchResult {
+    pub entity_type: EntryType,
+    pub entity_id: i64,
+    pub title: String,
+    pub snippet: String,
+    pub date: DateTime<Utc>,
+    /// Weighted blend of the two scores below, 0 to 1
+    pub score: f32,
+    /// Cosine similarity to the query, or None when embeddings are unavailable
+    pub semantic_score: Option<f32>,
+    /// Share of query terms found in the entry
+    pub keyword_score: f32,
+}
+
+// Similar dream models
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarDream {
+    pub dream_id: i64,
+    pub title: String,
+    pub date_occurred: DateTime<Utc>,
+    /// Weighted blend of content, card and tag similarity, 0 to 1
+    pub score: f32,
+    pub content_score: f32,
+    pub shared_cards: Vec<String>,
+    pub shared_tags: Vec<String>,
+}
+
+/// Recurrence fields worth setting on a dream that closely matches an earlier one
+#[derive(Debug, Clone, Serialize)]
+pub struct RecurrenceSuggestion {
+    pub is_recurring: bool,
+    pub last_occurrence_period: String,
+    pub matched_dream_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarDreamsResult {
+    pub dream_id: i64,
+    pub similar: Vec<SimilarDream>,
+    pub suggestion: Option<RecurrenceSuggestion>,
+    /// Whether the suggestion was written to the dream
+    pub applied: bool,
+}
+
+/// Length of the range a digest covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPeriod {
+    Week,
+    Month,
+    Custom,
+}
+
+impl DigestPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestPeriod::Week => "week",
+            DigestPeriod::Month => "month",
+            DigestPeriod::Custom => "custom",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(DigestPeriod::Week),
+            "month" => Some(DigestPeriod::Month),
+            "custom" => Some(DigestPeriod::Custom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagCount {
+    pub name: String,
+    pub count: i64,
+}
+
+/// Activity in a digest's range, stored with the digest as JSON
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DigestStats {
+    pub dream_count: i64,
+    pub lucid_dream_count: i64,
+    pub recurring_dream_count: i64,
+    pub average_sleep_quality: Option<f64>,
+    pub mind_dump_count: i64,
+    pub mind_dump_words: i64,
+    pub bugs_created: i64,
+    pub bugs_resolved: i64,
+    /// Most frequent emotion tags on the range's dreams
+    pub top_emotions: Vec<TagCount>,
+    /// Cards most often linked by the current analyses of the range's dreams
+    pub top_cards: Vec<TagCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub id: Option<i64>,
+    pub period_type: DigestPeriod,
+    pub start_date: DateTime<Utc>,
+    /// Exclusive
+    pub end_date: DateTime<Utc>,
+    pub stats: DigestStats,
+    pub summary: String,
+    #[serde(flatten)]
+    pub provenance: GenerationProvenance,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateDigestInput {
+    pub period_type: DigestPeriod,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub stats: DigestStats,
+    pub summary: String,
+    pub provenance: GenerationProvenance,
+}
+
+/// How the source of an entry link relates to its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryRelation {
+    RelatesTo,
+    /// The source was caused by the target, e.g. a bug caused by a recurring dream
+    CausedBy,
+    /// The source follows up on the target
+    FollowUp,
+    /// A bug created from the dream or mind dump it links to
+    PromotedFrom,
+}
+
+impl EntryRelation {
+    pub const ALL: [EntryRelation; 4] = [
+        EntryRelation::RelatesTo,
+        EntryRelation::CausedBy,
+        EntryRelation::FollowUp,
+        EntryRelation::PromotedFrom,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryRelation::RelatesTo => "relates_to",
+            EntryRelation::CausedBy => "caused_by",
+            EntryRelation::FollowUp => "follow_up",
+            EntryRelation::PromotedFrom => "promoted_from",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        EntryRelation::ALL.into_iter().find(|relation| relation.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryLink {
+    pub id: Option<i64>,
+    pub source_type: EntryType,
+    pub source_id: i64,
+    pub target_type: EntryType,
+    pub target_id: i64,
+    pub relation: EntryRelation,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Which end of a link the entry being viewed is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkDirection {
+    /// The viewed entry is the link's source
+    Outgoing,
+    /// The viewed entry is the link's target (a backlink)
+    Incoming,
+}
+
+/// A link seen from one entry, with the entry at the other end
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkedEntry {
+    pub link_id: i64,
+    pub relation: EntryRelation,
+    pub direction: LinkDirection,
+    pub entry_type: EntryType,
+    pub entry_id: i64,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `get_*` responses: the entry's own fields plus its links in both directions
+#[derive(Debug, Clone, Serialize)]
+pub struct WithLinks<T> {
+    #[serde(flatten)]
+    pub entry: T,
+    pub links: Vec<LinkedEntry>,
+}
+
+/// A bug created from a dream or mind dump, with its link back and any cards drawn
+#[derive(Debug, Clone, Serialize)]
+pub struct BugFromEntry {
+    pub bug: Bug,
+    pub link: EntryLink,
+    pub cards: Vec<Card>,
+}
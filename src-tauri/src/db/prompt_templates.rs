@@ -0,0 +1,102 @@
+use super::models::PromptTemplateOverride;
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Result as SqlResult};
+
+impl Database {
+    /// Create or replace the user's override for a prompt template
+    pub fn upsert_prompt_template_override(
+        &self,
+        template_id: &str,
+        template_text: &str,
+    ) -> SqlResult<PromptTemplateOverride> {
+        let conn = self.get_connection();
+        let now = Utc::now();
+
+        conn.execute(
+            "INSERT INTO prompt_template_overrides (template_id, template_text, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?3)
+             ON CONFLICT(template_id) DO UPDATE SET template_text = excluded.template_text, updated_at = excluded.updated_at",
+            params![template_id, template_text, now.to_rfc3339()],
+        )?;
+
+        conn.query_row(
+            "SELECT template_id, template_text, created_at, updated_at
+             FROM prompt_template_overrides WHERE template_id = ?1",
+            params![template_id],
+            |row| {
+                Ok(PromptTemplateOverride {
+                    template_id: row.get(0)?,
+                    template_text: row.get(1)?,
+                    created_at: row.get::<_, String>(2)?.parse().unwrap(),
+                    updated_at: row.get::<_, String>(3)?.parse().unwrap(),
+                })
+            },
+        )
+    }
+
+    pub fn list_prompt_template_overrides(&self) -> SqlResult<Vec<PromptTemplateOverride>> {
+        let conn = self.get_connection();
+
+        let mut stmt = conn.prepare(
+            "SELECT template_id, template_text, created_at, updated_at
+             FROM prompt_template_overrides
+             ORDER BY template_id ASC",
+        )?;
+
+        let overrides = stmt
+            .query_map([], |row| {
+                Ok(PromptTemplateOverride {
+                    template_id: row.get(0)?,
+                    template_text: row.get(1)?,
+                    created_at: row.get::<_, String>(2)?.parse().unwrap(),
+                    updated_at: row.get::<_, String>(3)?.parse().unwrap(),
+                })
+            })?
+            .collect::<SqlResult<Vec<PromptTemplateOverride>>>()?;
+
+        Ok(overrides)
+    }
+
+    /// Remove an override so the built-in template is used again
+    pub fn delete_prompt_template_override(&self, template_id: &str) -> SqlResult<bool> {
+        let conn = self.get_connection();
+
+        let rows_affected = conn.execute(
+            "DELETE FROM prompt_template_overrides WHERE template_id = ?1",
+            params![template_id],
+        )?;
+
+        Ok(rows_affected > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_support::setup_test_db;
+
+    #[test]
+    fn test_upsert_replaces_existing_override() {
+        let db = setup_test_db();
+
+        let first = db.upsert_prompt_template_override("card_commentary", "First {card_name}").unwrap();
+        let second = db.upsert_prompt_template_override("card_commentary", "Second {card_name}").unwrap();
+
+        assert_eq!(second.template_text, "Second {card_name}");
+        assert_eq!(second.created_at, first.created_at);
+
+        let overrides = db.list_prompt_template_overrides().unwrap();
+        assert_eq!(overrides.len(), 1);
+    }
+
+    #[test]
+    fn test_delete_override() {
+        let db = setup_test_db();
+
+        db.upsert_prompt_template_override("title_generation", "Title please").unwrap();
+
+        assert!(db.delete_prompt_template_override("title_generation").unwrap());
+        assert!(!db.delete_prompt_template_override("title_generation").unwrap());
+        assert!(db.list_prompt_template_overrides().unwrap().is_empty());
+    }
+}
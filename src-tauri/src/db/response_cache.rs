@@ -0,0 +1,110 @@
+use super::models::CreateCachedResponseInput;
+use super::Database;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Result as SqlResult};
+
+impl Database {
+    /// A cached response younger than `ttl`. Expired entries are removed on lookup.
+    pub fn get_cached_response(&self, cache_key: &str, ttl: Duration) -> SqlResult<Option<String>> {
+        let conn = self.get_connection();
+
+        let result = conn.query_row(
+            "SELECT response, created_at FROM llm_response_cache WHERE cache_key = ?1",
+            params![cache_key],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        );
+
+        let (response, created_at) = match result {
+            Ok(entry) => entry,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let now = Utc::now();
+        let created_at: DateTime<Utc> = created_at.parse().unwrap();
+        if now - created_at > ttl {
+            conn.execute("DELETE FROM llm_response_cache WHERE cache_key = ?1", params![cache_key])?;
+            return Ok(None);
+        }
+
+        conn.execute(
+            "UPDATE llm_response_cache SET last_used_at = ?1 WHERE cache_key = ?2",
+            params![now.to_rfc3339(), cache_key],
+        )?;
+
+        Ok(Some(response))
+    }
+
+    /// Store a response, then evict the least recently used entries beyond `max_entries`
+    pub fn put_cached_response(&self, input: &CreateCachedResponseInput, max_entries: usize) -> SqlResult<()> {
+        let conn = self.get_connection();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO llm_response_cache
+             (cache_key, operation, provider, model, response, created_at, last_used_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+            params![input.cache_key, input.operation, input.provider, input.model, input.response, now],
+        )?;
+
+        conn.execute(
+            "DELETE FROM llm_response_cache WHERE cache_key NOT IN (
+                 SELECT cache_key FROM llm_response_cache ORDER BY last_used_at DESC LIMIT ?1
+             )",
+            params![max_entries as i64],
+        )?;
+
+        Ok(())
+    }
+
+    /// Remove every cached response, returning how many were removed
+    pub fn clear_response_cache(&self) -> SqlResult<usize> {
+        let conn = self.get_connection();
+        conn.execute("DELETE FROM llm_response_cache", [])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::models::CreateCachedResponseInput;
+    use crate::db::test_support::setup_test_db;
+    use chrono::Duration;
+
+    fn entry(cache_key: &str) -> CreateCachedResponseInput {
+        CreateCachedResponseInput {
+            cache_key: cache_key.to_string(),
+            operation: "title".to_string(),
+            provider: "ollama".to_string(),
+            model: "llama3.2".to_string(),
+            response: format!("\"{}\"", cache_key),
+        }
+    }
+
+    #[test]
+    fn test_expired_entries_are_not_returned() {
+        let db = setup_test_db();
+        db.put_cached_response(&entry("a"), 10).unwrap();
+
+        assert_eq!(db.get_cached_response("a", Duration::hours(1)).unwrap().as_deref(), Some("\"a\""));
+        assert_eq!(db.get_cached_response("a", Duration::seconds(-1)).unwrap(), None);
+        // The expired entry was removed
+        assert_eq!(db.get_cached_response("a", Duration::hours(1)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_size_limit_evicts_least_recently_used() {
+        let db = setup_test_db();
+
+        db.put_cached_response(&entry("a"), 2).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        db.put_cached_response(&entry("b"), 2).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        db.get_cached_response("a", Duration::hours(1)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        db.put_cached_response(&entry("c"), 2).unwrap();
+
+        assert!(db.get_cached_response("a", Duration::hours(1)).unwrap().is_some());
+        assert!(db.get_cached_response("b", Duration::hours(1)).unwrap().is_none());
+        assert_eq!(db.clear_response_cache().unwrap(), 2);
+    }
+}
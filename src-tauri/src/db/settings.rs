@@ -0,0 +1,62 @@
+use super::Database;
+use chrono::Utc;
+use rusqlite::{params, Result as SqlResult};
+
+impl Database {
+    /// Raw JSON value of a setting, if it has been saved
+    pub fn get_setting(&self, key: &str) -> SqlResult<Option<String>> {
+        let conn = self.get_connection();
+
+        let result = conn.query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            params![key],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> SqlResult<()> {
+        let conn = self.get_connection();
+
+        conn.execute(
+            "INSERT INTO app_settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![key, value, Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete_setting(&self, key: &str) -> SqlResult<bool> {
+        let conn = self.get_connection();
+
+        let rows_affected = conn.execute("DELETE FROM app_settings WHERE key = ?1", params![key])?;
+
+        Ok(rows_affected > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::test_support::setup_test_db;
+
+    #[test]
+    fn test_set_get_and_delete_setting() {
+        let db = setup_test_db();
+
+        assert_eq!(db.get_setting("llm_config").unwrap(), None);
+
+        db.set_setting("llm_config", r#"{"provider":"ollama"}"#).unwrap();
+        db.set_setting("llm_config", r#"{"provider":"openai"}"#).unwrap();
+        assert_eq!(db.get_setting("llm_config").unwrap().as_deref(), Some(r#"{"provider":"openai"}"#));
+
+        assert!(db.delete_setting("llm_config").unwrap());
+        assert!(!db.delete_setting("llm_config").unwrap());
+        assert_eq!(db.get_setting("llm_config").unwrap(), None);
+    }
+}
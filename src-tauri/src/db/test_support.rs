@@ -0,0 +1,44 @@
+//! Fixtures shared by the unit tests across `db` and `llm`
+use super::models::{CreateCardInput, CreateDreamInput, Dream};
+use super::Database;
+use chrono::Utc;
+use rusqlite::Connection;
+use std::future::Future;
+
+/// An in-memory database with every migration applied
+pub fn setup_test_db() -> Database {
+    let conn = Connection::open_in_memory().unwrap();
+    crate::db::migrations::run_migrations(&conn).unwrap();
+    Database::from_connection(conn)
+}
+
+/// A dream occurring now with no optional details; override fields with `..dream_input(..)`
+pub fn dream_input(title: &str, content: &str) -> CreateDreamInput {
+    CreateDreamInput {
+        date_occurred: Utc::now(),
+        title: title.to_string(),
+        content: content.to_string(),
+        emotions_tags: None,
+        sleep_quality: None,
+        is_recurring: None,
+        last_occurrence_period: None,
+        is_lucid: None,
+    }
+}
+
+pub fn create_dream(db: &Database, title: &str, content: &str) -> Dream {
+    db.create_dream(dream_input(title, content)).unwrap()
+}
+
+/// Add a card to the deck, returning its id
+pub fn create_card(db: &Database, name: &str) -> i64 {
+    db.create_card_internal(CreateCardInput { name: name.to_string() })
+        .unwrap()
+        .id
+        .unwrap()
+}
+
+/// Run an async LLM call to completion on a fresh runtime
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Runtime::new().unwrap().block_on(future)
+}
@@ -3,18 +3,25 @@ use tauri::Manager;
 pub mod db;
 mod commands;
 pub mod llm;
+pub mod secrets;
 
 use db::Database;
+use secrets::Secrets;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .manage(llm::http::LlmHttp::new())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
             // Initialize database
             let db = Database::new().expect("Failed to initialize database");
             app.manage(db);
+
+            // API keys live in the OS keyring, or an encrypted file next to the database
+            let data_dir = Database::data_dir().expect("Failed to determine data directory");
+            app.manage(Secrets::new(&data_dir));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -28,6 +35,12 @@ pub fn run() {
             commands::optimize_dream_description,
             commands::generate_dream_analysis,
             commands::get_dream_analysis_with_cards,
+            commands::regenerate_dream_analysis,
+            commands::list_dream_analysis_versions,
+            commands::pin_dream_analysis,
+            commands::unpin_dream_analysis,
+            commands::delete_dream_analysis_version,
+            commands::find_outdated_dream_analyses,
             commands::generate_dream_creative_prompts,
             commands::get_dream_creative_prompts,
             commands::create_bug,
@@ -37,6 +50,12 @@ pub fn run() {
             commands::delete_bug,
             commands::optimize_bug_description,
             commands::generate_bug_title,
+            commands::create_bug_from_entry,
+            commands::generate_bug_retrospective,
+            commands::get_bug_retrospective,
+            commands::classify_bug_patterns,
+            commands::get_bug_patterns,
+            commands::get_pattern_frequencies,
             commands::comment_on_card,
             commands::comment_on_multiple_cards,
             commands::chat_with_history,
@@ -46,16 +65,46 @@ pub fn run() {
             commands::update_mind_dump,
             commands::delete_mind_dump,
             commands::search_mind_dumps,
+            commands::analyze_mind_dump,
+            commands::get_mind_dump_analysis_with_cards,
             commands::get_card,
             commands::get_card_by_name,
             commands::list_cards,
             commands::list_cards_by_usage,
+            commands::list_life_areas,
+            commands::create_life_area,
+            commands::delete_life_area,
             commands::create_bug_with_cards,
             commands::link_card_to_bug,
             commands::get_bug_cards,
             commands::unlink_card_from_bug,
             commands::clear_bug_cards,
             commands::get_card_bugs,
+            commands::list_prompt_templates,
+            commands::get_prompt_template,
+            commands::set_prompt_template_override,
+            commands::reset_prompt_template,
+            commands::preview_prompt,
+            commands::test_llm_connection,
+            commands::list_available_models,
+            commands::get_llm_config,
+            commands::set_llm_config,
+            commands::delete_llm_api_key,
+            commands::get_user_profile,
+            commands::update_user_profile,
+            commands::get_llm_usage,
+            commands::clear_llm_cache,
+            commands::semantic_search,
+            commands::sync_embeddings,
+            commands::find_similar_dreams,
+            commands::ask_journal,
+            commands::link_entries,
+            commands::unlink_entries,
+            commands::list_entry_links,
+            commands::generate_period_digest,
+            commands::list_digests,
+            commands::get_digest,
+            commands::delete_digest,
             commands::backup_database,
             commands::get_database_path,
         ])
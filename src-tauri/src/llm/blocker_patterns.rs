@@ -0,0 +1,75 @@
+/// One of the blocker patterns listed in the discovery chat prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockerPattern {
+    pub id: &'static str,
+    pub group: &'static str,
+    pub description: &'static str,
+}
+
+const fn pattern(id: &'static str, group: &'static str, description: &'static str) -> BlockerPattern {
+    BlockerPattern { id, group, description }
+}
+
+/// The taxonomy from `DISCOVERY_CHAT_SYSTEM_PROMPT`, in the same order
+pub const BLOCKER_PATTERNS: [BlockerPattern; 24] = [
+    pattern("substantialist_thinking", "Epistemological Obstacles", "Treating dynamic processes as fixed traits"),
+    pattern("obstacle_of_experience", "Epistemological Obstacles", "Over-reliance on past experience"),
+    pattern("verbal_obstacle", "Epistemological Obstacles", "Getting trapped in language/metaphors"),
+    pattern("unitary_knowledge", "Epistemological Obstacles", "Resistance to complexity"),
+    pattern("pragmatic_knowledge", "Epistemological Obstacles", "Quick-fix seeking over deep understanding"),
+    pattern("quantitative_obstacle", "Epistemological Obstacles", "Over-focus on metrics"),
+    pattern("animistic_thinking", "Epistemological Obstacles", "External attribution patterns"),
+    pattern("mythical_valorization", "Epistemological Obstacles", "Idealization of approaches"),
+    pattern("circular_reasoning", "Epistemological Obstacles", "Self-reinforcing thought loops"),
+    pattern("false_precision", "Epistemological Obstacles", "Pseudo-accuracy masking uncertainty"),
+    pattern("cognitive_rigidity", "Epistemological Obstacles", "Difficulty shifting perspectives"),
+    pattern("avoidance_pattern", "Epistemological Obstacles", "Systematic topic avoidance"),
+    pattern("confirmation_bias", "Cognitive Biases", "Seeking confirming information"),
+    pattern("dunning_kruger_effect", "Cognitive Biases", "Overconfidence in low-competence areas"),
+    pattern("sunk_cost_fallacy", "Cognitive Biases", "Continuing due to past investment"),
+    pattern("choice_overload_paralysis", "Cognitive Biases", "Decision avoidance"),
+    pattern("avoidant_attachment_block", "Attachment Patterns", "Emotional discomfort"),
+    pattern("anxious_attachment_block", "Attachment Patterns", "Validation seeking"),
+    pattern("disorganized_attachment_block", "Attachment Patterns", "Chaotic engagement"),
+    pattern("autonomy_threat_response", "Psychological Reactance", "Resistance to perceived control"),
+    pattern("catastrophic_thinking", "Cognitive Distortions", "Worst-case scenario focus"),
+    pattern("all_or_nothing_thinking", "Cognitive Distortions", "Black-and-white patterns"),
+    pattern("mental_filtering", "Cognitive Distortions", "Negative focus"),
+    pattern("personalization_bias", "Cognitive Distortions", "Excessive self-blame"),
+];
+
+pub fn find(id: &str) -> Option<&'static BlockerPattern> {
+    BLOCKER_PATTERNS.iter().find(|pattern| pattern.id == id)
+}
+
+pub fn pattern_ids() -> Vec<String> {
+    BLOCKER_PATTERNS.iter().map(|pattern| pattern.id.to_string()).collect()
+}
+
+/// The taxonomy as prompt text, one `- id (group): description` line per pattern
+pub fn taxonomy_text() -> String {
+    BLOCKER_PATTERNS
+        .iter()
+        .map(|pattern| format!("- {} ({}): {}", pattern.id, pattern.group, pattern.description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::prompts::DISCOVERY_CHAT_SYSTEM_PROMPT;
+
+    #[test]
+    fn test_taxonomy_matches_discovery_prompt() {
+        let listed: Vec<&str> = DISCOVERY_CHAT_SYSTEM_PROMPT
+            .lines()
+            .filter_map(|line| line.strip_prefix("- "))
+            .filter_map(|line| line.split_once(": "))
+            .map(|(id, _)| id)
+            .filter(|id| id.chars().all(|c| c.is_ascii_lowercase() || c == '_'))
+            .collect();
+
+        assert_eq!(listed, BLOCKER_PATTERNS.iter().map(|p| p.id).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,130 @@
+use super::cache::ResponseCache;
+use super::client;
+use super::embeddings::JournalEntry;
+use super::error::LlmError;
+use super::http::LlmHttp;
+use super::templates::PromptRegistry;
+use super::types::LLMConfig;
+use crate::db::models::{BugFromEntry, CreateBugInput, EntryType};
+use crate::db::Database;
+
+/// Create a bug from a dream or mind dump: the title and description are generated
+/// from the entry, the bug is linked back to it as `promoted_from` and, when
+/// `draw_cards` is set, the cards suggested by the entry's analysis are drawn
+#[allow(clippy::too_many_arguments)]
+pub async fn create_bug_from_entry(
+    entry_type: EntryType,
+    entry_id: i64,
+    draw_cards: bool,
+    db: &Database,
+    http: &LlmHttp,
+    cache: &ResponseCache<'_>,
+    prompts: &PromptRegistry,
+    config: &LLMConfig,
+) -> Result<BugFromEntry, LlmError> {
+    let entry = match entry_type {
+        EntryType::Dream => db
+            .get_dream(entry_id)
+            .map_err(|e| e.to_string())?
+            .as_ref()
+            .and_then(JournalEntry::from_dream),
+        EntryType::MindDump => db
+            .get_mind_dump(entry_id)
+            .map_err(|e| e.to_string())?
+            .as_ref()
+            .and_then(JournalEntry::from_mind_dump),
+        EntryType::Bug => return Err("Bugs can only be created from dreams or mind dumps".to_string().into()),
+    }
+    .ok_or_else(|| format!("{} {} not found", entry_type.as_str(), entry_id))?;
+
+    let title = client::generate_title(&entry.text, prompts, http, cache, config).await?;
+    let description = client::optimize_description(&entry.text, prompts, http, config).await?;
+
+    let card_ids = if draw_cards { suggested_card_ids(entry_type, entry_id, db)? } else { Vec::new() };
+
+    let input = CreateBugInput {
+        title: title.trim().to_string(),
+        description: description.trim().to_string(),
+        cards_drawn: None,
+        conversation_history: None,
+        notes: None,
+        life_area_id: None,
+    };
+    let (bug, link) = db
+        .create_bug_from_entry(input, entry_type, entry_id, &card_ids)
+        .map_err(|e| e.to_string())?;
+    let cards = db.get_bug_cards(bug.id.expect("created bug has an id")).map_err(|e| e.to_string())?;
+
+    Ok(BugFromEntry { bug, link, cards })
+}
+
+/// Cards linked to the entry's current analysis, if it has one
+fn suggested_card_ids(entry_type: EntryType, entry_id: i64, db: &Database) -> Result<Vec<i64>, String> {
+    let ids = match entry_type {
+        EntryType::Dream => db
+            .get_dream_analysis_with_cards(entry_id)
+            .map_err(|e| e.to_string())?
+            .map(|analysis| analysis.cards.iter().map(|card| card.card_id).collect()),
+        EntryType::MindDump => db
+            .get_mind_dump_analysis_with_cards(entry_id)
+            .map_err(|e| e.to_string())?
+            .map(|analysis| analysis.cards.iter().map(|card| card.card_id).collect()),
+        EntryType::Bug => None,
+    };
+
+    Ok(ids.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::{CreateDreamAnalysisInput, EntryRelation, GenerationProvenance};
+    use crate::db::test_support::{block_on, create_card, create_dream, setup_test_db};
+    use crate::llm::types::LLMProvider;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_bug_from_dream_links_back_and_draws_analysis_cards() {
+        let db = setup_test_db();
+        let dream_id = create_dream(&db, "Locked out", "I could not find the key to my own house").id.unwrap();
+        let analysis = db
+            .create_dream_analysis(CreateDreamAnalysisInput {
+                dream_id,
+                themes_patterns: String::new(),
+                emotional_analysis: String::new(),
+                narrative_summary: "Feeling shut out".to_string(),
+                provenance: GenerationProvenance::default(),
+            })
+            .unwrap();
+        let hermit_id = create_card(&db, "The Hermit");
+        db.link_card_to_dream_analysis(analysis.id.unwrap(), hermit_id, None).unwrap();
+
+        let config = LLMConfig {
+            provider: LLMProvider::Mock,
+            mock_responses: HashMap::from([
+                ("title".to_string(), "Avoiding home".to_string()),
+                ("description".to_string(), "I keep putting off going home.".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let created = block_on(create_bug_from_entry(
+            EntryType::Dream,
+            dream_id,
+            true,
+            &db,
+            &LlmHttp::new(),
+            &ResponseCache::disabled(),
+            &PromptRegistry::default(),
+            &config,
+        ))
+        .unwrap();
+
+        assert_eq!(created.bug.title, "Avoiding home");
+        assert_eq!(created.bug.description, "I keep putting off going home.");
+        assert_eq!(created.link.target_type, EntryType::Dream);
+        assert_eq!(created.link.target_id, dream_id);
+        assert_eq!(created.link.relation, EntryRelation::PromotedFrom);
+        assert_eq!(created.cards.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["The Hermit"]);
+        assert_eq!(db.list_entry_links(EntryType::Dream, dream_id).unwrap(), vec![created.link]);
+    }
+}
@@ -0,0 +1,100 @@
+use super::blocker_patterns;
+use super::bug_retrospective::load_bug_input;
+use super::client;
+use super::error::LlmError;
+use super::http::LlmHttp;
+use super::templates::{PromptRegistry, PromptTemplateId};
+use super::types::LLMConfig;
+use crate::db::models::{BugPattern, CreateBugPatternInput, PatternFrequency};
+use crate::db::Database;
+
+/// Patterns below this confidence are not counted in frequencies unless asked for
+pub const DEFAULT_MIN_CONFIDENCE: f64 = 0.5;
+
+/// Classify the blocker patterns in a bug and store them, replacing earlier ones
+pub async fn classify_bug_patterns(
+    bug_id: i64,
+    db: &Database,
+    http: &LlmHttp,
+    prompts: &PromptRegistry,
+    config: &LLMConfig,
+) -> Result<Vec<BugPattern>, LlmError> {
+    let bug = load_bug_input(bug_id, db)?;
+    let response = client::classify_bug_patterns(&bug, prompts, http, config).await?;
+
+    let patterns = response
+        .patterns
+        .into_iter()
+        .map(|detected| CreateBugPatternInput {
+            pattern: detected.pattern,
+            confidence: detected.confidence,
+            evidence: Some(detected.evidence).filter(|evidence| !evidence.trim().is_empty()),
+        })
+        .collect();
+    let provenance = client::generation_provenance(
+        PromptTemplateId::BugPatternClassification,
+        client::PATTERN_CLASSIFICATION_TEMPERATURE,
+        prompts,
+        config,
+    );
+
+    Ok(db.replace_bug_patterns(bug_id, patterns, provenance).map_err(|e| e.to_string())?)
+}
+
+/// Pattern frequencies across all bugs, labelled with their taxonomy group
+pub fn pattern_frequencies(db: &Database, min_confidence: f64) -> Result<Vec<PatternFrequency>, String> {
+    let mut frequencies = db.get_pattern_frequencies(min_confidence).map_err(|e| e.to_string())?;
+    for frequency in &mut frequencies {
+        frequency.group = blocker_patterns::find(&frequency.pattern).map(|pattern| pattern.group.to_string());
+    }
+    Ok(frequencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::CreateBugInput;
+    use crate::db::test_support::{block_on, setup_test_db};
+    use crate::llm::types::LLMProvider;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_classified_patterns_are_stored_and_grouped() {
+        let db = setup_test_db();
+        let bug_id = db
+            .create_bug(CreateBugInput {
+                title: "Can't pick a job offer".to_string(),
+                description: "Every option feels wrong so I pick none".to_string(),
+                cards_drawn: None,
+                conversation_history: None,
+                notes: None,
+                life_area_id: None,
+            })
+            .unwrap()
+            .id
+            .unwrap();
+
+        let config = LLMConfig {
+            provider: LLMProvider::Mock,
+            mock_responses: HashMap::from([(
+                "pattern_classification".to_string(),
+                r#"{"patterns": [
+                    {"pattern": "choice_overload_paralysis", "confidence": 0.85, "evidence": "I pick none"},
+                    {"pattern": "all_or_nothing_thinking", "confidence": 0.3, "evidence": ""}
+                ]}"#
+                .to_string(),
+            )]),
+            ..Default::default()
+        };
+        let stored = block_on(classify_bug_patterns(bug_id, &db, &LlmHttp::new(), &PromptRegistry::default(), &config)).unwrap();
+
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].evidence.as_deref(), Some("I pick none"));
+        assert_eq!(stored[1].evidence, None);
+
+        let frequencies = pattern_frequencies(&db, DEFAULT_MIN_CONFIDENCE).unwrap();
+        assert_eq!(frequencies.len(), 1);
+        assert_eq!(frequencies[0].pattern, "choice_overload_paralysis");
+        assert_eq!(frequencies[0].group.as_deref(), Some("Cognitive Biases"));
+    }
+}
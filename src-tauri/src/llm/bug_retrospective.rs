@@ -0,0 +1,159 @@
+use super::client;
+use super::error::LlmError;
+use super::http::LlmHttp;
+use super::templates::{PromptRegistry, PromptTemplateId};
+use super::types::LLMConfig;
+use crate::db::models::{Bug, BugRetrospective, CreateBugRetrospectiveInput};
+use crate::db::Database;
+use serde_json::Value;
+
+/// Generate and store a retrospective of the bug, replacing any earlier one
+pub async fn generate_bug_retrospective(
+    bug_id: i64,
+    db: &Database,
+    http: &LlmHttp,
+    prompts: &PromptRegistry,
+    config: &LLMConfig,
+) -> Result<BugRetrospective, LlmError> {
+    let bug = load_bug_input(bug_id, db)?;
+    let response = client::generate_bug_retrospective(&bug, prompts, http, config).await?;
+
+    let retrospective = db
+        .create_bug_retrospective(CreateBugRetrospectiveInput {
+            bug_id,
+            root_pattern: response.root_pattern,
+            blocker_pattern: response.blocker_pattern,
+            what_helped: response.what_helped,
+            follow_ups: response.follow_ups,
+            provenance: client::generation_provenance(
+                PromptTemplateId::BugRetrospective,
+                client::BUG_RETROSPECTIVE_TEMPERATURE,
+                prompts,
+                config,
+            ),
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(retrospective)
+}
+
+/// The stored bug and its cards as prompt text
+pub(super) fn load_bug_input(bug_id: i64, db: &Database) -> Result<String, String> {
+    let bug = db
+        .get_bug(bug_id)
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Bug {} not found", bug_id))?;
+    let cards: Vec<String> = db
+        .get_bug_cards(bug_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|card| card.name)
+        .collect();
+
+    Ok(bug_input(&bug, &cards))
+}
+
+/// The bug as prompt text. `conversation_history` and `notes` are the JSON arrays
+/// the frontend stores; entries without text are skipped.
+fn bug_input(bug: &Bug, cards: &[String]) -> String {
+    let mut text = format!("Title: {}\nStatus: {}\n\nDescription:\n{}", bug.title, bug.status, bug.description);
+
+    if !cards.is_empty() {
+        text.push_str(&format!("\n\nCards drawn: {}", cards.join(", ")));
+    }
+
+    let conversation: Vec<String> = json_items(bug.conversation_history.as_deref())
+        .iter()
+        .filter_map(|message| {
+            let content = message.get("content")?.as_str()?.trim();
+            let role = message.get("role").and_then(Value::as_str).unwrap_or("user");
+            (!content.is_empty()).then(|| format!("{}: {}", role, content))
+        })
+        .collect();
+    if !conversation.is_empty() {
+        text.push_str("\n\nDiscovery conversation:\n");
+        text.push_str(&conversation.join("\n"));
+    }
+
+    let notes: Vec<String> = json_items(bug.notes.as_deref())
+        .iter()
+        .filter_map(|note| note.get("content")?.as_str())
+        .map(str::trim)
+        .filter(|content| !content.is_empty())
+        .map(|content| format!("- {}", content))
+        .collect();
+    if !notes.is_empty() {
+        text.push_str("\n\nNotes:\n");
+        text.push_str(&notes.join("\n"));
+    }
+
+    text
+}
+
+fn json_items(json: Option<&str>) -> Vec<Value> {
+    json.and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::CreateBugInput;
+    use crate::db::test_support::{block_on, setup_test_db};
+    use crate::llm::types::LLMProvider;
+    use std::collections::HashMap;
+
+    fn create_bug(db: &Database) -> Bug {
+        db.create_bug(CreateBugInput {
+            title: "Never finishing drafts".to_string(),
+            description: "I abandon every draft at 80%".to_string(),
+            cards_drawn: None,
+            conversation_history: Some(
+                r#"[{"role": "user", "content": "It has to be perfect", "timestamp": "2026-01-01T00:00:00Z"}]"#.to_string(),
+            ),
+            notes: Some(r#"[{"content": "Shipped one rough draft", "timestamp": "2026-01-02T00:00:00Z"}]"#.to_string()),
+            life_area_id: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_input_includes_conversation_and_notes() {
+        let db = setup_test_db();
+        let text = bug_input(&create_bug(&db), &["The Fool".to_string()]);
+
+        assert!(text.contains("Description:\nI abandon every draft at 80%"));
+        assert!(text.contains("Cards drawn: The Fool"));
+        assert!(text.contains("user: It has to be perfect"));
+        assert!(text.contains("- Shipped one rough draft"));
+    }
+
+    #[test]
+    fn test_retrospective_rejects_unknown_pattern_then_stores() {
+        let db = setup_test_db();
+        let bug_id = create_bug(&db).id.unwrap();
+
+        let response = |pattern: &str| {
+            format!(
+                r#"{{"root_pattern": "Perfectionism", "blocker_pattern": "{}", "what_helped": ["Shipping rough"], "follow_ups": []}}"#,
+                pattern
+            )
+        };
+        let config = LLMConfig {
+            provider: LLMProvider::Mock,
+            mock_responses: HashMap::from([("bug_retrospective".to_string(), response("perfectionism"))]),
+            ..Default::default()
+        };
+        let prompts = PromptRegistry::default();
+        let err = block_on(generate_bug_retrospective(bug_id, &db, &LlmHttp::new(), &prompts, &config)).unwrap_err();
+        assert!(matches!(err, LlmError::InvalidResponse(_)));
+
+        let config = LLMConfig {
+            mock_responses: HashMap::from([("bug_retrospective".to_string(), response("all_or_nothing_thinking"))]),
+            ..config
+        };
+        let stored = block_on(generate_bug_retrospective(bug_id, &db, &LlmHttp::new(), &prompts, &config)).unwrap();
+        assert_eq!(stored.blocker_pattern, "all_or_nothing_thinking");
+        assert_eq!(stored.provenance.prompt_version.as_deref(), Some("bug_retrospective@v1"));
+        assert_eq!(db.get_bug_retrospective(bug_id).unwrap().unwrap().id, stored.id);
+    }
+}
@@ -0,0 +1,149 @@
+use super::client::resolved_model;
+use super::error::LlmError;
+use super::hash::Fnv1a;
+use super::http::Operation;
+use super::types::{CacheSettings, LLMConfig};
+use crate::db::models::CreateCachedResponseInput;
+use crate::db::Database;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+
+/// Looks up and stores responses in `llm_response_cache` when the user has enabled caching.
+/// Cache failures are logged and never fail the request.
+pub struct ResponseCache<'a> {
+    db: Option<&'a Database>,
+    settings: CacheSettings,
+}
+
+impl<'a> ResponseCache<'a> {
+    pub fn new(db: &'a Database, settings: &CacheSettings) -> Self {
+        ResponseCache {
+            db: Some(db),
+            settings: settings.clone(),
+        }
+    }
+
+    /// A cache that always calls through, for callers without a database
+    pub fn disabled() -> Self {
+        ResponseCache {
+            db: None,
+            settings: CacheSettings::default(),
+        }
+    }
+
+    /// Return the cached result for `parts` or compute and store it.
+    /// `parts` are the rendered prompt and any other inputs that change the output.
+    pub async fn get_or_compute<T, F, Fut>(
+        &self,
+        operation: Operation,
+        config: &LLMConfig,
+        parts: &[&str],
+        compute: F,
+    ) -> Result<T, LlmError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, LlmError>>,
+    {
+        let (Some(db), true) = (self.db, self.settings.enabled) else {
+            return compute().await;
+        };
+        let Some(model) = resolved_model(config) else {
+            return compute().await;
+        };
+
+        let key = cache_key(operation, config.provider.as_str(), &model, parts);
+        let ttl = chrono::Duration::hours(self.settings.ttl_hours as i64);
+
+        match db.get_cached_response(&key, ttl) {
+            Ok(Some(cached)) => match serde_json::from_str(&cached) {
+                Ok(value) => return Ok(value),
+                Err(e) => eprintln!("Ignoring unreadable cached {} response: {}", operation.as_str(), e),
+            },
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to read LLM response cache: {}", e),
+        }
+
+        let value = compute().await?;
+
+        let stored = serde_json::to_string(&value).map_err(|e| e.to_string()).and_then(|response| {
+            db.put_cached_response(
+                &CreateCachedResponseInput {
+                    cache_key: key,
+                    operation: operation.as_str().to_string(),
+                    provider: config.provider.as_str().to_string(),
+                    model,
+                    response,
+                },
+                self.settings.max_entries,
+            )
+            .map_err(|e| e.to_string())
+        });
+        if let Err(e) = stored {
+            eprintln!("Failed to write LLM response cache: {}", e);
+        }
+
+        Ok(value)
+    }
+}
+
+/// Hex 64-bit FNV-1a over the provider, model, operation and inputs, NUL-separated
+pub(super) fn cache_key(operation: Operation, provider: &str, model: &str, parts: &[&str]) -> String {
+    let fields = [provider, model, operation.as_str()].into_iter().chain(parts.iter().copied());
+
+    let mut hasher = Fnv1a::new();
+    for field in fields {
+        hasher.write(field.as_bytes());
+        hasher.write(&[0]);
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{block_on, setup_test_db};
+    use crate::llm::LLMProvider;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_cache_key_depends_on_every_input() {
+        let base = cache_key(Operation::Title, "openai", "gpt-4o", &["prompt"]);
+
+        assert_eq!(base, cache_key(Operation::Title, "openai", "gpt-4o", &["prompt"]));
+        assert_ne!(base, cache_key(Operation::Title, "openai", "gpt-4o-mini", &["prompt"]));
+        assert_ne!(base, cache_key(Operation::CardCommentary, "openai", "gpt-4o", &["prompt"]));
+        assert_ne!(base, cache_key(Operation::Title, "openai", "gpt-4o", &["prompt!"]));
+        assert_ne!(
+            cache_key(Operation::Title, "openai", "gpt-4o", &["ab", "c"]),
+            cache_key(Operation::Title, "openai", "gpt-4o", &["a", "bc"])
+        );
+    }
+
+    #[test]
+    fn test_second_call_is_served_from_cache_only_when_enabled() {
+        let db = setup_test_db();
+        let config = LLMConfig {
+            provider: LLMProvider::Ollama,
+            ..Default::default()
+        };
+        let calls = Cell::new(0);
+        let compute = || async {
+            calls.set(calls.get() + 1);
+            Ok::<_, LlmError>("A Title".to_string())
+        };
+
+        let disabled = ResponseCache::new(&db, &CacheSettings::default());
+        block_on(disabled.get_or_compute(Operation::Title, &config, &["dream"], compute)).unwrap();
+        block_on(disabled.get_or_compute(Operation::Title, &config, &["dream"], compute)).unwrap();
+        assert_eq!(calls.get(), 2);
+
+        let enabled = ResponseCache::new(&db, &CacheSettings { enabled: true, ..Default::default() });
+        block_on(enabled.get_or_compute(Operation::Title, &config, &["dream"], compute)).unwrap();
+        let cached = block_on(enabled.get_or_compute(Operation::Title, &config, &["dream"], compute)).unwrap();
+        assert_eq!(cached, "A Title");
+        assert_eq!(calls.get(), 3);
+    }
+}
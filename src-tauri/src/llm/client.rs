@@ -1,11 +1,17 @@
-use super::types::{LLMConfig, LLMProvider, GenerateDreamAnalysisResponse, GenerateCreativePromptsResponse};
-use super::prompts::{
-    TITLE_GENERATION_PROMPT, DESCRIPTION_OPTIMIZATION_PROMPT, CARD_COMMENTARY_PROMPT,
-    CARD_COMMENTARY_WITH_CONTEXT_PROMPT, MULTIPLE_CARDS_COMMENTARY_PROMPT,
-    DISCOVERY_CHAT_SYSTEM_PROMPT, DREAM_ANALYSIS_PROMPT, CREATIVE_PROMPTS_GENERATION,
-};
+use super::types::{FixtureMode, LLMConfig, LLMProvider, GenerateDreamAnalysisResponse, GenerateCreativePromptsResponse, MindDumpAnalysisResponse, BugRetrospectiveResponse, BugPatternClassificationResponse};
+use super::error::LlmError;
+use super::cache::ResponseCache;
+use super::fixtures::{self, FixtureResponse};
+use super::http::{LlmHttp, Operation};
+use super::mock;
+use super::blocker_patterns;
+use super::usage;
+use super::structured::{self, OutputSchema};
+use super::templates::{PromptRegistry, PromptTemplateId};
+use crate::db::models::{GenerationProvenance, LifeArea};
 use reqwest;
 use serde_json::{json, Value};
+use std::time::Duration;
 
 fn map_ollama_model(model_name: &str) -> &str {
     match model_name {
@@ -35,21 +41,229 @@ fn map_anthropic_model(model_name: &str) -> &str {
     }
 }
 
-pub async fn generate_title(content: &str, config: &LLMConfig) -> Result<String, String> {
+/// Sampling temperatures for stored generations, recorded alongside their output
+pub const DREAM_ANALYSIS_TEMPERATURE: f64 = 0.7;
+pub const CREATIVE_PROMPTS_TEMPERATURE: f64 = 0.7;
+pub const REFLECTION_TEMPERATURE: f64 = 0.7;
+pub const MIND_DUMP_ANALYSIS_TEMPERATURE: f64 = 0.7;
+pub const BUG_RETROSPECTIVE_TEMPERATURE: f64 = 0.5;
+pub const PATTERN_CLASSIFICATION_TEMPERATURE: f64 = 0.2;
+
+/// The model name actually sent to the configured provider
+pub fn resolved_model(config: &LLMConfig) -> Option<String> {
     match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => generate_title_ollama(content, config).await,
-        LLMProvider::OpenAI => generate_title_openai(content, config).await,
-        LLMProvider::Anthropic => generate_title_anthropic(content, config).await,
+        LLMProvider::Disabled => None,
+        LLMProvider::Ollama => Some(map_ollama_model(&config.ollama_model).to_string()),
+        LLMProvider::OpenAI => Some(map_openai_model(&config.openai_model).to_string()),
+        LLMProvider::Anthropic => Some(map_anthropic_model(&config.anthropic_model).to_string()),
+        LLMProvider::Mock => Some("mock".to_string()),
+    }
+}
+
+const MAX_ATTEMPTS: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Send a provider request and return its JSON body, recording its token usage.
+/// Transient failures (rate limits, timeouts, 5xx) are retried with exponential backoff,
+/// honouring the provider's `Retry-After` when it sends one.
+pub(super) async fn send_json(
+    http: &LlmHttp,
+    config: &LLMConfig,
+    operation: Operation,
+    provider: &'static str,
+    request: reqwest::RequestBuilder,
+    model: &str,
+) -> Result<Value, LlmError> {
+    let mut attempt = 1;
+
+    loop {
+        let attempt_request = request
+            .try_clone()
+            .ok_or_else(|| LlmError::Other("Request cannot be retried".to_string()))?;
+
+        match send_once(config, operation, provider, attempt_request, model).await {
+            Ok(data) => {
+                // Replayed calls cost nothing
+                if config.fixtures.mode != FixtureMode::Replay {
+                    http.record_usage(usage::from_response(provider, model, operation, &data));
+                }
+                return Ok(data);
+            }
+            Err(error) if error.is_transient() && attempt < MAX_ATTEMPTS => {
+                let backoff = INITIAL_BACKOFF * 2u32.pow(attempt - 1);
+                let delay = error.retry_after().unwrap_or(backoff).min(MAX_BACKOFF);
+                eprintln!("{} (attempt {}/{}), retrying in {:?}", error, attempt, MAX_ATTEMPTS, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Send a request once, or serve it from a fixture in replay mode.
+/// In record mode the redacted request and response are written to a fixture.
+pub(super) async fn send_once(
+    config: &LLMConfig,
+    operation: Operation,
+    provider: &'static str,
+    request: reqwest::RequestBuilder,
+    model: &str,
+) -> Result<Value, LlmError> {
+    let (client, request) = request.build_split();
+    let request = request.map_err(|e| LlmError::Other(format!("Failed to build {} request: {}", provider, e)))?;
+
+    let response = match config.fixtures.mode {
+        FixtureMode::Off => execute(provider, client, request).await?,
+        FixtureMode::Record => {
+            let recorded = fixtures::describe(config, &request);
+            let response = execute(provider, client, request).await?;
+            if let Err(e) = fixtures::record(config, operation, provider, model, recorded, &response) {
+                eprintln!("Failed to record LLM fixture: {}", e);
+            }
+            response
+        }
+        FixtureMode::Replay => {
+            fixtures::replay(&config.fixtures, operation, provider, model, &fixtures::describe(config, &request))?
+        }
+    };
+
+    if !(200..300).contains(&response.status) {
+        let retry_after = response.retry_after_secs.map(Duration::from_secs);
+        return Err(LlmError::from_status(provider, response.status, retry_after, &response.body_text(), model));
+    }
+
+    match response.body {
+        Value::String(text) => serde_json::from_str(&text)
+            .map_err(|e| LlmError::InvalidResponse(format!("Failed to parse {} response: {}", provider, e))),
+        body => Ok(body),
+    }
+}
+
+async fn execute(provider: &'static str, client: reqwest::Client, request: reqwest::Request) -> Result<FixtureResponse, LlmError> {
+    let url = request.url().to_string();
+
+    let response = client
+        .execute(request)
+        .await
+        .map_err(|e| LlmError::from_transport(provider, &url, &e))?;
+
+    let status = response.status().as_u16();
+    let retry_after_secs = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+    let text = response
+        .text()
+        .await
+        .map_err(|e| LlmError::from_transport(provider, &url, &e))?;
+
+    Ok(FixtureResponse::new(status, retry_after_secs, text))
+}
+
+/// Add the strictest `response_format` the model supports.
+/// Older chat models get no format and are handled by text parsing alone.
+fn with_openai_response_format(mut body: Value, model: &str, schema: &OutputSchema) -> Value {
+    let supports_schema = ["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4"]
+        .iter()
+        .any(|prefix| model.starts_with(prefix));
+    let supports_json_object = model.starts_with("gpt-4-turbo") || model.starts_with("gpt-3.5-turbo");
+
+    if supports_schema {
+        body["response_format"] = json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": schema.name,
+                "strict": true,
+                "schema": schema.schema
+            }
+        });
+    } else if supports_json_object {
+        body["response_format"] = json!({ "type": "json_object" });
+    }
+
+    body
+}
+
+// Anthropic has no JSON mode; forcing a single tool call gets schema-shaped input instead
+fn anthropic_tool(schema: &OutputSchema) -> Value {
+    json!({
+        "name": schema.name,
+        "description": schema.description,
+        "input_schema": schema.schema
+    })
+}
+
+/// The forced tool call's input as JSON text, or the first text block if the model answered in prose
+fn anthropic_structured_output(data: &Value) -> Option<String> {
+    let content = data.get("content").and_then(|v| v.as_array())?;
+
+    content
+        .iter()
+        .find(|block| block.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+        .and_then(|block| block.get("input"))
+        .map(|input| input.to_string())
+        .or_else(|| {
+            content
+                .iter()
+                .find_map(|block| block.get("text").and_then(|v| v.as_str()))
+                .map(|text| text.to_string())
+        })
+}
+
+/// Describe how a stored generation was produced with the current settings
+pub fn generation_provenance(
+    template: PromptTemplateId,
+    temperature: f64,
+    prompts: &PromptRegistry,
+    config: &LLMConfig,
+) -> GenerationProvenance {
+    GenerationProvenance {
+        provider: Some(config.provider.as_str().to_string()),
+        model: resolved_model(config),
+        prompt_version: Some(prompts.version(template)),
+        temperature: Some(temperature),
     }
 }
 
-pub async fn optimize_description(content: &str, config: &LLMConfig) -> Result<String, String> {
+pub async fn generate_title(
+    content: &str,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    cache: &ResponseCache<'_>,
+    config: &LLMConfig,
+) -> Result<String, LlmError> {
+    let instructions = prompts.render(PromptTemplateId::TitleGeneration, &[])?;
+
+    cache
+        .get_or_compute(Operation::Title, config, &[&instructions, content], || async {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(Operation::Title, config),
+                LLMProvider::Ollama => generate_title_ollama(&instructions, content, http, config).await,
+                LLMProvider::OpenAI => generate_title_openai(&instructions, content, http, config).await,
+                LLMProvider::Anthropic => generate_title_anthropic(&instructions, content, http, config).await,
+            }
+        })
+        .await
+}
+
+pub async fn optimize_description(
+    content: &str,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<String, LlmError> {
+    let instructions = prompts.render(PromptTemplateId::DescriptionOptimization, &[])?;
+
     match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => optimize_description_ollama(content, config).await,
-        LLMProvider::OpenAI => optimize_description_openai(content, config).await,
-        LLMProvider::Anthropic => optimize_description_anthropic(content, config).await,
+        LLMProvider::Disabled => Err(LlmError::Disabled),
+        LLMProvider::Mock => mock::respond(Operation::Description, config),
+        LLMProvider::Ollama => optimize_description_ollama(&instructions, content, http, config).await,
+        LLMProvider::OpenAI => optimize_description_openai(&instructions, content, http, config).await,
+        LLMProvider::Anthropic => optimize_description_anthropic(&instructions, content, http, config).await,
     }
 }
 
@@ -57,61 +271,164 @@ pub async fn comment_on_card(
     card_name: &str,
     card_question: &str,
     card_meaning: &str,
-    life_area: &str,
+    life_area: &LifeArea,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
-    match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => comment_on_card_ollama(card_name, card_question, card_meaning, life_area, config).await,
-        LLMProvider::OpenAI => comment_on_card_openai(card_name, card_question, card_meaning, life_area, config).await,
-        LLMProvider::Anthropic => comment_on_card_anthropic(card_name, card_question, card_meaning, life_area, config).await,
-    }
+) -> Result<String, LlmError> {
+    comment_on_card_with_context(
+        card_name,
+        card_question,
+        card_meaning,
+        life_area,
+        &[],
+        prompts,
+        http,
+        &ResponseCache::disabled(),
+        config,
+    )
+    .await
 }
 
 pub async fn comment_on_multiple_cards(
     cards: &[Value],
-    life_area: &str,
+    life_area: &LifeArea,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => comment_on_multiple_cards_ollama(cards, life_area, config).await,
-        LLMProvider::OpenAI => comment_on_multiple_cards_openai(cards, life_area, config).await,
-        LLMProvider::Anthropic => comment_on_multiple_cards_anthropic(cards, life_area, config).await,
+) -> Result<std::collections::HashMap<String, String>, LlmError> {
+    let prompt = multiple_cards_prompt(cards, life_area, prompts)?;
+    request_multiple_cards_commentary(&prompt, cards, http, config).await
+}
+
+fn multiple_cards_prompt(cards: &[Value], life_area: &LifeArea, prompts: &PromptRegistry) -> Result<String, LlmError> {
+    let mut cards_list = String::new();
+    for card in cards {
+        let id = card.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+        let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
+        let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
+        cards_list.push_str(&format!(
+            "\nCard {}: {}\nQuestion: {}\nMeaning: {}\n",
+            id, name, question, meaning
+        ));
+        if let Some(insight) = card_life_area_insight(name, &life_area.key) {
+            cards_list.push_str(&format!("In the {} area: {}\n", life_area.name, insight));
+        }
     }
+
+    Ok(prompts.render(
+        PromptTemplateId::MultipleCardsCommentary,
+        &[("life_area", &life_area.name), ("cards_list", &cards_list)],
+    )?)
 }
 
+async fn request_multiple_cards_commentary(
+    prompt: &str,
+    cards: &[Value],
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<std::collections::HashMap<String, String>, LlmError> {
+    let card_ids: Vec<String> = cards
+        .iter()
+        .map(|card| card.get("id").and_then(|v| v.as_u64()).unwrap_or(0).to_string())
+        .collect();
+    let schema = &structured::card_commentary_schema(&card_ids);
+
+    structured::request_with_retry(
+        prompt,
+        |prompt| async move {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(Operation::CardCommentary, config),
+                LLMProvider::Ollama => comment_on_multiple_cards_ollama(&prompt, schema, http, config).await,
+                LLMProvider::OpenAI => comment_on_multiple_cards_openai(&prompt, schema, http, config).await,
+                LLMProvider::Anthropic => comment_on_multiple_cards_anthropic(&prompt, schema, http, config).await,
+            }
+        },
+        |commentary| structured::validate_card_commentary(commentary, &card_ids),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn comment_on_card_with_context(
     card_name: &str,
     card_question: &str,
     card_meaning: &str,
-    life_area: &str,
+    life_area: &LifeArea,
     selected_cards: &[Value],
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    cache: &ResponseCache<'_>,
     config: &LLMConfig,
-) -> Result<String, String> {
-    match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => comment_on_card_with_context_ollama(card_name, card_question, card_meaning, life_area, selected_cards, config).await,
-        LLMProvider::OpenAI => comment_on_card_with_context_openai(card_name, card_question, card_meaning, life_area, selected_cards, config).await,
-        LLMProvider::Anthropic => comment_on_card_with_context_anthropic(card_name, card_question, card_meaning, life_area, selected_cards, config).await,
-    }
+) -> Result<String, LlmError> {
+    let life_area_insight = card_life_area_insight(card_name, &life_area.key)
+        .or_else(|| life_area.description.clone())
+        .unwrap_or_else(|| "No specific insight for this area; rely on the card's meaning.".to_string());
+    let card_variables = [
+        ("life_area", life_area.name.as_str()),
+        ("card_name", card_name),
+        ("card_question", card_question),
+        ("card_meaning", card_meaning),
+        ("life_area_insight", &life_area_insight),
+    ];
+
+    let prompt = if selected_cards.is_empty() {
+        prompts.render(PromptTemplateId::CardCommentary, &card_variables)?
+    } else {
+        let mut selected_cards_list = String::new();
+        for card in selected_cards {
+            let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
+            let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
+            selected_cards_list.push_str(&format!(
+                "- {}\n  Question: {}\n  Meaning: {}\n",
+                name, question, meaning
+            ));
+        }
+
+        let mut variables = card_variables.to_vec();
+        variables.push(("selected_cards_list", &selected_cards_list));
+        prompts.render(PromptTemplateId::CardCommentaryWithContext, &variables)?
+    };
+
+    cache
+        .get_or_compute(Operation::CardCommentary, config, &[&prompt], || async {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(Operation::CardCommentary, config),
+                LLMProvider::Ollama => comment_on_card_ollama(&prompt, http, config).await,
+                LLMProvider::OpenAI => comment_on_card_openai(&prompt, http, config).await,
+                LLMProvider::Anthropic => comment_on_card_anthropic(&prompt, http, config).await,
+            }
+        })
+        .await
 }
 
+// Selected cards are accepted for API symmetry but the multiple-cards prompt
+// does not use them yet.
+#[allow(clippy::too_many_arguments)]
 pub async fn comment_on_multiple_cards_with_context(
     cards: &[Value],
-    life_area: &str,
-    selected_cards: &[Value],
+    life_area: &LifeArea,
+    _selected_cards: &[Value],
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    cache: &ResponseCache<'_>,
     config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => comment_on_multiple_cards_with_context_ollama(cards, life_area, selected_cards, config).await,
-        LLMProvider::OpenAI => comment_on_multiple_cards_with_context_openai(cards, life_area, selected_cards, config).await,
-        LLMProvider::Anthropic => comment_on_multiple_cards_with_context_anthropic(cards, life_area, selected_cards, config).await,
-    }
+) -> Result<std::collections::HashMap<String, String>, LlmError> {
+    let prompt = multiple_cards_prompt(cards, life_area, prompts)?;
+
+    cache
+        .get_or_compute(Operation::CardCommentary, config, &[&prompt], || {
+            request_multiple_cards_commentary(&prompt, cards, http, config)
+        })
+        .await
 }
 
-pub async fn chat_with_history_with_profile(
+#[allow(clippy::too_many_arguments)]
+pub async fn chat_with_history(
     user_message: &str,
     messages: &[Value],
     card_name: &str,
@@ -119,59 +436,95 @@ pub async fn chat_with_history_with_profile(
     card_meaning: &str,
     card_insights: &str,
     life_area: &str,
-    user_name: &str,
-    zodiac_sign: Option<&str>,
-    mbti_type: Option<&str>,
     selected_cards: &[Value],
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<String, LlmError> {
+    // Build selected cards context
+    let mut selected_cards_context = String::new();
+    for card in selected_cards {
+        if let (Some(name), Some(question), Some(commentary)) = (
+            card.get("name").and_then(|v| v.as_str()),
+            card.get("card_question").and_then(|v| v.as_str()),
+            card.get("commentary").and_then(|v| v.as_str())
+        ) {
+            selected_cards_context.push_str(&format!(
+                "- {}: {}\n  Commentary: {}\n",
+                name, question, commentary
+            ));
+        }
+    }
+
+    let mut system_prompt = prompts.render(
+        PromptTemplateId::DiscoveryChat,
+        &[
+            ("life_area", life_area),
+            ("card_name", card_name),
+            ("card_question", card_question),
+            ("card_meaning", card_meaning),
+            (
+                "selected_cards_context",
+                if selected_cards_context.is_empty() { "No other cards selected yet." } else { &selected_cards_context },
+            ),
+        ],
+    )?;
+
+    if !card_insights.is_empty() {
+        system_prompt.push_str(&format!("\n\nCard Insights (generated for this life area):\n{}", card_insights));
+    }
+
+    chat_with_system_prompt(&system_prompt, user_message, messages, http, config).await
+}
+
+/// Continue a conversation under an already rendered system prompt
+pub async fn chat_with_system_prompt(
+    system_prompt: &str,
+    user_message: &str,
+    messages: &[Value],
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
+) -> Result<String, LlmError> {
     match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => chat_with_history_ollama(user_message, messages, card_name, card_question, card_meaning, card_insights, life_area, user_name, zodiac_sign, mbti_type, selected_cards, config).await,
-        LLMProvider::OpenAI => chat_with_history_openai(user_message, messages, card_name, card_question, card_meaning, card_insights, life_area, user_name, zodiac_sign, mbti_type, selected_cards, config).await,
-        LLMProvider::Anthropic => chat_with_history_anthropic(user_message, messages, card_name, card_question, card_meaning, card_insights, life_area, user_name, zodiac_sign, mbti_type, selected_cards, config).await,
+        LLMProvider::Disabled => Err(LlmError::Disabled),
+        LLMProvider::Mock => mock::respond(Operation::Chat, config),
+        LLMProvider::Ollama => chat_with_history_ollama(system_prompt, user_message, messages, http, config).await,
+        LLMProvider::OpenAI => chat_with_history_openai(system_prompt, user_message, messages, http, config).await,
+        LLMProvider::Anthropic => chat_with_history_anthropic(system_prompt, user_message, messages, http, config).await,
     }
 }
 
-async fn generate_title_ollama(content: &str, config: &LLMConfig) -> Result<String, String> {
-    let client = reqwest::Client::new();
+async fn generate_title_ollama(instructions: &str, content: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
     let url = format!("{}/api/generate", config.ollama_url);
     let model = map_ollama_model(&config.ollama_model);
 
-    let prompt = format!("{}\n\n{}", TITLE_GENERATION_PROMPT, content);
+    let prompt = format!("{}\n\n{}", instructions, content);
 
-    let response = client
+    let request = client
         .post(&url)
+        .timeout(Operation::Title.timeout(&config.network.operation_timeouts))
         .json(&json!({
             "model": model,
             "prompt": prompt,
             "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("Ollama API error: {}", response.status()));
-    }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let data = send_json(http, config, Operation::Title, "Ollama", request, model).await?;
 
     data.get("response")
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Ollama response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))
 }
 
-async fn generate_title_openai(content: &str, config: &LLMConfig) -> Result<String, String> {
-    let client = reqwest::Client::new();
+async fn generate_title_openai(instructions: &str, content: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
     let model = map_openai_model(&config.openai_model);
 
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(Operation::Title.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("Authorization", format!("Bearer {}", config.openai_api_key))
         .json(&json!({
@@ -179,7 +532,7 @@ async fn generate_title_openai(content: &str, config: &LLMConfig) -> Result<Stri
             "messages": [
                 {
                     "role": "system",
-                    "content": TITLE_GENERATION_PROMPT
+                    "content": instructions
                 },
                 {
                     "role": "user",
@@ -188,20 +541,9 @@ async fn generate_title_openai(content: &str, config: &LLMConfig) -> Result<Stri
             ],
             "temperature": 0.7,
             "max_tokens": 20
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error: {}", error_text));
-    }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let data = send_json(http, config, Operation::Title, "OpenAI", request, model).await?;
 
     data.get("choices")
         .and_then(|v| v.as_array())
@@ -210,17 +552,18 @@ async fn generate_title_openai(content: &str, config: &LLMConfig) -> Result<Stri
         .and_then(|msg| msg.get("content"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid OpenAI response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))
 }
 
-async fn generate_title_anthropic(content: &str, config: &LLMConfig) -> Result<String, String> {
-    let client = reqwest::Client::new();
+async fn generate_title_anthropic(instructions: &str, content: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
     let model = map_anthropic_model(&config.anthropic_model);
 
-    let prompt = format!("{}\n\n{}", TITLE_GENERATION_PROMPT, content);
+    let prompt = format!("{}\n\n{}", instructions, content);
 
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(Operation::Title.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("x-api-key", &config.anthropic_api_key)
         .header("anthropic-version", "2023-06-01")
@@ -233,20 +576,9 @@ async fn generate_title_anthropic(content: &str, config: &LLMConfig) -> Result<S
                     "content": prompt
                 }
             ]
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error: {}", error_text));
-    }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+    let data = send_json(http, config, Operation::Title, "Anthropic", request, model).await?;
 
     data.get("content")
         .and_then(|v| v.as_array())
@@ -254,48 +586,40 @@ async fn generate_title_anthropic(content: &str, config: &LLMConfig) -> Result<S
         .and_then(|item| item.get("text"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Anthropic response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))
 }
 
-async fn optimize_description_ollama(content: &str, config: &LLMConfig) -> Result<String, String> {
-    let client = reqwest::Client::new();
+async fn optimize_description_ollama(instructions: &str, content: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
     let url = format!("{}/api/generate", config.ollama_url);
     let model = map_ollama_model(&config.ollama_model);
 
-    let prompt = format!("{}\n\n{}", DESCRIPTION_OPTIMIZATION_PROMPT, content);
+    let prompt = format!("{}\n\n{}", instructions, content);
 
-    let response = client
+    let request = client
         .post(&url)
+        .timeout(Operation::Description.timeout(&config.network.operation_timeouts))
         .json(&json!({
             "model": model,
             "prompt": prompt,
             "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
+        }));
 
-    if !response.status().is_success() {
-        return Err(format!("Ollama API error: {}", response.status()));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let data = send_json(http, config, Operation::Description, "Ollama", request, model).await?;
 
     data.get("response")
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Ollama response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))
 }
 
-async fn optimize_description_openai(content: &str, config: &LLMConfig) -> Result<String, String> {
-    let client = reqwest::Client::new();
+async fn optimize_description_openai(instructions: &str, content: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
     let model = map_openai_model(&config.openai_model);
 
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(Operation::Description.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("Authorization", format!("Bearer {}", config.openai_api_key))
         .json(&json!({
@@ -303,7 +627,7 @@ async fn optimize_description_openai(content: &str, config: &LLMConfig) -> Resul
             "messages": [
                 {
                     "role": "system",
-                    "content": DESCRIPTION_OPTIMIZATION_PROMPT
+                    "content": instructions
                 },
                 {
                     "role": "user",
@@ -312,20 +636,9 @@ async fn optimize_description_openai(content: &str, config: &LLMConfig) -> Resul
             ],
             "temperature": 0.7,
             "max_tokens": 2000
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error: {}", error_text));
-    }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let data = send_json(http, config, Operation::Description, "OpenAI", request, model).await?;
 
     data.get("choices")
         .and_then(|v| v.as_array())
@@ -334,17 +647,18 @@ async fn optimize_description_openai(content: &str, config: &LLMConfig) -> Resul
         .and_then(|msg| msg.get("content"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid OpenAI response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))
 }
 
-async fn optimize_description_anthropic(content: &str, config: &LLMConfig) -> Result<String, String> {
-    let client = reqwest::Client::new();
+async fn optimize_description_anthropic(instructions: &str, content: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
     let model = map_anthropic_model(&config.anthropic_model);
 
-    let prompt = format!("{}\n\n{}", DESCRIPTION_OPTIMIZATION_PROMPT, content);
+    let prompt = format!("{}\n\n{}", instructions, content);
 
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(Operation::Description.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("x-api-key", &config.anthropic_api_key)
         .header("anthropic-version", "2023-06-01")
@@ -357,20 +671,9 @@ async fn optimize_description_anthropic(content: &str, config: &LLMConfig) -> Re
                     "content": prompt
                 }
             ]
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error: {}", error_text));
-    }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+    let data = send_json(http, config, Operation::Description, "Anthropic", request, model).await?;
 
     data.get("content")
         .and_then(|v| v.as_array())
@@ -378,75 +681,47 @@ async fn optimize_description_anthropic(content: &str, config: &LLMConfig) -> Re
         .and_then(|item| item.get("text"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Anthropic response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))
 }
 
-// Private implementation functions - these are called through match statements
-// in public functions, so the compiler may not detect their usage. They are
-// definitely used, so we allow dead_code warnings.
-#[allow(dead_code)]
+// Single card commentary implementations (with or without selected cards)
 async fn comment_on_card_ollama(
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    life_area: &str,
+    prompt: &str,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
     let url = format!("{}/api/generate", config.ollama_url);
     let model = map_ollama_model(&config.ollama_model);
 
-    let prompt = CARD_COMMENTARY_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{card_name}", card_name)
-        .replace("{card_question}", card_question)
-        .replace("{card_meaning}", card_meaning);
-
-    let response = client
+    let request = client
         .post(&url)
+        .timeout(Operation::CardCommentary.timeout(&config.network.operation_timeouts))
         .json(&json!({
             "model": model,
             "prompt": prompt,
             "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
+        }));
 
-    if !response.status().is_success() {
-        return Err(format!("Ollama API error: {}", response.status()));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let data = send_json(http, config, Operation::CardCommentary, "Ollama", request, model).await?;
 
     data.get("response")
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Ollama response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))
 }
 
-#[allow(dead_code)]
 async fn comment_on_card_openai(
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    life_area: &str,
+    prompt: &str,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
     let model = map_openai_model(&config.openai_model);
 
-    let prompt = CARD_COMMENTARY_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{card_name}", card_name)
-        .replace("{card_question}", card_question)
-        .replace("{card_meaning}", card_meaning);
-
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(Operation::CardCommentary.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("Authorization", format!("Bearer {}", config.openai_api_key))
         .json(&json!({
@@ -459,20 +734,9 @@ async fn comment_on_card_openai(
             ],
             "temperature": 0.7,
             "max_tokens": 200
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error: {}", error_text));
-    }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let data = send_json(http, config, Operation::CardCommentary, "OpenAI", request, model).await?;
 
     data.get("choices")
         .and_then(|v| v.as_array())
@@ -481,28 +745,20 @@ async fn comment_on_card_openai(
         .and_then(|msg| msg.get("content"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid OpenAI response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))
 }
 
-#[allow(dead_code)]
 async fn comment_on_card_anthropic(
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    life_area: &str,
+    prompt: &str,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
     let model = map_anthropic_model(&config.anthropic_model);
 
-    let prompt = CARD_COMMENTARY_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{card_name}", card_name)
-        .replace("{card_question}", card_question)
-        .replace("{card_meaning}", card_meaning);
-
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(Operation::CardCommentary.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("x-api-key", &config.anthropic_api_key)
         .header("anthropic-version", "2023-06-01")
@@ -515,20 +771,9 @@ async fn comment_on_card_anthropic(
                     "content": prompt
                 }
             ]
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
+        }));
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error: {}", error_text));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+    let data = send_json(http, config, Operation::CardCommentary, "Anthropic", request, model).await?;
 
     data.get("content")
         .and_then(|v| v.as_array())
@@ -536,98 +781,54 @@ async fn comment_on_card_anthropic(
         .and_then(|item| item.get("text"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Anthropic response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))
 }
 
 async fn comment_on_multiple_cards_ollama(
-    cards: &[Value],
-    life_area: &str,
+    prompt: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
     let url = format!("{}/api/generate", config.ollama_url);
     let model = map_ollama_model(&config.ollama_model);
 
-    let mut cards_list = String::new();
-    for card in cards {
-        let id = card.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
-        let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
-        let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
-        let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
-        cards_list.push_str(&format!(
-            "\nCard {}: {}\nQuestion: {}\nMeaning: {}\n",
-            id, name, question, meaning
-        ));
-    }
-
-    let prompt = MULTIPLE_CARDS_COMMENTARY_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{cards_list}", &cards_list);
-
-    let response = client
+    let request = client
         .post(&url)
+        .timeout(Operation::CardCommentary.timeout(&config.network.operation_timeouts))
         .json(&json!({
             "model": model,
             "prompt": prompt,
-            "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Ollama API error: {}", error_text));
-    }
+            "stream": false,
+            "format": schema.schema
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let data = send_json(http, config, Operation::CardCommentary, "Ollama", request, model).await?;
 
     let response_text = data
         .get("response")
         .and_then(|v| v.as_str())
-        .ok_or("Invalid Ollama response format")?;
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))?;
 
-    // Extract JSON from response (in case there's extra text)
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
-
-    serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    Ok(response_text.to_string())
 }
 
 async fn comment_on_multiple_cards_openai(
-    cards: &[Value],
-    life_area: &str,
+    prompt: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
     let model = map_openai_model(&config.openai_model);
 
-    let mut cards_list = String::new();
-    for card in cards {
-        let id = card.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
-        let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
-        let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
-        let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
-        cards_list.push_str(&format!(
-            "\nCard {}: {}\nQuestion: {}\nMeaning: {}\n",
-            id, name, question, meaning
-        ));
-    }
-
-    let prompt = MULTIPLE_CARDS_COMMENTARY_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{cards_list}", &cards_list);
-
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(Operation::CardCommentary.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("Authorization", format!("Bearer {}", config.openai_api_key))
-        .json(&json!({
+        .json(&with_openai_response_format(json!({
             "model": model,
             "messages": [
                 {
@@ -637,20 +838,9 @@ async fn comment_on_multiple_cards_openai(
             ],
             "temperature": 0.7,
             "max_tokens": 500
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error: {}", error_text));
-    }
+        }), model, schema));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let data = send_json(http, config, Operation::CardCommentary, "OpenAI", request, model).await?;
 
     let response_text = data
         .get("choices")
@@ -659,103 +849,53 @@ async fn comment_on_multiple_cards_openai(
         .and_then(|item| item.get("message"))
         .and_then(|msg| msg.get("content"))
         .and_then(|v| v.as_str())
-        .ok_or("Invalid OpenAI response format")?;
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))?;
 
-    // Extract JSON from response (in case there's extra text)
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
-
-    serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    Ok(response_text.to_string())
 }
 
 async fn comment_on_multiple_cards_anthropic(
-    cards: &[Value],
-    life_area: &str,
+    prompt: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
     let model = map_anthropic_model(&config.anthropic_model);
 
-    let mut cards_list = String::new();
-    for card in cards {
-        let id = card.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
-        let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
-        let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
-        let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
-        cards_list.push_str(&format!(
-            "\nCard {}: {}\nQuestion: {}\nMeaning: {}\n",
-            id, name, question, meaning
-        ));
-    }
-
-    let prompt = MULTIPLE_CARDS_COMMENTARY_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{cards_list}", &cards_list);
-
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(Operation::CardCommentary.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("x-api-key", &config.anthropic_api_key)
         .header("anthropic-version", "2023-06-01")
         .json(&json!({
             "model": model,
             "max_tokens": 1000,
+            "tools": [anthropic_tool(schema)],
+            "tool_choice": { "type": "tool", "name": schema.name },
             "messages": [
                 {
                     "role": "user",
                     "content": prompt
                 }
             ]
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error: {}", error_text));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+        }));
 
-    let response_text = data
-        .get("content")
-        .and_then(|v| v.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|item| item.get("text"))
-        .and_then(|v| v.as_str())
-        .ok_or("Invalid Anthropic response format")?;
-
-    // Extract JSON from response (in case there's extra text)
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
+    let data = send_json(http, config, Operation::CardCommentary, "Anthropic", request, model).await?;
 
-    serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    anthropic_structured_output(&data).ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))
 }
 
 // Chat with history implementations
 async fn chat_with_history_ollama(
+    system_prompt: &str,
     user_message: &str,
     messages: &[Value],
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    card_insights: &str,
-    life_area: &str,
-    user_name: &str,
-    zodiac_sign: Option<&str>,
-    mbti_type: Option<&str>,
-    selected_cards: &[Value],
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
     let url = format!("{}/api/generate", config.ollama_url);
     let model = map_ollama_model(&config.ollama_model);
 
@@ -768,131 +908,35 @@ async fn chat_with_history_ollama(
         }
     }
 
-    // Build selected cards context
-    let mut selected_cards_context = String::new();
-    for card in selected_cards {
-        if let (Some(name), Some(question), Some(commentary)) = (
-            card.get("name").and_then(|v| v.as_str()),
-            card.get("card_question").and_then(|v| v.as_str()),
-            card.get("commentary").and_then(|v| v.as_str())
-        ) {
-            selected_cards_context.push_str(&format!(
-                "- {}: {}\n  Commentary: {}\n",
-                name, question, commentary
-            ));
-        }
-    }
-
-    let mut system_prompt = DISCOVERY_CHAT_SYSTEM_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{card_name}", card_name)
-        .replace("{card_question}", card_question)
-        .replace("{card_meaning}", card_meaning)
-        .replace("{selected_cards_context}", if selected_cards_context.is_empty() { "No other cards selected yet." } else { &selected_cards_context });
-    
-    if !card_insights.is_empty() {
-        system_prompt.push_str(&format!("\n\nCard Insights (generated for this life area):\n{}", card_insights));
-    }
-    
-    // Add user profile context
-    if !user_name.is_empty() || zodiac_sign.is_some() || mbti_type.is_some() {
-        system_prompt.push_str("\n\nUser Profile:");
-        if !user_name.is_empty() {
-            system_prompt.push_str(&format!("\n- Name: {}", user_name));
-        }
-        if let Some(sign) = zodiac_sign {
-            system_prompt.push_str(&format!("\n- Zodiac Sign: {}", sign));
-        }
-        if let Some(mbti) = mbti_type {
-            system_prompt.push_str(&format!("\n- MBTI Type: {}", mbti));
-        }
-    }
-
     let prompt = format!("{}\n\nConversation history:\n{}\nUser: {}\n\nAssistant:", system_prompt, conversation, user_message);
 
-    let response = client
+    let request = client
         .post(&url)
+        .timeout(Operation::Chat.timeout(&config.network.operation_timeouts))
         .json(&json!({
             "model": model,
             "prompt": prompt,
             "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
+        }));
 
-    if !response.status().is_success() {
-        return Err(format!("Ollama API error: {}", response.status()));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let data = send_json(http, config, Operation::Chat, "Ollama", request, model).await?;
 
     data.get("response")
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Ollama response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))
 }
 
 async fn chat_with_history_openai(
+    system_prompt: &str,
     user_message: &str,
     messages: &[Value],
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    card_insights: &str,
-    life_area: &str,
-    user_name: &str,
-    zodiac_sign: Option<&str>,
-    mbti_type: Option<&str>,
-    selected_cards: &[Value],
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
     let model = map_openai_model(&config.openai_model);
 
-    // Build selected cards context
-    let mut selected_cards_context = String::new();
-    for card in selected_cards {
-        if let (Some(name), Some(question), Some(commentary)) = (
-            card.get("name").and_then(|v| v.as_str()),
-            card.get("card_question").and_then(|v| v.as_str()),
-            card.get("commentary").and_then(|v| v.as_str())
-        ) {
-            selected_cards_context.push_str(&format!(
-                "- {}: {}\n  Commentary: {}\n",
-                name, question, commentary
-            ));
-        }
-    }
-
-    let mut system_prompt = DISCOVERY_CHAT_SYSTEM_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{card_name}", card_name)
-        .replace("{card_question}", card_question)
-        .replace("{card_meaning}", card_meaning)
-        .replace("{selected_cards_context}", if selected_cards_context.is_empty() { "No other cards selected yet." } else { &selected_cards_context });
-    
-    if !card_insights.is_empty() {
-        system_prompt.push_str(&format!("\n\nCard Insights (generated for this life area):\n{}", card_insights));
-    }
-    
-    // Add user profile context
-    if !user_name.is_empty() || zodiac_sign.is_some() || mbti_type.is_some() {
-        system_prompt.push_str("\n\nUser Profile:");
-        if !user_name.is_empty() {
-            system_prompt.push_str(&format!("\n- Name: {}", user_name));
-        }
-        if let Some(sign) = zodiac_sign {
-            system_prompt.push_str(&format!("\n- Zodiac Sign: {}", sign));
-        }
-        if let Some(mbti) = mbti_type {
-            system_prompt.push_str(&format!("\n- MBTI Type: {}", mbti));
-        }
-    }
-
     // Build message array for OpenAI
     let mut chat_messages = vec![
         json!({
@@ -917,8 +961,9 @@ async fn chat_with_history_openai(
         "content": user_message
     }));
 
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(Operation::Chat.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("Authorization", format!("Bearer {}", config.openai_api_key))
         .json(&json!({
@@ -926,20 +971,9 @@ async fn chat_with_history_openai(
             "messages": chat_messages,
             "temperature": 0.7,
             "max_tokens": 300
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
+        }));
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error: {}", error_text));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let data = send_json(http, config, Operation::Chat, "OpenAI", request, model).await?;
 
     data.get("choices")
         .and_then(|v| v.as_array())
@@ -948,66 +982,19 @@ async fn chat_with_history_openai(
         .and_then(|msg| msg.get("content"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid OpenAI response format".to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))
 }
 
 async fn chat_with_history_anthropic(
+    system_prompt: &str,
     user_message: &str,
     messages: &[Value],
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    card_insights: &str,
-    life_area: &str,
-    user_name: &str,
-    zodiac_sign: Option<&str>,
-    mbti_type: Option<&str>,
-    selected_cards: &[Value],
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
     let model = map_anthropic_model(&config.anthropic_model);
 
-    // Build selected cards context
-    let mut selected_cards_context = String::new();
-    for card in selected_cards {
-        if let (Some(name), Some(question), Some(commentary)) = (
-            card.get("name").and_then(|v| v.as_str()),
-            card.get("card_question").and_then(|v| v.as_str()),
-            card.get("commentary").and_then(|v| v.as_str())
-        ) {
-            selected_cards_context.push_str(&format!(
-                "- {}: {}\n  Commentary: {}\n",
-                name, question, commentary
-            ));
-        }
-    }
-
-    let mut system_prompt = DISCOVERY_CHAT_SYSTEM_PROMPT
-        .replace("{life_area}", life_area)
-        .replace("{card_name}", card_name)
-        .replace("{card_question}", card_question)
-        .replace("{card_meaning}", card_meaning)
-        .replace("{selected_cards_context}", if selected_cards_context.is_empty() { "No other cards selected yet." } else { &selected_cards_context });
-    
-    if !card_insights.is_empty() {
-        system_prompt.push_str(&format!("\n\nCard Insights (generated for this life area):\n{}", card_insights));
-    }
-    
-    // Add user profile context
-    if !user_name.is_empty() || zodiac_sign.is_some() || mbti_type.is_some() {
-        system_prompt.push_str("\n\nUser Profile:");
-        if !user_name.is_empty() {
-            system_prompt.push_str(&format!("\n- Name: {}", user_name));
-        }
-        if let Some(sign) = zodiac_sign {
-            system_prompt.push_str(&format!("\n- Zodiac Sign: {}", sign));
-        }
-        if let Some(mbti) = mbti_type {
-            system_prompt.push_str(&format!("\n- MBTI Type: {}", mbti));
-        }
-    }
-
     // Build message array for Anthropic
     let mut chat_messages = vec![];
 
@@ -1027,8 +1014,9 @@ async fn chat_with_history_anthropic(
         "content": user_message
     }));
 
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(Operation::Chat.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("x-api-key", &config.anthropic_api_key)
         .header("anthropic-version", "2023-06-01")
@@ -1037,236 +1025,9 @@ async fn chat_with_history_anthropic(
             "max_tokens": 300,
             "system": system_prompt,
             "messages": chat_messages
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error: {}", error_text));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
-
-    data.get("content")
-        .and_then(|v| v.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|item| item.get("text"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Anthropic response format".to_string())
-}
-
-// Context-aware card commentary functions
-async fn comment_on_card_with_context_ollama(
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    life_area: &str,
-    selected_cards: &[Value],
-    config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/api/generate", config.ollama_url);
-    let model = map_ollama_model(&config.ollama_model);
-
-    let mut selected_cards_list = String::new();
-    for card in selected_cards {
-        let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
-        let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
-        let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
-        selected_cards_list.push_str(&format!(
-            "- {}\n  Question: {}\n  Meaning: {}\n",
-            name, question, meaning
-        ));
-    }
-
-    let prompt = if selected_cards.is_empty() {
-        CARD_COMMENTARY_PROMPT
-            .replace("{life_area}", life_area)
-            .replace("{card_name}", card_name)
-            .replace("{card_question}", card_question)
-            .replace("{card_meaning}", card_meaning)
-    } else {
-        CARD_COMMENTARY_WITH_CONTEXT_PROMPT
-            .replace("{life_area}", life_area)
-            .replace("{card_name}", card_name)
-            .replace("{card_question}", card_question)
-            .replace("{card_meaning}", card_meaning)
-            .replace("{selected_cards_list}", &selected_cards_list)
-    };
-
-    let response = client
-        .post(&url)
-        .json(&json!({
-            "model": model,
-            "prompt": prompt,
-            "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("Ollama API error: {}", response.status()));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
-
-    data.get("response")
-        .and_then(|v| v.as_str())
-        .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Ollama response format".to_string())
-}
-
-async fn comment_on_card_with_context_openai(
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    life_area: &str,
-    selected_cards: &[Value],
-    config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let model = map_openai_model(&config.openai_model);
-
-    let mut selected_cards_list = String::new();
-    for card in selected_cards {
-        let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
-        let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
-        let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
-        selected_cards_list.push_str(&format!(
-            "- {}\n  Question: {}\n  Meaning: {}\n",
-            name, question, meaning
-        ));
-    }
-
-    let prompt = if selected_cards.is_empty() {
-        CARD_COMMENTARY_PROMPT
-            .replace("{life_area}", life_area)
-            .replace("{card_name}", card_name)
-            .replace("{card_question}", card_question)
-            .replace("{card_meaning}", card_meaning)
-    } else {
-        CARD_COMMENTARY_WITH_CONTEXT_PROMPT
-            .replace("{life_area}", life_area)
-            .replace("{card_name}", card_name)
-            .replace("{card_question}", card_question)
-            .replace("{card_meaning}", card_meaning)
-            .replace("{selected_cards_list}", &selected_cards_list)
-    };
-
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", config.openai_api_key))
-        .json(&json!({
-            "model": model,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ],
-            "temperature": 0.7,
-            "max_tokens": 200
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error: {}", error_text));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
-
-    data.get("choices")
-        .and_then(|v| v.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|choice| choice.get("message"))
-        .and_then(|msg| msg.get("content"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid OpenAI response format".to_string())
-}
-
-async fn comment_on_card_with_context_anthropic(
-    card_name: &str,
-    card_question: &str,
-    card_meaning: &str,
-    life_area: &str,
-    selected_cards: &[Value],
-    config: &LLMConfig,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let model = map_anthropic_model(&config.anthropic_model);
-
-    let mut selected_cards_list = String::new();
-    for card in selected_cards {
-        let name = card.get("name").and_then(|v| v.as_str()).unwrap_or("");
-        let question = card.get("question").and_then(|v| v.as_str()).unwrap_or("");
-        let meaning = card.get("meaning").and_then(|v| v.as_str()).unwrap_or("");
-        selected_cards_list.push_str(&format!(
-            "- {}\n  Question: {}\n  Meaning: {}\n",
-            name, question, meaning
-        ));
-    }
-
-    let prompt = if selected_cards.is_empty() {
-        CARD_COMMENTARY_PROMPT
-            .replace("{life_area}", life_area)
-            .replace("{card_name}", card_name)
-            .replace("{card_question}", card_question)
-            .replace("{card_meaning}", card_meaning)
-    } else {
-        CARD_COMMENTARY_WITH_CONTEXT_PROMPT
-            .replace("{life_area}", life_area)
-            .replace("{card_name}", card_name)
-            .replace("{card_question}", card_question)
-            .replace("{card_meaning}", card_meaning)
-            .replace("{selected_cards_list}", &selected_cards_list)
-    };
-
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
-        .header("Content-Type", "application/json")
-        .header("x-api-key", &config.anthropic_api_key)
-        .header("anthropic-version", "2023-06-01")
-        .json(&json!({
-            "model": model,
-            "max_tokens": 200,
-            "messages": [
-                {
-                    "role": "user",
-                    "content": prompt
-                }
-            ]
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error: {}", error_text));
-    }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+    let data = send_json(http, config, Operation::Chat, "Anthropic", request, model).await?;
 
     data.get("content")
         .and_then(|v| v.as_array())
@@ -1274,39 +1035,11 @@ async fn comment_on_card_with_context_anthropic(
         .and_then(|item| item.get("text"))
         .and_then(|v| v.as_str())
         .map(|s| s.trim().to_string())
-        .ok_or_else(|| "Invalid Anthropic response format".to_string())
-}
-
-// Multiple cards with context implementations
-async fn comment_on_multiple_cards_with_context_ollama(
-    cards: &[Value],
-    life_area: &str,
-    _selected_cards: &[Value],
-    config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    comment_on_multiple_cards_ollama(cards, life_area, config).await
-}
-
-async fn comment_on_multiple_cards_with_context_openai(
-    cards: &[Value],
-    life_area: &str,
-    _selected_cards: &[Value],
-    config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    comment_on_multiple_cards_openai(cards, life_area, config).await
-}
-
-async fn comment_on_multiple_cards_with_context_anthropic(
-    cards: &[Value],
-    life_area: &str,
-    _selected_cards: &[Value],
-    config: &LLMConfig,
-) -> Result<std::collections::HashMap<String, String>, String> {
-    comment_on_multiple_cards_anthropic(cards, life_area, config).await
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))
 }
 
 // Helper to extract just card names and core meanings from cards.json
-fn extract_card_summaries() -> Result<String, String> {
+fn extract_card_summaries() -> Result<String, LlmError> {
     let cards_json = include_str!("../../../src/cards.json");
     let cards_data: Value = serde_json::from_str(cards_json)
         .map_err(|e| format!("Failed to parse cards.json: {}", e))?;
@@ -1326,126 +1059,259 @@ fn extract_card_summaries() -> Result<String, String> {
     Ok(summaries.join("\n"))
 }
 
-// Dream analysis generation
-pub async fn generate_dream_analysis(
-    dream_title: &str,
-    dream_content: &str,
-    sleep_quality: Option<i32>,
-    config: &LLMConfig,
-) -> Result<GenerateDreamAnalysisResponse, String> {
-    match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => generate_dream_analysis_ollama(dream_title, dream_content, sleep_quality, config).await,
-        LLMProvider::OpenAI => generate_dream_analysis_openai(dream_title, dream_content, sleep_quality, config).await,
-        LLMProvider::Anthropic => generate_dream_analysis_anthropic(dream_title, dream_content, sleep_quality, config).await,
-    }
+// The card's `life_area_insights` entry for a built-in area key, if the deck has one
+fn card_life_area_insight(card_name: &str, area_key: &str) -> Option<String> {
+    let cards_json = include_str!("../../../src/cards.json");
+    let cards_data: Value = serde_json::from_str(cards_json).ok()?;
+
+    cards_data
+        .get("cards")?
+        .as_array()?
+        .iter()
+        .find(|card| card.get("name").and_then(|v| v.as_str()) == Some(card_name))?
+        .get("life_area_insights")?
+        .get(area_key)?
+        .as_str()
+        .map(|insight| insight.to_string())
+}
+
+// Names of every card in the deck, as the dream analysis must reference them
+fn deck_card_names() -> Result<Vec<String>, String> {
+    let cards_json = include_str!("../../../src/cards.json");
+    let cards_data: Value = serde_json::from_str(cards_json)
+        .map_err(|e| format!("Failed to parse cards.json: {}", e))?;
+
+    let cards_array = cards_data
+        .get("cards")
+        .and_then(|v| v.as_array())
+        .ok_or("Invalid cards.json structure")?;
+
+    Ok(cards_array
+        .iter()
+        .filter_map(|card| card.get("name").and_then(|v| v.as_str()))
+        .map(|name| name.to_string())
+        .collect())
 }
 
-async fn generate_dream_analysis_ollama(
+// Dream analysis generation
+pub async fn generate_dream_analysis(
     dream_title: &str,
     dream_content: &str,
     sleep_quality: Option<i32>,
+    history: Option<&str>,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<GenerateDreamAnalysisResponse, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-    let url = format!("{}/api/generate", config.ollama_url);
-    let model = map_ollama_model(&config.ollama_model);
-
+) -> Result<GenerateDreamAnalysisResponse, LlmError> {
     // Extract simplified card summaries
     let card_summaries = extract_card_summaries()?;
 
-    // Build the prompt with cards and dream info
-    let prompt = DREAM_ANALYSIS_PROMPT.replace("{CARDS_JSON}", &card_summaries);
+    // Build the prompt with cards
+    let prompt = prompts.render(PromptTemplateId::DreamAnalysis, &[("CARDS_JSON", &card_summaries)])?;
+
+    let deck = deck_card_names()?;
+    let schema = &structured::dream_analysis_schema(&deck);
+    let dream = &dream_analysis_input(dream_title, dream_content, sleep_quality, history);
+
+    structured::request_with_retry(
+        &prompt,
+        |prompt| async move {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(Operation::DreamAnalysis, config),
+                LLMProvider::Ollama => analyze_entry_ollama(Operation::DreamAnalysis, DREAM_ANALYSIS_TEMPERATURE, &prompt, dream, schema, http, config).await,
+                LLMProvider::OpenAI => analyze_entry_openai(Operation::DreamAnalysis, DREAM_ANALYSIS_TEMPERATURE, &prompt, dream, schema, http, config).await,
+                LLMProvider::Anthropic => analyze_entry_anthropic(Operation::DreamAnalysis, DREAM_ANALYSIS_TEMPERATURE, &prompt, dream, schema, http, config).await,
+            }
+        },
+        |analysis| structured::validate_dream_analysis(analysis, &deck),
+    )
+    .await
+}
+
+/// The dream as sent after the analysis prompt, followed by the dreamer's history when given
+fn dream_analysis_input(dream_title: &str, dream_content: &str, sleep_quality: Option<i32>, history: Option<&str>) -> String {
     let sleep_quality_text = match sleep_quality {
         Some(q) => format!("Sleep Quality: {}/5", q),
         None => "Sleep Quality: Not specified".to_string(),
     };
 
-    let full_prompt = format!(
-        "{}\n\nTitle: {}\n{}\n\nContent:\n{}",
-        prompt, dream_title, sleep_quality_text, dream_content
-    );
+    let mut input = format!("Title: {}\n{}\n\nContent:\n{}", dream_title, sleep_quality_text, dream_content);
+    if let Some(history) = history.filter(|h| !h.trim().is_empty()) {
+        input.push_str("\n\n");
+        input.push_str(history);
+    }
+    input
+}
+
+// Mind dump analysis generation
+pub async fn generate_mind_dump_analysis(
+    title: Option<&str>,
+    content: &str,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<MindDumpAnalysisResponse, LlmError> {
+    let card_summaries = extract_card_summaries()?;
+    let prompt = prompts.render(PromptTemplateId::MindDumpAnalysis, &[("CARDS_JSON", &card_summaries)])?;
+
+    let deck = deck_card_names()?;
+    let schema = &structured::mind_dump_analysis_schema(&deck);
+    let mind_dump = &match title.filter(|t| !t.trim().is_empty()) {
+        Some(title) => format!("Title: {}\n\nContent:\n{}", title, content),
+        None => format!("Content:\n{}", content),
+    };
+
+    let operation = Operation::MindDumpAnalysis;
+    let temperature = MIND_DUMP_ANALYSIS_TEMPERATURE;
+    structured::request_with_retry(
+        &prompt,
+        |prompt| async move {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(operation, config),
+                LLMProvider::Ollama => analyze_entry_ollama(operation, temperature, &prompt, mind_dump, schema, http, config).await,
+                LLMProvider::OpenAI => analyze_entry_openai(operation, temperature, &prompt, mind_dump, schema, http, config).await,
+                LLMProvider::Anthropic => analyze_entry_anthropic(operation, temperature, &prompt, mind_dump, schema, http, config).await,
+            }
+        },
+        |analysis| structured::validate_mind_dump_analysis(analysis, &deck),
+    )
+    .await
+}
+
+/// `bug` is the bug's description, cards, conversation and notes as prepared by the caller
+pub async fn generate_bug_retrospective(
+    bug: &str,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<BugRetrospectiveResponse, LlmError> {
+    let prompt = prompts.render(
+        PromptTemplateId::BugRetrospective,
+        &[("blocker_patterns", &blocker_patterns::taxonomy_text())],
+    )?;
+
+    let pattern_ids = blocker_patterns::pattern_ids();
+    let schema = &structured::bug_retrospective_schema(&pattern_ids);
+
+    let operation = Operation::BugRetrospective;
+    let temperature = BUG_RETROSPECTIVE_TEMPERATURE;
+    structured::request_with_retry(
+        &prompt,
+        |prompt| async move {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(operation, config),
+                LLMProvider::Ollama => analyze_entry_ollama(operation, temperature, &prompt, bug, schema, http, config).await,
+                LLMProvider::OpenAI => analyze_entry_openai(operation, temperature, &prompt, bug, schema, http, config).await,
+                LLMProvider::Anthropic => analyze_entry_anthropic(operation, temperature, &prompt, bug, schema, http, config).await,
+            }
+        },
+        |retrospective| structured::validate_bug_retrospective(retrospective, &pattern_ids),
+    )
+    .await
+}
+
+/// `bug` is the bug's description, cards, conversation and notes as prepared by the caller
+pub async fn classify_bug_patterns(
+    bug: &str,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<BugPatternClassificationResponse, LlmError> {
+    let prompt = prompts.render(
+        PromptTemplateId::BugPatternClassification,
+        &[("blocker_patterns", &blocker_patterns::taxonomy_text())],
+    )?;
+
+    let pattern_ids = blocker_patterns::pattern_ids();
+    let schema = &structured::bug_pattern_classification_schema(&pattern_ids);
+
+    let operation = Operation::PatternClassification;
+    let temperature = PATTERN_CLASSIFICATION_TEMPERATURE;
+    structured::request_with_retry(
+        &prompt,
+        |prompt| async move {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(operation, config),
+                LLMProvider::Ollama => analyze_entry_ollama(operation, temperature, &prompt, bug, schema, http, config).await,
+                LLMProvider::OpenAI => analyze_entry_openai(operation, temperature, &prompt, bug, schema, http, config).await,
+                LLMProvider::Anthropic => analyze_entry_anthropic(operation, temperature, &prompt, bug, schema, http, config).await,
+            }
+        },
+        |classification| structured::validate_bug_pattern_classification(classification, &pattern_ids),
+    )
+    .await
+}
+
+// Structured analysis of one journal entry, shared by dreams, mind dumps and bugs
+async fn analyze_entry_ollama(
+    operation: Operation,
+    temperature: f64,
+    prompt: &str,
+    entry: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
+    let url = format!("{}/api/generate", config.ollama_url);
+    let model = map_ollama_model(&config.ollama_model);
+
+    let full_prompt = format!("{}\n\n{}", prompt, entry);
 
     eprintln!("Sending request to Ollama...");
     eprintln!("Model: {}", model);
     eprintln!("Prompt length: {} chars", full_prompt.len());
 
-    let response = client
+    let request = client
         .post(&url)
+        .timeout(operation.timeout(&config.network.operation_timeouts))
         .json(&json!({
             "model": model,
             "prompt": full_prompt,
-            "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
+            "stream": false,
+            "format": schema.schema,
+            "options": { "temperature": temperature }
+        }));
 
-    eprintln!("Received response with status: {}", response.status());
-
-    if !response.status().is_success() {
-        return Err(format!("Ollama API error: {}", response.status()));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let data = send_json(http, config, operation, "Ollama", request, model).await?;
 
     let response_text = data
         .get("response")
         .and_then(|v| v.as_str())
-        .ok_or("Invalid Ollama response format")?;
-
-    // Extract JSON from response
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))?;
 
-    serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    Ok(response_text.to_string())
 }
 
-async fn generate_dream_analysis_openai(
-    dream_title: &str,
-    dream_content: &str,
-    sleep_quality: Option<i32>,
+async fn analyze_entry_openai(
+    operation: Operation,
+    temperature: f64,
+    prompt: &str,
+    entry: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<GenerateDreamAnalysisResponse, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
     let model = map_openai_model(&config.openai_model);
 
-    // Extract simplified card summaries
-    let card_summaries = extract_card_summaries()?;
-
-    // Build the prompt with cards
-    let prompt = DREAM_ANALYSIS_PROMPT.replace("{CARDS_JSON}", &card_summaries);
-    let sleep_quality_text = match sleep_quality {
-        Some(q) => format!("Sleep Quality: {}/5", q),
-        None => "Sleep Quality: Not specified".to_string(),
-    };
-
-    let user_message = format!(
-        "Title: {}\n{}\n\nContent:\n{}",
-        dream_title, sleep_quality_text, dream_content
-    );
+    let user_message = entry;
 
     eprintln!("Sending request to OpenAI API...");
     eprintln!("Model: {}", model);
     eprintln!("System prompt length: {} chars", prompt.len());
     eprintln!("User message length: {} chars", user_message.len());
 
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(operation.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("Authorization", format!("Bearer {}", config.openai_api_key))
-        .json(&json!({
+        .json(&with_openai_response_format(json!({
             "model": model,
             "messages": [
                 {
@@ -1457,24 +1323,11 @@ async fn generate_dream_analysis_openai(
                     "content": user_message
                 }
             ],
-            "temperature": 0.7,
+            "temperature": temperature,
             "max_tokens": 1500
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
-
-    eprintln!("Received response with status: {}", response.status());
+        }), model, schema));
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("OpenAI API error: {}", error_text));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let data = send_json(http, config, operation, "OpenAI", request, model).await?;
 
     let response_text = data
         .get("choices")
@@ -1483,103 +1336,57 @@ async fn generate_dream_analysis_openai(
         .and_then(|choice| choice.get("message"))
         .and_then(|msg| msg.get("content"))
         .and_then(|v| v.as_str())
-        .ok_or("Invalid OpenAI response format")?;
-
-    // Extract JSON from response
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))?;
 
-    serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    Ok(response_text.to_string())
 }
 
-async fn generate_dream_analysis_anthropic(
-    dream_title: &str,
-    dream_content: &str,
-    sleep_quality: Option<i32>,
+async fn analyze_entry_anthropic(
+    operation: Operation,
+    temperature: f64,
+    prompt: &str,
+    entry: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<GenerateDreamAnalysisResponse, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
     let model = map_anthropic_model(&config.anthropic_model);
 
-    // Extract simplified card summaries
-    let card_summaries = extract_card_summaries()?;
-
-    // Build the prompt with cards
-    let prompt = DREAM_ANALYSIS_PROMPT.replace("{CARDS_JSON}", &card_summaries);
-    let sleep_quality_text = match sleep_quality {
-        Some(q) => format!("Sleep Quality: {}/5", q),
-        None => "Sleep Quality: Not specified".to_string(),
-    };
-
-    let user_message = format!(
-        "{}\n\nTitle: {}\n{}\n\nContent:\n{}",
-        prompt, dream_title, sleep_quality_text, dream_content
-    );
+    let user_message = format!("{}\n\n{}", prompt, entry);
 
     eprintln!("Sending request to Anthropic API...");
     eprintln!("Model: {}", model);
     eprintln!("Message length: {} chars", user_message.len());
 
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(operation.timeout(&config.network.operation_timeouts))
         .header("Content-Type", "application/json")
         .header("x-api-key", &config.anthropic_api_key)
         .header("anthropic-version", "2023-06-01")
         .json(&json!({
             "model": model,
             "max_tokens": 1500,
+            "temperature": temperature,
+            "tools": [anthropic_tool(schema)],
+            "tool_choice": { "type": "tool", "name": schema.name },
             "messages": [
                 {
                     "role": "user",
                     "content": user_message
                 }
             ]
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
+        }));
 
-    eprintln!("Received response with status: {}", response.status());
-
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Anthropic API error: {}", error_text));
-    }
-
-    eprintln!("Reading response body...");
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
-    eprintln!("Response body parsed successfully");
+    let data = send_json(http, config, operation, "Anthropic", request, model).await?;
 
     eprintln!("Extracting text content from response...");
-    let response_text = data
-        .get("content")
-        .and_then(|v| v.as_array())
-        .and_then(|arr| arr.first())
-        .and_then(|item| item.get("text"))
-        .and_then(|v| v.as_str())
-        .ok_or("Invalid Anthropic response format")?;
+    let response_text = anthropic_structured_output(&data)
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))?;
     eprintln!("Response text length: {} chars", response_text.len());
 
-    // Extract JSON from response
-    eprintln!("Extracting JSON from response text...");
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
-    eprintln!("JSON string length: {} chars", json_str.len());
-
-    eprintln!("Parsing JSON analysis response...");
-    let result = serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))?;
-    eprintln!("Analysis parsed successfully!");
-    Ok(result)
+    Ok(response_text.to_string())
 }
 
 // Creative Prompts Generation
@@ -1587,111 +1394,90 @@ pub async fn generate_creative_prompts(
     themes_patterns: &str,
     emotional_analysis: &str,
     narrative_summary: &str,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<GenerateCreativePromptsResponse, String> {
-    match config.provider {
-        LLMProvider::Disabled => Err("LLM is disabled".to_string()),
-        LLMProvider::Ollama => generate_creative_prompts_ollama(themes_patterns, emotional_analysis, narrative_summary, config).await,
-        LLMProvider::OpenAI => generate_creative_prompts_openai(themes_patterns, emotional_analysis, narrative_summary, config).await,
-        LLMProvider::Anthropic => generate_creative_prompts_anthropic(themes_patterns, emotional_analysis, narrative_summary, config).await,
-    }
-}
-
-async fn generate_creative_prompts_ollama(
-    themes_patterns: &str,
-    emotional_analysis: &str,
-    narrative_summary: &str,
-    config: &LLMConfig,
-) -> Result<GenerateCreativePromptsResponse, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-    let url = format!("{}/api/generate", config.ollama_url);
-    let model = map_ollama_model(&config.ollama_model);
+) -> Result<GenerateCreativePromptsResponse, LlmError> {
+    let instructions = prompts.render(PromptTemplateId::CreativePrompts, &[])?;
 
     let analysis_summary = format!(
         "Themes & Patterns:\n{}\n\nEmotional Analysis:\n{}\n\nNarrative Summary:\n{}",
         themes_patterns, emotional_analysis, narrative_summary
     );
 
-    let full_prompt = format!("{}\n\n{}", CREATIVE_PROMPTS_GENERATION, analysis_summary);
+    let full_prompt = format!("{}\n\n{}", instructions, analysis_summary);
+
+    let schema = &structured::creative_prompts_schema();
+
+    structured::request_with_retry(
+        &full_prompt,
+        |prompt| async move {
+            match config.provider {
+                LLMProvider::Disabled => Err(LlmError::Disabled),
+                LLMProvider::Mock => mock::respond(Operation::CreativePrompts, config),
+                LLMProvider::Ollama => generate_creative_prompts_ollama(&prompt, schema, http, config).await,
+                LLMProvider::OpenAI => generate_creative_prompts_openai(&prompt, schema, http, config).await,
+                LLMProvider::Anthropic => generate_creative_prompts_anthropic(&prompt, schema, http, config).await,
+            }
+        },
+        structured::validate_creative_prompts,
+    )
+    .await
+}
 
-    let response = client
+async fn generate_creative_prompts_ollama(
+    full_prompt: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
+    let url = format!("{}/api/generate", config.ollama_url);
+    let model = map_ollama_model(&config.ollama_model);
+
+    let request = client
         .post(&url)
+        .timeout(Operation::CreativePrompts.timeout(&config.network.operation_timeouts))
         .json(&json!({
             "model": model,
             "prompt": full_prompt,
-            "stream": false
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("Ollama API error: {}", response.status()));
-    }
+            "stream": false,
+            "format": schema.schema,
+            "options": { "temperature": CREATIVE_PROMPTS_TEMPERATURE }
+        }));
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+    let data = send_json(http, config, Operation::CreativePrompts, "Ollama", request, model).await?;
 
     let response_text = data
         .get("response")
         .and_then(|v| v.as_str())
-        .ok_or("Invalid Ollama response format")?;
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))?;
 
-    // Extract JSON from response
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
-
-    serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    Ok(response_text.to_string())
 }
 
 async fn generate_creative_prompts_openai(
-    themes_patterns: &str,
-    emotional_analysis: &str,
-    narrative_summary: &str,
+    full_prompt: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<GenerateCreativePromptsResponse, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
     let model = map_openai_model(&config.openai_model);
 
-    let analysis_summary = format!(
-        "Themes & Patterns:\n{}\n\nEmotional Analysis:\n{}\n\nNarrative Summary:\n{}",
-        themes_patterns, emotional_analysis, narrative_summary
-    );
-
-    let user_message = format!("{}\n\n{}", CREATIVE_PROMPTS_GENERATION, analysis_summary);
-
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(Operation::CreativePrompts.timeout(&config.network.operation_timeouts))
         .header("Authorization", format!("Bearer {}", config.openai_api_key))
-        .json(&json!({
+        .json(&with_openai_response_format(json!({
             "model": model,
             "messages": [
-                {"role": "user", "content": user_message}
+                {"role": "user", "content": full_prompt}
             ],
-            "response_format": { "type": "json_object" }
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("OpenAI request failed: {}", e))?;
+            "temperature": CREATIVE_PROMPTS_TEMPERATURE
+        }), model, schema));
 
-    if !response.status().is_success() {
-        return Err(format!("OpenAI API error: {}", response.status()));
-    }
-
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
+    let data = send_json(http, config, Operation::CreativePrompts, "OpenAI", request, model).await?;
 
     let response_text = data
         .get("choices")
@@ -1699,67 +1485,362 @@ async fn generate_creative_prompts_openai(
         .and_then(|c| c.get("message"))
         .and_then(|m| m.get("content"))
         .and_then(|c| c.as_str())
-        .ok_or("Invalid OpenAI response format")?;
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))?;
 
-    serde_json::from_str(response_text)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    Ok(response_text.to_string())
 }
 
 async fn generate_creative_prompts_anthropic(
-    themes_patterns: &str,
-    emotional_analysis: &str,
-    narrative_summary: &str,
+    full_prompt: &str,
+    schema: &OutputSchema,
+    http: &LlmHttp,
     config: &LLMConfig,
-) -> Result<GenerateCreativePromptsResponse, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(120))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
     let model = map_anthropic_model(&config.anthropic_model);
 
-    let analysis_summary = format!(
-        "Themes & Patterns:\n{}\n\nEmotional Analysis:\n{}\n\nNarrative Summary:\n{}",
-        themes_patterns, emotional_analysis, narrative_summary
-    );
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(Operation::CreativePrompts.timeout(&config.network.operation_timeouts))
+        .header("x-api-key", &config.anthropic_api_key)
+        .header("anthropic-version", "2023-06-01")
+        .json(&json!({
+            "model": model,
+            "max_tokens": 2000,
+            "temperature": CREATIVE_PROMPTS_TEMPERATURE,
+            "tools": [anthropic_tool(schema)],
+            "tool_choice": { "type": "tool", "name": schema.name },
+            "messages": [
+                {"role": "user", "content": full_prompt}
+            ]
+        }));
 
-    let user_message = format!("{}\n\n{}", CREATIVE_PROMPTS_GENERATION, analysis_summary);
+    let data = send_json(http, config, Operation::CreativePrompts, "Anthropic", request, model).await?;
 
-    let response = client
-        .post("https://api.anthropic.com/v1/messages")
+    anthropic_structured_output(&data).ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))
+}
+
+// Period digest generation
+pub async fn generate_period_digest(
+    period_label: &str,
+    date_range: &str,
+    stats: &str,
+    entries: &str,
+    prompts: &PromptRegistry,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<String, LlmError> {
+    let prompt = prompts.render(
+        PromptTemplateId::PeriodDigest,
+        &[("period_label", period_label), ("date_range", date_range), ("stats", stats), ("entries", entries)],
+    )?;
+
+    generate_reflection(Operation::Digest, &prompt, http, config).await
+}
+
+/// Free-text reflection from a fully rendered prompt, sent as a single user message
+async fn generate_reflection(operation: Operation, prompt: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    match config.provider {
+        LLMProvider::Disabled => Err(LlmError::Disabled),
+        LLMProvider::Mock => mock::respond(operation, config),
+        LLMProvider::Ollama => generate_reflection_ollama(operation, prompt, http, config).await,
+        LLMProvider::OpenAI => generate_reflection_openai(operation, prompt, http, config).await,
+        LLMProvider::Anthropic => generate_reflection_anthropic(operation, prompt, http, config).await,
+    }
+}
+
+async fn generate_reflection_ollama(operation: Operation, prompt: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
+    let url = format!("{}/api/generate", config.ollama_url);
+    let model = map_ollama_model(&config.ollama_model);
+
+    let request = client
+        .post(&url)
+        .timeout(operation.timeout(&config.network.operation_timeouts))
+        .json(&json!({
+            "model": model,
+            "prompt": prompt,
+            "stream": false,
+            "options": { "temperature": REFLECTION_TEMPERATURE }
+        }));
+
+    let data = send_json(http, config, operation, "Ollama", request, model).await?;
+
+    data.get("response")
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama response format".to_string()))
+}
+
+async fn generate_reflection_openai(operation: Operation, prompt: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
+    let model = map_openai_model(&config.openai_model);
+
+    let request = client
+        .post(format!("{}/v1/chat/completions", config.openai_base_url))
+        .timeout(operation.timeout(&config.network.operation_timeouts))
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", config.openai_api_key))
+        .json(&json!({
+            "model": model,
+            "messages": [
+                {
+                    "role": "user",
+                    "content": prompt
+                }
+            ],
+            "temperature": REFLECTION_TEMPERATURE,
+            "max_tokens": 1500
+        }));
+
+    let data = send_json(http, config, operation, "OpenAI", request, model).await?;
+
+    data.get("choices")
+        .and_then(|v| v.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|choice| choice.get("message"))
+        .and_then(|msg| msg.get("content"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI response format".to_string()))
+}
+
+async fn generate_reflection_anthropic(operation: Operation, prompt: &str, http: &LlmHttp, config: &LLMConfig) -> Result<String, LlmError> {
+    let client = http.client(&LLMProvider::Anthropic, &config.network)?;
+    let model = map_anthropic_model(&config.anthropic_model);
+
+    let request = client
+        .post(format!("{}/v1/messages", config.anthropic_base_url))
+        .timeout(operation.timeout(&config.network.operation_timeouts))
+        .header("Content-Type", "application/json")
         .header("x-api-key", &config.anthropic_api_key)
         .header("anthropic-version", "2023-06-01")
         .json(&json!({
             "model": model,
-            "max_tokens": 2000,
+            "max_tokens": 1500,
+            "temperature": REFLECTION_TEMPERATURE,
             "messages": [
-                {"role": "user", "content": user_message}
+                {
+                    "role": "user",
+                    "content": prompt
+                }
             ]
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Anthropic request failed: {}", e))?;
+        }));
+
+    let data = send_json(http, config, operation, "Anthropic", request, model).await?;
 
-    if !response.status().is_success() {
-        return Err(format!("Anthropic API error: {}", response.status()));
+    data.get("content")
+        .and_then(|v| v.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|item| item.get("text"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Anthropic response format".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::block_on;
+    use crate::llm::profile::UserProfile;
+    use crate::llm::stub_server::{StubResponse, StubServer};
+    use std::collections::HashMap;
+
+    fn life_area(key: &str, name: &str) -> LifeArea {
+        LifeArea {
+            id: None,
+            key: key.to_string(),
+            name: name.to_string(),
+            description: None,
+            is_builtin: true,
+            created_at: chrono::Utc::now(),
+        }
     }
 
-    let data: Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
+    #[test]
+    fn test_card_prompts_use_catalog_insight_for_the_area() {
+        let cards = vec![json!({ "id": 1, "name": "Delivery Driver", "question": "?", "meaning": "Arrivals" })];
+        let prompt = multiple_cards_prompt(&cards, &life_area("work", "Work"), &PromptRegistry::default()).unwrap();
+        assert!(prompt.contains("their Work area"));
+        assert!(prompt.contains("In the Work area: A project update, client feedback"));
 
-    let response_text = data
-        .get("content")
-        .and_then(|c| c.get(0))
-        .and_then(|c| c.get("text"))
-        .and_then(|t| t.as_str())
-        .ok_or("Invalid Anthropic response format")?;
+        let custom = multiple_cards_prompt(&cards, &life_area("side_projects", "Side Projects"), &PromptRegistry::default()).unwrap();
+        assert!(!custom.contains("In the Side Projects area"));
+    }
+
+    #[test]
+    fn test_ollama_dream_analysis_is_parsed_and_validated() {
+        let card = deck_card_names().unwrap().remove(0);
+        let analysis = json!({
+            "themes_patterns": "Falling and flight",
+            "emotional_analysis": "Anxious, then relieved",
+            "narrative_summary": "A fall that turns into flying",
+            "symbol_cards": [{ "card_name": card, "relevance_note": "The leap" }]
+        });
+        let server = StubServer::start(vec![StubResponse::ollama_generate(&analysis.to_string())]);
+        let config = server.config(LLMProvider::Ollama);
+
+        let result = block_on(generate_dream_analysis(
+            "Flying",
+            "I fell off a cliff and then I was flying.",
+            Some(4),
+            None,
+            &PromptRegistry::default(),
+            &LlmHttp::new(),
+            &config,
+        ))
+        .unwrap();
+
+        assert_eq!(result.themes_patterns, "Falling and flight");
+        assert_eq!(result.symbol_cards[0].card_name, card);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/generate");
+        assert_eq!(requests[0].body["model"], "llama3.2");
+        let prompt = requests[0].body["prompt"].as_str().unwrap();
+        assert!(prompt.contains("Sleep Quality: 4/5"));
+        assert!(prompt.contains("I fell off a cliff"));
+    }
+
+    #[test]
+    fn test_openai_mind_dump_analysis_sends_mind_dump_and_parses() {
+        let card = deck_card_names().unwrap().remove(0);
+        let analysis = json!({
+            "summary": "A busy week weighing on you",
+            "worries": ["Missing the deadline"],
+            "action_items": ["Ask Sam for an extension"],
+            "suggested_cards": [{ "card_name": card, "relevance_note": "Asking for help" }]
+        });
+        let server = StubServer::start(vec![StubResponse::openai_chat(&analysis.to_string())]);
+        let config = server.config(LLMProvider::OpenAI);
+
+        let result = block_on(generate_mind_dump_analysis(
+            Some("Deadlines"),
+            "The report is due Friday and I have not started.",
+            &PromptRegistry::default(),
+            &LlmHttp::new(),
+            &config,
+        ))
+        .unwrap();
+
+        assert_eq!(result.action_items, vec!["Ask Sam for an extension"]);
+        assert_eq!(result.suggested_cards[0].card_name, card);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/chat/completions");
+        let user_message = requests[0].body["messages"][1]["content"].as_str().unwrap();
+        assert!(user_message.starts_with("Title: Deadlines"));
+        assert!(user_message.contains("due Friday"));
+    }
+
+    #[test]
+    fn test_openai_chat_sends_system_prompt_history_and_message() {
+        let server = StubServer::start(vec![StubResponse::openai_chat("  What did the tower mean to you?  ")]);
+        let config = server.config(LLMProvider::OpenAI);
+        let history = vec![
+            json!({ "role": "user", "content": "I drew the tower" }),
+            json!({ "role": "assistant", "content": "How did that feel?" }),
+            json!({ "role": "note" }),
+        ];
+
+        let prompts = PromptRegistry::default().with_profile(UserProfile { name: "Sam".to_string(), ..Default::default() });
+        let reply = block_on(chat_with_history(
+            "Unsettling",
+            &history,
+            "The Tower",
+            "What is falling apart?",
+            "Sudden change",
+            "",
+            "Work",
+            &[],
+            &prompts,
+            &LlmHttp::new(),
+            &config,
+        ))
+        .unwrap();
+        assert_eq!(reply, "What did the tower mean to you?");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/v1/chat/completions");
+        assert_eq!(request.header("authorization"), Some("Bearer sk-test"));
+
+        let messages = request.body["messages"].as_array().unwrap();
+        let roles: Vec<&str> = messages.iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "user"]);
+        assert!(messages[0]["content"].as_str().unwrap().contains("- Name: Sam"));
+        assert_eq!(messages[3]["content"], "Unsettling");
+    }
 
-    // Extract JSON from response
-    let json_start = response_text.find('{').ok_or("No JSON object found in response")?;
-    let json_end = response_text.rfind('}').ok_or("No JSON object found in response")?;
-    let json_str = &response_text[json_start..=json_end];
+    #[test]
+    fn test_anthropic_auth_error_is_not_retried() {
+        let server = StubServer::start(vec![StubResponse::error(
+            401,
+            json!({ "type": "error", "error": { "type": "authentication_error", "message": "invalid x-api-key" } }),
+        )]);
+        let config = server.config(LLMProvider::Anthropic);
+
+        let error = block_on(generate_title(
+            "A dream",
+            &PromptRegistry::default(),
+            &LlmHttp::new(),
+            &ResponseCache::disabled(),
+            &config,
+        ))
+        .unwrap_err();
+
+        assert!(matches!(error, LlmError::Auth { provider: "Anthropic", .. }));
+        assert_eq!(server.requests().len(), 1);
+    }
 
-    serde_json::from_str(json_str)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))
+    #[test]
+    fn test_anthropic_rate_limit_is_retried_and_usage_recorded() {
+        let server = StubServer::start(vec![
+            StubResponse::error(429, json!({ "error": { "message": "slow down" } })).with_header("Retry-After", "0"),
+            StubResponse::anthropic_message("The Open Door"),
+        ]);
+        let config = server.config(LLMProvider::Anthropic);
+        let http = LlmHttp::new();
+
+        let title = block_on(generate_title(
+            "A dream about a door",
+            &PromptRegistry::default(),
+            &http,
+            &ResponseCache::disabled(),
+            &config,
+        ))
+        .unwrap();
+        assert_eq!(title, "The Open Door");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].header("x-api-key"), Some("sk-ant-test"));
+
+        let usage = http.take_usage();
+        assert_eq!(usage.len(), 1);
+        assert_eq!((usage[0].prompt_tokens, usage[0].completion_tokens), (10, 5));
+    }
+
+    #[test]
+    fn test_mock_provider_serves_scripted_responses() {
+        let config = LLMConfig {
+            provider: LLMProvider::Mock,
+            mock_responses: HashMap::from([
+                ("title".to_string(), "Scripted Title".to_string()),
+                ("default".to_string(), r#"{"1": "First card", "2": "Second card"}"#.to_string()),
+            ]),
+            ..Default::default()
+        };
+        let http = LlmHttp::new();
+        let prompts = PromptRegistry::default();
+
+        let title = block_on(generate_title("A dream", &prompts, &http, &ResponseCache::disabled(), &config)).unwrap();
+        assert_eq!(title, "Scripted Title");
+
+        let cards = vec![json!({ "id": 1, "name": "The Fool" }), json!({ "id": 2, "name": "The Tower" })];
+        let commentary = block_on(comment_on_multiple_cards(&cards, &life_area("work", "Work"), &prompts, &http, &config)).unwrap();
+        assert_eq!(commentary["2"], "Second card");
+
+        let error = block_on(optimize_description("text", &prompts, &http, &LLMConfig { mock_responses: HashMap::new(), ..config })).unwrap_err();
+        assert!(matches!(error, LlmError::Other(_)));
+    }
 }
@@ -0,0 +1,320 @@
+use super::client;
+use super::error::LlmError;
+use super::http::LlmHttp;
+use super::similar_dreams::emotion_tags;
+use super::templates::{PromptRegistry, PromptTemplateId};
+use super::types::LLMConfig;
+use crate::db::models::{Bug, CreateDigestInput, Digest, DigestPeriod, DigestStats, Dream, MindDump, TagCount};
+use crate::db::Database;
+use chrono::{DateTime, Duration, Months, Utc};
+use std::collections::HashMap;
+
+/// Emotions and cards listed in the stats
+const TOP_COUNT: usize = 5;
+
+/// Longest excerpt of one entry put into the prompt
+const ENTRY_EXCERPT_CHARS: usize = 600;
+
+/// The `[start, end)` range a digest covers; custom ranges need an end after the start
+pub fn period_range(
+    period: DigestPeriod,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let end = match period {
+        DigestPeriod::Week => start + Duration::weeks(1),
+        DigestPeriod::Month => start
+            .checked_add_months(Months::new(1))
+            .ok_or_else(|| format!("Invalid month starting {}", start))?,
+        DigestPeriod::Custom => end.ok_or("A custom digest needs an end date")?,
+    };
+
+    if end <= start {
+        return Err("The digest end date must be after its start date".to_string());
+    }
+    Ok((start, end))
+}
+
+/// Everything recorded in a digest's range
+struct PeriodActivity {
+    dreams: Vec<Dream>,
+    mind_dumps: Vec<MindDump>,
+    bugs_created: Vec<Bug>,
+    bugs_resolved: Vec<Bug>,
+}
+
+impl PeriodActivity {
+    fn load(db: &Database, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Self, String> {
+        let in_range = |date: DateTime<Utc>| date >= start && date < end;
+
+        let dreams = db
+            .list_all_dreams()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|d| in_range(d.date_occurred))
+            .collect();
+        let mind_dumps = db
+            .list_all_mind_dumps()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|m| in_range(m.created_at))
+            .collect();
+
+        let bugs = db.list_bugs(None, None).map_err(|e| e.to_string())?;
+        let bugs_created = bugs.iter().filter(|b| in_range(b.created_at)).cloned().collect();
+        let bugs_resolved = bugs.into_iter().filter(|b| b.resolved_at.is_some_and(in_range)).collect();
+
+        Ok(PeriodActivity { dreams, mind_dumps, bugs_created, bugs_resolved })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dreams.is_empty() && self.mind_dumps.is_empty() && self.bugs_created.is_empty() && self.bugs_resolved.is_empty()
+    }
+}
+
+/// Gather the range's dreams, mind dumps and bug activity, ask the model for a
+/// reflection and store it as a new digest
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_period_digest(
+    period: DigestPeriod,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    db: &Database,
+    http: &LlmHttp,
+    prompts: &PromptRegistry,
+    config: &LLMConfig,
+) -> Result<Digest, LlmError> {
+    let (start, end) = period_range(period, start, end)?;
+    let activity = PeriodActivity::load(db, start, end)?;
+    if activity.is_empty() {
+        return Err(LlmError::Other(format!(
+            "Nothing was recorded between {} and {}",
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d")
+        )));
+    }
+
+    let cards = db.current_analysis_card_names().map_err(|e| e.to_string())?;
+    let stats = digest_stats(&activity, &cards);
+
+    let period_label = match period {
+        DigestPeriod::Week => "weekly",
+        DigestPeriod::Month => "monthly",
+        DigestPeriod::Custom => "period",
+    };
+    // The range is exclusive; show the last day it includes
+    let date_range = format!(
+        "{} to {}",
+        start.format("%Y-%m-%d"),
+        (end - Duration::seconds(1)).format("%Y-%m-%d")
+    );
+
+    let summary = client::generate_period_digest(
+        period_label,
+        &date_range,
+        &stats_text(&stats),
+        &entries_text(&activity),
+        prompts,
+        http,
+        config,
+    )
+    .await?;
+
+    let digest = db
+        .create_digest(CreateDigestInput {
+            period_type: period,
+            start_date: start,
+            end_date: end,
+            stats,
+            summary,
+            provenance: client::generation_provenance(
+                PromptTemplateId::PeriodDigest,
+                client::REFLECTION_TEMPERATURE,
+                prompts,
+                config,
+            ),
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(digest)
+}
+
+fn digest_stats(activity: &PeriodActivity, cards: &HashMap<i64, Vec<String>>) -> DigestStats {
+    let sleep: Vec<i32> = activity.dreams.iter().filter_map(|d| d.sleep_quality).collect();
+
+    DigestStats {
+        dream_count: activity.dreams.len() as i64,
+        lucid_dream_count: activity.dreams.iter().filter(|d| d.is_lucid == Some(true)).count() as i64,
+        recurring_dream_count: activity.dreams.iter().filter(|d| d.is_recurring == Some(true)).count() as i64,
+        average_sleep_quality: if sleep.is_empty() {
+            None
+        } else {
+            Some(sleep.iter().sum::<i32>() as f64 / sleep.len() as f64)
+        },
+        mind_dump_count: activity.mind_dumps.len() as i64,
+        mind_dump_words: activity.mind_dumps.iter().map(|m| m.word_count as i64).sum(),
+        bugs_created: activity.bugs_created.len() as i64,
+        bugs_resolved: activity.bugs_resolved.len() as i64,
+        top_emotions: top_counts(activity.dreams.iter().flat_map(emotion_tags)),
+        top_cards: top_counts(
+            activity
+                .dreams
+                .iter()
+                .filter_map(|d| d.id.and_then(|id| cards.get(&id)))
+                .flatten()
+                .cloned(),
+        ),
+    }
+}
+
+/// The most frequent names, most common first, ties alphabetical
+fn top_counts(names: impl Iterator<Item = String>) -> Vec<TagCount> {
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+
+    let mut counts: Vec<TagCount> = counts.into_iter().map(|(name, count)| TagCount { name, count }).collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then(a.name.cmp(&b.name)));
+    counts.truncate(TOP_COUNT);
+    counts
+}
+
+fn stats_text(stats: &DigestStats) -> String {
+    let list = |counts: &[TagCount]| {
+        if counts.is_empty() {
+            "none".to_string()
+        } else {
+            counts.iter().map(|c| format!("{} ({})", c.name, c.count)).collect::<Vec<_>>().join(", ")
+        }
+    };
+
+    let mut lines = vec![
+        format!(
+            "- Dreams: {} ({} lucid, {} recurring)",
+            stats.dream_count, stats.lucid_dream_count, stats.recurring_dream_count
+        ),
+        format!("- Mind dumps: {} ({} words)", stats.mind_dump_count, stats.mind_dump_words),
+        format!("- Bugs opened: {}, resolved: {}", stats.bugs_created, stats.bugs_resolved),
+        format!("- Most frequent emotions: {}", list(&stats.top_emotions)),
+        format!("- Most frequent dream cards: {}", list(&stats.top_cards)),
+    ];
+    if let Some(quality) = stats.average_sleep_quality {
+        lines.insert(1, format!("- Average sleep quality: {:.1}/5", quality));
+    }
+    lines.join("\n")
+}
+
+/// Every entry in date order, so the model can follow the period as it unfolded
+fn entries_text(activity: &PeriodActivity) -> String {
+    let mut entries: Vec<(DateTime<Utc>, String)> = Vec::new();
+
+    for dream in &activity.dreams {
+        let tags = emotion_tags(dream);
+        let emotions = if tags.is_empty() { String::new() } else { format!(" [emotions: {}]", tags.join(", ")) };
+        entries.push((
+            dream.date_occurred,
+            format!("Dream \"{}\"{}: {}", dream.title, emotions, excerpt(&dream.content)),
+        ));
+    }
+    for mind_dump in &activity.mind_dumps {
+        let title = mind_dump.title.as_deref().map(|t| format!(" \"{}\"", t)).unwrap_or_default();
+        entries.push((mind_dump.created_at, format!("Mind dump{}: {}", title, excerpt(&mind_dump.content))));
+    }
+    for bug in &activity.bugs_created {
+        entries.push((bug.created_at, format!("Bug opened \"{}\": {}", bug.title, excerpt(&bug.description))));
+    }
+    for bug in &activity.bugs_resolved {
+        if let Some(resolved_at) = bug.resolved_at {
+            entries.push((resolved_at, format!("Bug resolved \"{}\"", bug.title)));
+        }
+    }
+
+    entries.sort_by_key(|(date, _)| *date);
+    entries
+        .into_iter()
+        .map(|(date, text)| format!("{} - {}", date.format("%Y-%m-%d"), text))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(ENTRY_EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::{CreateDreamInput, CreateMindDumpInput};
+    use crate::db::test_support::{block_on, dream_input, setup_test_db};
+    use crate::llm::types::LLMProvider;
+    use chrono::TimeZone;
+
+    fn create_dream(db: &Database, date: DateTime<Utc>, tags: &str, lucid: bool) {
+        db.create_dream(CreateDreamInput {
+            date_occurred: date,
+            emotions_tags: Some(tags.to_string()),
+            sleep_quality: Some(if lucid { 4 } else { 3 }),
+            is_lucid: Some(lucid),
+            ..dream_input("Dream", "Walking through a forest")
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_period_range() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 31, 0, 0, 0).unwrap();
+
+        assert_eq!(period_range(DigestPeriod::Week, start, None).unwrap().1, start + Duration::days(7));
+        assert_eq!(
+            period_range(DigestPeriod::Month, start, None).unwrap().1,
+            Utc.with_ymd_and_hms(2026, 2, 28, 0, 0, 0).unwrap()
+        );
+        assert!(period_range(DigestPeriod::Custom, start, None).is_err());
+        assert!(period_range(DigestPeriod::Custom, start, Some(start)).is_err());
+    }
+
+    #[test]
+    fn test_digest_covers_only_the_range_and_is_stored() {
+        let db = setup_test_db();
+        let start = Utc::now() - Duration::days(6);
+        create_dream(&db, start + Duration::days(1), r#"["fear", "awe"]"#, true);
+        create_dream(&db, start + Duration::days(2), r#"["fear"]"#, false);
+        create_dream(&db, start - Duration::days(3), r#"["joy"]"#, false);
+        db.create_mind_dump(CreateMindDumpInput {
+            title: None,
+            content: "Busy week at work".to_string(),
+            word_count: 4,
+        })
+        .unwrap();
+
+        let config = LLMConfig {
+            provider: LLMProvider::Mock,
+            mock_responses: HashMap::from([("digest".to_string(), "**Themes** - Forests and fear.".to_string())]),
+            ..Default::default()
+        };
+        let digest = block_on(generate_period_digest(
+            DigestPeriod::Week,
+            start,
+            None,
+            &db,
+            &LlmHttp::new(),
+            &PromptRegistry::default(),
+            &config,
+        ))
+        .unwrap();
+
+        assert_eq!(digest.summary, "**Themes** - Forests and fear.");
+        assert_eq!(digest.stats.dream_count, 2);
+        assert_eq!(digest.stats.lucid_dream_count, 1);
+        assert_eq!(digest.stats.average_sleep_quality, Some(3.5));
+        assert_eq!(digest.stats.mind_dump_words, 4);
+        assert_eq!(digest.stats.top_emotions[0], TagCount { name: "fear".to_string(), count: 2 });
+        assert_eq!(digest.provenance.prompt_version.as_deref(), Some("period_digest@v1"));
+        assert_eq!(db.list_digests(None).unwrap().len(), 1);
+    }
+}
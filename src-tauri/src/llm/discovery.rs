@@ -0,0 +1,211 @@
+use super::client::{resolved_model, send_once};
+use super::error::LlmError;
+use super::http::{LlmHttp, Operation};
+use super::types::{AvailableModel, ConnectionTestResult, LLMConfig, LLMProvider};
+use serde_json::{json, Value};
+use std::time::Instant;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Send the smallest possible generation to the configured model and time it.
+/// One attempt only, so the latency and error are what the user would see first.
+pub async fn test_connection(http: &LlmHttp, config: &LLMConfig) -> ConnectionTestResult {
+    let model = resolved_model(config);
+    let started = Instant::now();
+    let result = ping(http, config, model.as_deref().unwrap_or_default()).await;
+
+    ConnectionTestResult {
+        ok: result.is_ok(),
+        provider: config.provider.as_str().to_string(),
+        model,
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+async fn ping(http: &LlmHttp, config: &LLMConfig, model: &str) -> Result<(), LlmError> {
+    let client = |provider| http.client(provider, &config.network);
+    let timeout = Operation::ConnectionTest.timeout(&config.network.operation_timeouts);
+
+    let (provider, request) = match config.provider {
+        LLMProvider::Disabled => return Err(LlmError::Disabled),
+        LLMProvider::Mock => return Ok(()),
+        LLMProvider::Ollama => (
+            "Ollama",
+            client(&LLMProvider::Ollama)?
+                .post(format!("{}/api/generate", config.ollama_url))
+                .json(&json!({
+                    "model": model,
+                    "prompt": "ping",
+                    "stream": false,
+                    "options": { "num_predict": 1 }
+                })),
+        ),
+        LLMProvider::OpenAI => (
+            "OpenAI",
+            client(&LLMProvider::OpenAI)?
+                .post(format!("{}/v1/chat/completions", config.openai_base_url))
+                .header("Authorization", format!("Bearer {}", config.openai_api_key))
+                .json(&json!({
+                    "model": model,
+                    "messages": [{ "role": "user", "content": "ping" }],
+                    "max_tokens": 1
+                })),
+        ),
+        LLMProvider::Anthropic => (
+            "Anthropic",
+            client(&LLMProvider::Anthropic)?
+                .post(format!("{}/v1/messages", config.anthropic_base_url))
+                .header("x-api-key", &config.anthropic_api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&json!({
+                    "model": model,
+                    "max_tokens": 1,
+                    "messages": [{ "role": "user", "content": "ping" }]
+                })),
+        ),
+    };
+
+    send_once(config, Operation::ConnectionTest, provider, request.timeout(timeout), model).await.map(|_| ())
+}
+
+/// Models the configured provider reports, for the settings page model picker
+pub async fn list_models(http: &LlmHttp, config: &LLMConfig) -> Result<Vec<AvailableModel>, LlmError> {
+    if let LLMProvider::Mock = config.provider {
+        return Ok(vec![AvailableModel {
+            id: "mock".to_string(),
+            display_name: Some("Mock (scripted responses)".to_string()),
+        }]);
+    }
+
+    let client = http.client(&config.provider, &config.network)?;
+    let timeout = Operation::ConnectionTest.timeout(&config.network.operation_timeouts);
+
+    match config.provider {
+        LLMProvider::Disabled | LLMProvider::Mock => Err(LlmError::Disabled),
+        LLMProvider::Ollama => {
+            let request = client
+                .get(format!("{}/api/tags", config.ollama_url))
+                .timeout(timeout);
+            Ok(parse_ollama_models(&send_once(config, Operation::ConnectionTest, "Ollama", request, "").await?))
+        }
+        LLMProvider::OpenAI => {
+            let request = client
+                .get(format!("{}/v1/models", config.openai_base_url))
+                .timeout(timeout)
+                .header("Authorization", format!("Bearer {}", config.openai_api_key));
+            Ok(parse_openai_models(&send_once(config, Operation::ConnectionTest, "OpenAI", request, "").await?))
+        }
+        LLMProvider::Anthropic => {
+            let request = client
+                .get(format!("{}/v1/models", config.anthropic_base_url))
+                .query(&[("limit", "1000")])
+                .timeout(timeout)
+                .header("x-api-key", &config.anthropic_api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION);
+            Ok(parse_anthropic_models(&send_once(config, Operation::ConnectionTest, "Anthropic", request, "").await?))
+        }
+    }
+}
+
+/// `GET /api/tags` lists locally pulled models as `{"models": [{"name": ...}]}`
+fn parse_ollama_models(data: &Value) -> Vec<AvailableModel> {
+    let mut models: Vec<AvailableModel> = list_field(data, "models")
+        .filter_map(|m| m.get("name").and_then(|n| n.as_str()))
+        .map(|name| AvailableModel {
+            id: name.to_string(),
+            display_name: None,
+        })
+        .collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models
+}
+
+/// `GET /v1/models` also returns embedding, audio and image models; keep the chat ones
+fn parse_openai_models(data: &Value) -> Vec<AvailableModel> {
+    let mut models: Vec<AvailableModel> = list_field(data, "data")
+        .filter_map(|m| m.get("id").and_then(|id| id.as_str()))
+        .filter(|id| is_openai_chat_model(id))
+        .map(|id| AvailableModel {
+            id: id.to_string(),
+            display_name: None,
+        })
+        .collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models
+}
+
+fn is_openai_chat_model(id: &str) -> bool {
+    let chat_family = ["gpt-", "chatgpt-", "o1", "o3", "o4"]
+        .iter()
+        .any(|prefix| id.starts_with(prefix));
+    let non_chat = ["audio", "realtime", "tts", "transcribe", "image", "search", "instruct"]
+        .iter()
+        .any(|marker| id.contains(marker));
+
+    chat_family && !non_chat
+}
+
+/// `GET /v1/models` returns newest first, which is the order we want to show
+fn parse_anthropic_models(data: &Value) -> Vec<AvailableModel> {
+    list_field(data, "data")
+        .filter_map(|m| {
+            let id = m.get("id").and_then(|id| id.as_str())?;
+            Some(AvailableModel {
+                id: id.to_string(),
+                display_name: m.get("display_name").and_then(|n| n.as_str()).map(|n| n.to_string()),
+            })
+        })
+        .collect()
+}
+
+fn list_field<'a>(data: &'a Value, field: &str) -> impl Iterator<Item = &'a Value> {
+    data.get(field)
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::block_on;
+
+    #[test]
+    fn test_parse_provider_model_lists() {
+        let ollama = json!({"models": [{"name": "mistral:latest"}, {"name": "llama3.2:latest"}]});
+        let ids: Vec<String> = parse_ollama_models(&ollama).into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec!["llama3.2:latest", "mistral:latest"]);
+
+        let openai = json!({"data": [
+            {"id": "gpt-4o-mini"},
+            {"id": "text-embedding-3-small"},
+            {"id": "gpt-4o-realtime-preview"},
+            {"id": "o3-mini"},
+            {"id": "dall-e-3"}
+        ]});
+        let ids: Vec<String> = parse_openai_models(&openai).into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec!["gpt-4o-mini", "o3-mini"]);
+
+        let anthropic = json!({"data": [
+            {"id": "claude-sonnet-4-5", "display_name": "Claude Sonnet 4.5"},
+            {"id": "claude-haiku-4-5", "display_name": "Claude Haiku 4.5"}
+        ]});
+        let models = parse_anthropic_models(&anthropic);
+        assert_eq!(models[0].id, "claude-sonnet-4-5");
+        assert_eq!(models[1].display_name.as_deref(), Some("Claude Haiku 4.5"));
+
+        assert!(parse_ollama_models(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_connection_reports_disabled_provider() {
+        let config = LLMConfig::default();
+
+        let result = block_on(test_connection(&LlmHttp::new(), &config));
+
+        assert!(!result.ok);
+        assert_eq!(result.provider, "disabled");
+        assert_eq!(result.error, Some(LlmError::Disabled));
+    }
+}
@@ -0,0 +1,158 @@
+use super::error::LlmError;
+use super::similar_dreams::{emotion_tags, find_similar_dreams};
+use super::types::LLMConfig;
+use crate::db::models::Dream;
+use crate::db::Database;
+
+/// Earlier dreams summarised for a history-aware analysis
+pub const RELATED_DREAMS: usize = 3;
+
+/// Longest excerpt of an earlier dream used when it has no analysis yet
+const EXCERPT_CHARS: usize = 300;
+
+/// The dream's metadata and summaries of related earlier dreams with their cards,
+/// appended after the dream so the analysis can comment on patterns across time
+pub fn analysis_history(
+    dream: &Dream,
+    db: &Database,
+    config: &LLMConfig,
+) -> Result<String, LlmError> {
+    let mut text = String::from("Dream details:");
+    for line in metadata_lines(dream) {
+        text.push_str("\n- ");
+        text.push_str(&line);
+    }
+
+    let related = match dream.id {
+        Some(id) => find_similar_dreams(id, RELATED_DREAMS, db, config)?,
+        None => Vec::new(),
+    };
+    if related.is_empty() {
+        text.push_str("\n\nRelated earlier dreams: none recorded.");
+        return Ok(text);
+    }
+
+    let cards = db.current_analysis_card_names().map_err(|e| e.to_string())?;
+    text.push_str(
+        "\n\nRelated earlier dreams (for context only; analyse the dream above and note any recurring patterns, symbols or cards):",
+    );
+
+    for similar in &related {
+        let summary = match db.get_dream_analysis(similar.dream_id).map_err(|e| e.to_string())? {
+            Some(analysis) => analysis.narrative_summary,
+            None => db
+                .get_dream(similar.dream_id)
+                .map_err(|e| e.to_string())?
+                .map(|earlier| excerpt(&earlier.content))
+                .unwrap_or_default(),
+        };
+
+        text.push_str(&format!(
+            "\n- {} \"{}\" (similarity {:.2})",
+            similar.date_occurred.format("%Y-%m-%d"),
+            similar.title,
+            similar.score
+        ));
+        if let Some(names) = cards.get(&similar.dream_id).filter(|names| !names.is_empty()) {
+            text.push_str(&format!("\n  Cards: {}", names.join(", ")));
+        }
+        if !summary.trim().is_empty() {
+            text.push_str(&format!("\n  Summary: {}", summary.trim()));
+        }
+    }
+
+    Ok(text)
+}
+
+fn metadata_lines(dream: &Dream) -> Vec<String> {
+    let tags = emotion_tags(dream);
+    let mut lines = vec![if tags.is_empty() {
+        "Emotions: Not specified".to_string()
+    } else {
+        format!("Emotions: {}", tags.join(", "))
+    }];
+
+    if let Some(lucid) = dream.is_lucid {
+        lines.push(format!("Lucid: {}", if lucid { "yes, the dreamer knew they were dreaming" } else { "no" }));
+    }
+
+    match (dream.is_recurring, dream.last_occurrence_period.as_deref()) {
+        (Some(true), Some(period)) => {
+            lines.push(format!("Recurring: yes, last occurred {}", period_label(period)))
+        }
+        (Some(true), None) => lines.push("Recurring: yes".to_string()),
+        (Some(false), _) => lines.push("Recurring: no".to_string()),
+        (None, _) => {}
+    }
+
+    lines
+}
+
+fn period_label(period: &str) -> &str {
+    match period {
+        "today" => "earlier today",
+        "yesterday" => "yesterday",
+        "last_week" => "last week",
+        "few_weeks_ago" => "a few weeks ago",
+        "last_month" => "last month",
+        "months_ago" => "months ago",
+        "last_year" => "about a year ago",
+        other => other,
+    }
+}
+
+fn excerpt(content: &str) -> String {
+    match content.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &content[..end]),
+        None => content.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::{CreateDreamAnalysisInput, CreateDreamInput, GenerationProvenance};
+    use crate::db::test_support::{create_card, dream_input, setup_test_db};
+    use crate::llm::types::LLMProvider;
+    use chrono::{Duration, Utc};
+
+    fn create_dream(db: &Database, days_ago: i64, title: &str, content: &str) -> Dream {
+        db.create_dream(CreateDreamInput {
+            date_occurred: Utc::now() - Duration::days(days_ago),
+            emotions_tags: Some(r#"["fear", "relief"]"#.to_string()),
+            is_recurring: Some(true),
+            last_occurrence_period: Some("few_weeks_ago".to_string()),
+            is_lucid: Some(false),
+            ..dream_input(title, content)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_history_lists_metadata_and_earlier_dreams_with_cards() {
+        let db = setup_test_db();
+        let earlier = create_dream(&db, 20, "Falling", "I fell from a tall tower into dark water");
+        let analysis = db
+            .create_dream_analysis(CreateDreamAnalysisInput {
+                dream_id: earlier.id.unwrap(),
+                themes_patterns: String::new(),
+                emotional_analysis: String::new(),
+                narrative_summary: "A fall that ends in deep water".to_string(),
+                provenance: GenerationProvenance::default(),
+            })
+            .unwrap();
+        let tower_id = create_card(&db, "The Tower");
+        db.link_card_to_dream_analysis(analysis.id.unwrap(), tower_id, None).unwrap();
+        let dream = create_dream(&db, 0, "Falling again", "I fell from the tower into the water again");
+
+        let config = LLMConfig { provider: LLMProvider::Mock, ..Default::default() };
+        let history = analysis_history(&dream, &db, &config).unwrap();
+
+        assert!(history.contains("Emotions: fear, relief"));
+        assert!(history.contains("Lucid: no"));
+        assert!(history.contains("Recurring: yes, last occurred a few weeks ago"));
+        assert!(history.contains("\"Falling\""));
+        assert!(history.contains("Cards: The Tower"));
+        assert!(history.contains("Summary: A fall that ends in deep water"));
+    }
+}
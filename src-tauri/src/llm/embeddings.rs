@@ -0,0 +1,446 @@
+use super::cache::cache_key;
+use super::client::send_json;
+use super::error::LlmError;
+use super::http::{LlmHttp, Operation};
+use super::mock;
+use super::types::{LLMConfig, LLMProvider};
+use crate::db::models::{Bug, Dream, EntryType, MindDump, SemanticSearchResult, StoredEmbedding};
+use crate::db::Database;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Weight of cosine similarity in the hybrid score; keyword overlap gets the rest
+const SEMANTIC_WEIGHT: f32 = 0.7;
+
+const SNIPPET_CHARS: usize = 200;
+
+/// Words too common to count as keyword matches
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "about", "with", "for", "was", "were", "that", "this", "from", "into", "what", "when", "where",
+    "which", "have", "had", "been", "being", "are", "but", "not", "you", "your", "my", "me", "i",
+];
+
+/// A journal entry as it is embedded and shown in search results
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub entry_type: EntryType,
+    pub id: i64,
+    pub title: String,
+    pub text: String,
+    pub date: DateTime<Utc>,
+}
+
+impl JournalEntry {
+    pub fn from_dream(dream: &Dream) -> Option<Self> {
+        Some(JournalEntry {
+            entry_type: EntryType::Dream,
+            id: dream.id?,
+            title: dream.title.clone(),
+            text: format!("{}\n\n{}", dream.title, dream.content),
+            date: dream.date_occurred,
+        })
+    }
+
+    pub fn from_mind_dump(mind_dump: &MindDump) -> Option<Self> {
+        let title = mind_dump.title.clone().unwrap_or_default();
+        Some(JournalEntry {
+            entry_type: EntryType::MindDump,
+            id: mind_dump.id?,
+            text: if title.is_empty() { mind_dump.content.clone() } else { format!("{}\n\n{}", title, mind_dump.content) },
+            title,
+            date: mind_dump.created_at,
+        })
+    }
+
+    pub fn from_bug(bug: &Bug) -> Option<Self> {
+        Some(JournalEntry {
+            entry_type: EntryType::Bug,
+            id: bug.id?,
+            title: bug.title.clone(),
+            text: format!("{}\n\n{}", bug.title, bug.description),
+            date: bug.created_at,
+        })
+    }
+
+    fn snippet(&self) -> String {
+        let body = self.text.strip_prefix(&self.title).unwrap_or(&self.text).trim();
+        match body.char_indices().nth(SNIPPET_CHARS) {
+            Some((end, _)) => format!("{}…", &body[..end]),
+            None => body.to_string(),
+        }
+    }
+}
+
+/// Every dream, mind dump and bug of the requested types
+pub fn journal_entries(db: &Database, entry_types: &[EntryType]) -> Result<Vec<JournalEntry>, String> {
+    let mut entries = Vec::new();
+
+    if entry_types.contains(&EntryType::Dream) {
+        let dreams = db.list_all_dreams().map_err(|e| e.to_string())?;
+        entries.extend(dreams.iter().filter_map(JournalEntry::from_dream));
+    }
+    if entry_types.contains(&EntryType::MindDump) {
+        let mind_dumps = db.list_all_mind_dumps().map_err(|e| e.to_string())?;
+        entries.extend(mind_dumps.iter().filter_map(JournalEntry::from_mind_dump));
+    }
+    if entry_types.contains(&EntryType::Bug) {
+        let bugs = db.list_bugs(None, None).map_err(|e| e.to_string())?;
+        entries.extend(bugs.iter().filter_map(JournalEntry::from_bug));
+    }
+
+    Ok(entries)
+}
+
+/// Embeddings are switched on and the provider can compute them
+pub fn enabled(config: &LLMConfig) -> bool {
+    config.embeddings.enabled && embedding_model(config).is_some()
+}
+
+/// The embedding model for the configured provider; Anthropic has no embeddings API
+pub fn embedding_model(config: &LLMConfig) -> Option<String> {
+    match config.provider {
+        LLMProvider::Ollama => Some(config.embeddings.ollama_model.clone()),
+        LLMProvider::OpenAI => Some(config.embeddings.openai_model.clone()),
+        LLMProvider::Mock => Some("mock".to_string()),
+        LLMProvider::Anthropic | LLMProvider::Disabled => None,
+    }
+}
+
+pub async fn embed(text: &str, http: &LlmHttp, config: &LLMConfig) -> Result<Vec<f32>, LlmError> {
+    match config.provider {
+        LLMProvider::Disabled => Err(LlmError::Disabled),
+        LLMProvider::Anthropic => Err(LlmError::Other(
+            "Anthropic has no embeddings API; use Ollama or OpenAI for semantic search".to_string(),
+        )),
+        LLMProvider::Mock => Ok(mock::embed(text)),
+        LLMProvider::Ollama => embed_ollama(text, http, config).await,
+        LLMProvider::OpenAI => embed_openai(text, http, config).await,
+    }
+}
+
+async fn embed_ollama(text: &str, http: &LlmHttp, config: &LLMConfig) -> Result<Vec<f32>, LlmError> {
+    let client = http.client(&LLMProvider::Ollama, &config.network)?;
+    let model = &config.embeddings.ollama_model;
+
+    let request = client
+        .post(format!("{}/api/embeddings", config.ollama_url))
+        .timeout(Operation::Embedding.timeout(&config.network.operation_timeouts))
+        .json(&json!({
+            "model": model,
+            "prompt": text
+        }));
+
+    let data = send_json(http, config, Operation::Embedding, "Ollama", request, model).await?;
+
+    parse_vector(data.get("embedding"))
+        .ok_or_else(|| LlmError::InvalidResponse("Invalid Ollama embeddings response".to_string()))
+}
+
+async fn embed_openai(text: &str, http: &LlmHttp, config: &LLMConfig) -> Result<Vec<f32>, LlmError> {
+    let client = http.client(&LLMProvider::OpenAI, &config.network)?;
+    let model = &config.embeddings.openai_model;
+
+    let request = client
+        .post(format!("{}/v1/embeddings", config.openai_base_url))
+        .timeout(Operation::Embedding.timeout(&config.network.operation_timeouts))
+        .header("Authorization", format!("Bearer {}", config.openai_api_key))
+        .json(&json!({
+            "model": model,
+            "input": text
+        }));
+
+    let data = send_json(http, config, Operation::Embedding, "OpenAI", request, model).await?;
+
+    let embedding = data
+        .get("data")
+        .and_then(|v| v.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|item| item.get("embedding"));
+    parse_vector(embedding).ok_or_else(|| LlmError::InvalidResponse("Invalid OpenAI embeddings response".to_string()))
+}
+
+fn parse_vector(value: Option<&Value>) -> Option<Vec<f32>> {
+    let values = value?.as_array()?;
+    let vector: Vec<f32> = values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect();
+    (!vector.is_empty() && vector.len() == values.len()).then_some(vector)
+}
+
+/// Embed an entry unless its current text is already embedded with the current model
+pub async fn index_entry(db: &Database, http: &LlmHttp, config: &LLMConfig, entry: &JournalEntry) -> Result<bool, LlmError> {
+    let Some(model) = embedding_model(config) else {
+        return Ok(false);
+    };
+    let provider = config.provider.as_str();
+    let content_hash = cache_key(Operation::Embedding, provider, &model, &[&entry.text]);
+
+    let existing = db.get_embedding(entry.entry_type, entry.id).map_err(|e| e.to_string())?;
+    if existing.is_some_and(|e| e.provider == provider && e.model == model && e.content_hash == content_hash) {
+        return Ok(false);
+    }
+
+    let vector = embed(&entry.text, http, config).await?;
+
+    db.upsert_embedding(&StoredEmbedding {
+        entity_type: entry.entry_type,
+        entity_id: entry.id,
+        provider: provider.to_string(),
+        model,
+        content_hash,
+        vector,
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(true)
+}
+
+/// Embed every entry that is new or changed since it was last embedded.
+/// Returns how many were embedded.
+pub async fn sync_index(db: &Database, http: &LlmHttp, config: &LLMConfig, entries: &[JournalEntry]) -> Result<usize, LlmError> {
+    let mut embedded = 0;
+    for entry in entries {
+        if index_entry(db, http, config, entry).await? {
+            embedded += 1;
+        }
+    }
+    Ok(embedded)
+}
+
+/// Rank entries by a blend of embedding similarity and keyword overlap with `query`.
+/// Falls back to keyword overlap alone when embeddings are off or unsupported.
+pub async fn semantic_search(
+    query: &str,
+    entry_types: &[EntryType],
+    limit: usize,
+    db: &Database,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<Vec<SemanticSearchResult>, LlmError> {
+    let entries = journal_entries(db, entry_types)?;
+    search_entries(query, &entries, limit, db, http, config).await
+}
+
+/// Rank `entries` against `query` using the vectors already stored. Entries are
+/// embedded when saved or by `sync_index`, never here; when the query itself can't
+/// be embedded the ranking falls back to keyword overlap.
+pub async fn search_entries(
+    query: &str,
+    entries: &[JournalEntry],
+    limit: usize,
+    db: &Database,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<Vec<SemanticSearchResult>, LlmError> {
+    let similarities = if enabled(config) {
+        match similarities(query, db, http, config).await {
+            Ok(similarities) => Some(similarities),
+            Err(e) => {
+                eprintln!("Semantic search fell back to keywords: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok(rank(query, entries, similarities.as_ref(), limit))
+}
+
+/// Cosine similarity of `query` to every stored vector from the current model
+async fn similarities(
+    query: &str,
+    db: &Database,
+    http: &LlmHttp,
+    config: &LLMConfig,
+) -> Result<HashMap<(EntryType, i64), f32>, LlmError> {
+    let model = embedding_model(config).unwrap_or_default();
+    let query_vector = embed(query, http, config).await?;
+
+    Ok(db
+        .list_embeddings(config.provider.as_str(), &model)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|e| ((e.entity_type, e.entity_id), cosine_similarity(&query_vector, &e.vector)))
+        .collect())
+}
+
+fn rank(
+    query: &str,
+    entries: &[JournalEntry],
+    similarities: Option<&HashMap<(EntryType, i64), f32>>,
+    limit: usize,
+) -> Vec<SemanticSearchResult> {
+    let terms = query_terms(query);
+
+    let mut results: Vec<SemanticSearchResult> = entries
+        .iter()
+        .map(|entry| {
+            let keyword_score = keyword_score(&terms, &entry.text);
+            let semantic_score = similarities.map(|s| s.get(&(entry.entry_type, entry.id)).copied().unwrap_or(0.0).max(0.0));
+            let score = match semantic_score {
+                Some(semantic) => SEMANTIC_WEIGHT * semantic + (1.0 - SEMANTIC_WEIGHT) * keyword_score,
+                None => keyword_score,
+            };
+
+            SemanticSearchResult {
+                entity_type: entry.entry_type,
+                entity_id: entry.id,
+                title: entry.title.clone(),
+                snippet: entry.snippet(),
+                date: entry.date,
+                score,
+                semantic_score,
+                keyword_score,
+            }
+        })
+        .filter(|result| result.score > 0.0)
+        .collect();
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.date.cmp(&a.date)));
+    results.truncate(limit);
+    results
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+pub(super) fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query.split(|c: char| !c.is_alphanumeric()).map(|term| term.to_lowercase()) {
+        if term.chars().count() > 2 && !STOP_WORDS.contains(&term.as_str()) && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Share of query terms that appear in the text, ignoring case
+fn keyword_score(terms: &[String], text: &str) -> f32 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+
+    let text = text.to_lowercase();
+    let matched = terms.iter().filter(|term| text.contains(term.as_str())).count();
+    matched as f32 / terms.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::{CreateMindDumpInput, UpdateDreamInput};
+    use crate::db::test_support::{block_on, create_dream, setup_test_db};
+    use crate::llm::stub_server::{StubResponse, StubServer};
+
+    fn mock_config() -> LLMConfig {
+        LLMConfig {
+            provider: LLMProvider::Mock,
+            embeddings: crate::llm::types::EmbeddingSettings { enabled: true, ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 2.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_keyword_score_ignores_stop_words_and_case() {
+        let terms = query_terms("Dreams about the SHADOW chasing me");
+        assert_eq!(terms, vec!["dreams", "shadow", "chasing"]);
+        assert!((keyword_score(&terms, "A shadow was chasing me") - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(keyword_score(&[], "anything"), 0.0);
+    }
+
+    #[test]
+    fn test_search_ranks_related_entries_and_reindexes_edits() {
+        let db = setup_test_db();
+        let http = LlmHttp::new();
+        let config = mock_config();
+
+        let chase = create_dream(&db, "Night run", "Running from a shadow through dark streets");
+        create_dream(&db, "Beach", "Warm sand and a calm blue sea");
+        db.create_mind_dump(CreateMindDumpInput {
+            title: None,
+            content: "Felt like a shadow was following me all day".to_string(),
+            word_count: 8,
+        })
+        .unwrap();
+
+        let entries = journal_entries(&db, &EntryType::ALL).unwrap();
+        assert_eq!(block_on(sync_index(&db, &http, &config, &entries)).unwrap(), 3);
+
+        let results = block_on(semantic_search("shadow running", &EntryType::ALL, 10, &db, &http, &config)).unwrap();
+        assert_eq!(results[0].entity_id, chase.id.unwrap());
+        assert_eq!(results[0].entity_type, EntryType::Dream);
+        assert!(results.iter().any(|r| r.entity_type == EntryType::MindDump));
+        assert!(results.iter().all(|r| r.semantic_score.is_some()));
+        assert_eq!(db.list_embeddings("mock", "mock").unwrap().len(), 3);
+
+        let before = db.get_embedding(EntryType::Dream, chase.id.unwrap()).unwrap().unwrap();
+        db.update_dream(UpdateDreamInput {
+            id: chase.id.unwrap(),
+            title: None,
+            content: Some("Swimming with dolphins".to_string()),
+            date_occurred: None,
+            emotions_tags: None,
+            sleep_quality: None,
+            is_recurring: None,
+            last_occurrence_period: None,
+            is_lucid: None,
+        })
+        .unwrap();
+        let dream = db.get_dream(chase.id.unwrap()).unwrap().unwrap();
+        assert!(block_on(index_entry(&db, &http, &config, &JournalEntry::from_dream(&dream).unwrap())).unwrap());
+        assert_ne!(db.get_embedding(EntryType::Dream, chase.id.unwrap()).unwrap().unwrap().content_hash, before.content_hash);
+    }
+
+    #[test]
+    fn test_search_falls_back_to_keywords_without_embeddings() {
+        let db = setup_test_db();
+        create_dream(&db, "Night run", "Running from a shadow");
+        create_dream(&db, "Beach", "Warm sand");
+
+        let config = LLMConfig::default();
+        let results = block_on(semantic_search("shadow", &[EntryType::Dream], 10, &db, &LlmHttp::new(), &config)).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].semantic_score, None);
+        assert_eq!(results[0].keyword_score, 1.0);
+        assert!(db.list_embeddings("disabled", "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_falls_back_to_keywords_when_the_query_cannot_be_embedded() {
+        let db = setup_test_db();
+        create_dream(&db, "Night run", "Running from a shadow");
+
+        let server = StubServer::start(vec![StubResponse::error(400, json!({ "error": "bad request" }))]);
+        let mut config = server.config(LLMProvider::Ollama);
+        config.embeddings.enabled = true;
+        let results = block_on(semantic_search("shadow", &[EntryType::Dream], 10, &db, &LlmHttp::new(), &config)).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].semantic_score, None);
+        // Only the query was sent; the unindexed dream is left for the next save or sync
+        assert_eq!(server.requests().len(), 1);
+        assert!(db.get_embedding(EntryType::Dream, results[0].entity_id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,212 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::time::Duration;
+
+/// Why an LLM call failed, in terms the UI can act on.
+/// Serialized to the frontend as `{ kind, message, retry_after_secs }`.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum LlmError {
+    #[error("LLM is disabled")]
+    Disabled,
+
+    #[error("{provider} rejected the API key: {message}")]
+    Auth { provider: &'static str, message: String },
+
+    #[error("{provider} rate limit reached, try again later")]
+    RateLimited {
+        provider: &'static str,
+        retry_after: Option<Duration>,
+    },
+
+    #[error("Model '{model}' was not found on {provider}")]
+    ModelNotFound { provider: &'static str, model: String },
+
+    #[error("Could not connect to {provider} at {url}. Is it running?")]
+    ConnectionRefused { provider: &'static str, url: String },
+
+    #[error("{provider} request timed out")]
+    Timeout { provider: &'static str },
+
+    #[error("{provider} request failed: {message}")]
+    Network { provider: &'static str, message: String },
+
+    #[error("{provider} API error ({status}): {message}")]
+    Api {
+        provider: &'static str,
+        status: u16,
+        message: String,
+    },
+
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    /// The caller gave up on the request before it finished
+    #[error("Request cancelled")]
+    Cancelled,
+
+    /// Failures before anything was sent, e.g. a prompt template that does not render
+    #[error("{0}")]
+    Other(String),
+}
+
+impl LlmError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LlmError::Disabled => "disabled",
+            LlmError::Auth { .. } => "auth",
+            LlmError::RateLimited { .. } => "rate_limited",
+            LlmError::ModelNotFound { .. } => "model_not_found",
+            LlmError::ConnectionRefused { .. } => "connection_refused",
+            LlmError::Timeout { .. } => "timeout",
+            LlmError::Network { .. } => "network",
+            LlmError::Api { .. } => "api",
+            LlmError::InvalidResponse(_) => "invalid_response",
+            LlmError::Cancelled => "cancelled",
+            LlmError::Other(_) => "other",
+        }
+    }
+
+    /// Worth retrying the same request after a pause
+    pub fn is_transient(&self) -> bool {
+        match self {
+            LlmError::RateLimited { .. } | LlmError::Timeout { .. } | LlmError::Network { .. } => true,
+            LlmError::Api { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            LlmError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Classify a non-success HTTP response
+    pub fn from_status(
+        provider: &'static str,
+        status: u16,
+        retry_after: Option<Duration>,
+        body: &str,
+        model: &str,
+    ) -> Self {
+        let message = error_message(body);
+
+        match status {
+            401 | 403 => LlmError::Auth { provider, message },
+            // A 404 that doesn't name the model is a wrong base URL or path
+            404 if !model.is_empty() && message.contains(model) => LlmError::ModelNotFound {
+                provider,
+                model: model.to_string(),
+            },
+            404 => LlmError::Api {
+                provider,
+                status,
+                message: if message.is_empty() {
+                    "endpoint not found, check the base URL".to_string()
+                } else {
+                    message
+                },
+            },
+            408 => LlmError::Timeout { provider },
+            429 => LlmError::RateLimited {
+                provider,
+                retry_after,
+            },
+            _ => LlmError::Api {
+                provider,
+                status,
+                message,
+            },
+        }
+    }
+
+    /// Classify a failure to get any response at all
+    pub fn from_transport(provider: &'static str, url: &str, error: &reqwest::Error) -> Self {
+        if error.is_timeout() {
+            LlmError::Timeout { provider }
+        } else if error.is_connect() {
+            LlmError::ConnectionRefused {
+                provider,
+                url: url.to_string(),
+            }
+        } else {
+            LlmError::Network {
+                provider,
+                message: error.to_string(),
+            }
+        }
+    }
+}
+
+/// Pull the human-readable part out of a provider error body.
+/// OpenAI and Anthropic use `{"error": {"message": ...}}`, Ollama uses `{"error": "..."}`.
+fn error_message(body: &str) -> String {
+    let parsed: Option<serde_json::Value> = serde_json::from_str(body).ok();
+    let error = parsed.as_ref().and_then(|v| v.get("error"));
+
+    error
+        .and_then(|e| e.get("message").and_then(|m| m.as_str()).or_else(|| e.as_str()))
+        .map(|m| m.to_string())
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
+impl From<String> for LlmError {
+    fn from(message: String) -> Self {
+        LlmError::Other(message)
+    }
+}
+
+impl From<&str> for LlmError {
+    fn from(message: &str) -> Self {
+        LlmError::Other(message.to_string())
+    }
+}
+
+impl Serialize for LlmError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("LlmError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("retry_after_secs", &self.retry_after().map(|d| d.as_secs()))?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status_classifies_common_failures() {
+        let auth = LlmError::from_status("OpenAI", 401, None, r#"{"error": {"message": "Incorrect API key"}}"#, "gpt-4o");
+        assert_eq!(auth, LlmError::Auth { provider: "OpenAI", message: "Incorrect API key".to_string() });
+
+        let missing = LlmError::from_status("Ollama", 404, None, r#"{"error": "model 'x' not found"}"#, "x");
+        assert_eq!(missing.kind(), "model_not_found");
+
+        let wrong_url = LlmError::from_status("OpenAI", 404, None, "", "gpt-4o");
+        assert_eq!(wrong_url.kind(), "api");
+        assert!(wrong_url.to_string().contains("check the base URL"));
+
+        let limited = LlmError::from_status("Anthropic", 429, Some(Duration::from_secs(7)), "", "claude");
+        assert!(limited.is_transient());
+        assert_eq!(limited.retry_after(), Some(Duration::from_secs(7)));
+
+        assert!(LlmError::from_status("Anthropic", 529, None, "overloaded", "claude").is_transient());
+        assert!(!LlmError::from_status("OpenAI", 400, None, "bad request", "gpt-4o").is_transient());
+    }
+
+    #[test]
+    fn test_serializes_kind_and_message() {
+        let value = serde_json::to_value(LlmError::RateLimited {
+            provider: "OpenAI",
+            retry_after: Some(Duration::from_secs(3)),
+        })
+        .unwrap();
+
+        assert_eq!(value["kind"], "rate_limited");
+        assert_eq!(value["retry_after_secs"], 3);
+        assert!(value["message"].as_str().unwrap().contains("OpenAI"));
+        assert_eq!(serde_json::to_value(LlmError::Cancelled).unwrap()["kind"], "cancelled");
+    }
+}
@@ -0,0 +1,240 @@
+use super::cache::cache_key;
+use super::error::LlmError;
+use super::http::Operation;
+use super::types::{FixtureSettings, LLMConfig};
+use crate::db::Database;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const REDACTED: &str = "[redacted]";
+
+/// Headers that carry credentials; their values are never written to disk
+const SECRET_HEADERS: &[&str] = &["authorization", "x-api-key", "api-key"];
+
+/// One provider call as written to `<operation>-<key>.json`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fixture {
+    pub operation: String,
+    pub provider: String,
+    pub model: String,
+    pub recorded_at: String,
+    pub request: FixtureRequest,
+    pub response: FixtureResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureRequest {
+    pub method: String,
+    /// Path and query only, so fixtures replay against any host
+    pub path: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Value,
+}
+
+/// A provider response, whether it came over the network or from a fixture.
+/// Bodies that are not JSON are kept as a string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureResponse {
+    pub status: u16,
+    pub retry_after_secs: Option<u64>,
+    pub body: Value,
+}
+
+impl FixtureResponse {
+    pub fn new(status: u16, retry_after_secs: Option<u64>, text: String) -> Self {
+        FixtureResponse {
+            status,
+            retry_after_secs,
+            body: serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        }
+    }
+
+    pub fn body_text(&self) -> String {
+        match &self.body {
+            Value::String(text) => text.clone(),
+            body => body.to_string(),
+        }
+    }
+}
+
+/// The request as it will be recorded: credential headers blanked and
+/// any configured API key removed from the URL and body
+pub fn describe(config: &LLMConfig, request: &reqwest::Request) -> FixtureRequest {
+    let url = request.url();
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                redact(config, value.to_str().unwrap_or_default())
+            };
+            (name.as_str().to_string(), value)
+        })
+        .collect();
+
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(|bytes| redact(config, &String::from_utf8_lossy(bytes)))
+        .map(|text| serde_json::from_str(&text).unwrap_or(Value::String(text)))
+        .unwrap_or(Value::Null);
+
+    FixtureRequest {
+        method: request.method().to_string(),
+        path: redact(config, &path),
+        headers,
+        body,
+    }
+}
+
+/// Write the call to the fixture directory, replacing any earlier recording of the same request
+pub fn record(
+    config: &LLMConfig,
+    operation: Operation,
+    provider: &str,
+    model: &str,
+    request: FixtureRequest,
+    response: &FixtureResponse,
+) -> Result<PathBuf, String> {
+    let path = fixture_path(&config.fixtures, operation, provider, model, &request)?;
+
+    let fixture = Fixture {
+        operation: operation.as_str().to_string(),
+        provider: provider.to_string(),
+        model: model.to_string(),
+        recorded_at: chrono::Utc::now().to_rfc3339(),
+        request,
+        response: FixtureResponse {
+            body: serde_json::from_str(&redact(config, &response.body.to_string())).unwrap_or(Value::Null),
+            ..response.clone()
+        },
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&fixture).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(path)
+}
+
+/// The recorded response for an identical request
+pub fn replay(
+    settings: &FixtureSettings,
+    operation: Operation,
+    provider: &str,
+    model: &str,
+    request: &FixtureRequest,
+) -> Result<FixtureResponse, LlmError> {
+    let path = fixture_path(settings, operation, provider, model, request).map_err(LlmError::Other)?;
+
+    let json = std::fs::read_to_string(&path).map_err(|_| {
+        LlmError::Other(format!(
+            "No recorded {} fixture for this {} request (expected {})",
+            operation.as_str(),
+            provider,
+            path.display()
+        ))
+    })?;
+
+    let fixture: Fixture = serde_json::from_str(&json)
+        .map_err(|e| LlmError::Other(format!("Invalid fixture {}: {}", path.display(), e)))?;
+
+    Ok(fixture.response)
+}
+
+fn fixture_path(
+    settings: &FixtureSettings,
+    operation: Operation,
+    provider: &str,
+    model: &str,
+    request: &FixtureRequest,
+) -> Result<PathBuf, String> {
+    let dir = if settings.dir.trim().is_empty() {
+        Database::data_dir().map_err(|e| e.to_string())?.join("llm_fixtures")
+    } else {
+        PathBuf::from(&settings.dir)
+    };
+
+    let body = request.body.to_string();
+    let key = cache_key(operation, provider, model, &[&request.method, &request.path, &body]);
+
+    Ok(dir.join(format!("{}-{}.json", operation.as_str(), key)))
+}
+
+fn redact(config: &LLMConfig, text: &str) -> String {
+    [&config.openai_api_key, &config.anthropic_api_key]
+        .into_iter()
+        .filter(|key| !key.is_empty())
+        .fold(text.to_string(), |text, key| text.replace(key.as_str(), REDACTED))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::block_on;
+    use crate::llm::cache::ResponseCache;
+    use crate::llm::client::generate_title;
+    use crate::llm::http::LlmHttp;
+    use crate::llm::stub_server::{StubResponse, StubServer};
+    use crate::llm::templates::PromptRegistry;
+    use crate::llm::types::{FixtureMode, LLMProvider};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("limnl-fixtures-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn title(config: &LLMConfig, content: &str) -> Result<String, LlmError> {
+        block_on(generate_title(
+            content,
+            &PromptRegistry::default(),
+            &LlmHttp::new(),
+            &ResponseCache::disabled(),
+            config,
+        ))
+    }
+
+    #[test]
+    fn test_recorded_call_is_redacted_and_replays_offline() {
+        let dir = temp_dir("record-replay");
+        let server = StubServer::start(vec![StubResponse::openai_chat("The Flooded House")]);
+        let mut config = server.config(LLMProvider::OpenAI);
+        config.fixtures = FixtureSettings {
+            mode: FixtureMode::Record,
+            dir: dir.to_string_lossy().to_string(),
+        };
+
+        assert_eq!(title(&config, "Water rising in my old house").unwrap(), "The Flooded House");
+
+        let files: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+        let recorded = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(!recorded.contains("sk-test"));
+        assert!(recorded.contains("Water rising in my old house"));
+
+        // Nothing is listening here and the key differs; the fixture still matches
+        config.fixtures.mode = FixtureMode::Replay;
+        config.openai_base_url = "http://127.0.0.1:9".to_string();
+        config.openai_api_key = "sk-someone-else".to_string();
+        assert_eq!(title(&config, "Water rising in my old house").unwrap(), "The Flooded House");
+
+        let missing = title(&config, "A different dream").unwrap_err();
+        assert!(matches!(missing, LlmError::Other(message) if message.contains("No recorded title fixture")));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
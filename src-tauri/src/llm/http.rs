@@ -0,0 +1,212 @@
+use super::error::LlmError;
+use super::types::{LLMProvider, NetworkSettings, OperationTimeouts, ProviderTimeouts};
+use crate::db::models::CreateLlmUsageInput;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The kinds of request we make, each with its own overall timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Title,
+    Description,
+    CardCommentary,
+    Chat,
+    DreamAnalysis,
+    CreativePrompts,
+    ConnectionTest,
+    Embedding,
+    Digest,
+    MindDumpAnalysis,
+    BugRetrospective,
+    PatternClassification,
+}
+
+impl Operation {
+    /// Name recorded in `llm_usage.operation`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Title => "title",
+            Operation::Description => "description",
+            Operation::CardCommentary => "card_commentary",
+            Operation::Chat => "chat",
+            Operation::DreamAnalysis => "dream_analysis",
+            Operation::CreativePrompts => "creative_prompts",
+            Operation::ConnectionTest => "connection_test",
+            Operation::Embedding => "embedding",
+            Operation::Digest => "digest",
+            Operation::MindDumpAnalysis => "mind_dump_analysis",
+            Operation::BugRetrospective => "bug_retrospective",
+            Operation::PatternClassification => "pattern_classification",
+        }
+    }
+
+    pub fn timeout(&self, timeouts: &OperationTimeouts) -> Duration {
+        let secs = match self {
+            Operation::Title => timeouts.title_secs,
+            Operation::Description => timeouts.description_secs,
+            Operation::CardCommentary => timeouts.card_commentary_secs,
+            Operation::Chat => timeouts.chat_secs,
+            Operation::DreamAnalysis => timeouts.dream_analysis_secs,
+            Operation::CreativePrompts => timeouts.creative_prompts_secs,
+            Operation::ConnectionTest => timeouts.connection_test_secs,
+            Operation::Embedding => timeouts.embedding_secs,
+            Operation::Digest => timeouts.digest_secs,
+            Operation::MindDumpAnalysis => timeouts.mind_dump_analysis_secs,
+            Operation::BugRetrospective => timeouts.bug_retrospective_secs,
+            Operation::PatternClassification => timeouts.pattern_classification_secs,
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+#[derive(Clone)]
+struct ProviderClients {
+    ollama: reqwest::Client,
+    openai: reqwest::Client,
+    anthropic: reqwest::Client,
+}
+
+/// HTTP clients shared by every LLM request so connections are reused.
+/// Managed as Tauri state; the clients are rebuilt when the network settings change.
+#[derive(Default)]
+pub struct LlmHttp {
+    clients: Arc<Mutex<Option<(NetworkSettings, ProviderClients)>>>,
+    /// Usage from completed calls, waiting to be written to `llm_usage`
+    usage: Mutex<Vec<CreateLlmUsageInput>>,
+}
+
+impl LlmHttp {
+    pub fn new() -> Self {
+        LlmHttp::default()
+    }
+
+    /// A handle sharing these clients with a usage buffer of its own, so each
+    /// command records only the calls it made
+    pub fn fork(&self) -> Self {
+        LlmHttp {
+            clients: Arc::clone(&self.clients),
+            usage: Mutex::default(),
+        }
+    }
+
+    /// The client for `provider` configured with `settings`.
+    /// Cloning a `reqwest::Client` is cheap and shares its connection pool.
+    pub fn client(&self, provider: &LLMProvider, settings: &NetworkSettings) -> Result<reqwest::Client, LlmError> {
+        let mut cached = self.clients.lock().unwrap();
+
+        let clients = match cached.as_ref() {
+            Some((cached_settings, clients)) if cached_settings == settings => clients.clone(),
+            _ => {
+                let clients = ProviderClients {
+                    ollama: build_client(&settings.ollama, settings)?,
+                    openai: build_client(&settings.openai, settings)?,
+                    anthropic: build_client(&settings.anthropic, settings)?,
+                };
+                *cached = Some((settings.clone(), clients.clone()));
+                clients
+            }
+        };
+
+        match provider {
+            LLMProvider::Disabled => Err(LlmError::Disabled),
+            LLMProvider::Mock => Err(LlmError::Other("The mock provider makes no HTTP requests".to_string())),
+            LLMProvider::Ollama => Ok(clients.ollama),
+            LLMProvider::OpenAI => Ok(clients.openai),
+            LLMProvider::Anthropic => Ok(clients.anthropic),
+        }
+    }
+
+    pub fn record_usage(&self, usage: CreateLlmUsageInput) {
+        self.usage.lock().unwrap().push(usage);
+    }
+
+    /// Usage recorded since the last call, for the caller to persist
+    pub fn take_usage(&self) -> Vec<CreateLlmUsageInput> {
+        std::mem::take(&mut *self.usage.lock().unwrap())
+    }
+}
+
+fn build_client(timeouts: &ProviderTimeouts, settings: &NetworkSettings) -> Result<reqwest::Client, LlmError> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(timeouts.connect_secs))
+        .read_timeout(Duration::from_secs(timeouts.read_secs));
+
+    if let Some(proxy_url) = settings.proxy_url.as_deref().filter(|url| !url.trim().is_empty()) {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| LlmError::Other(format!("Invalid proxy URL '{}': {}", proxy_url, e)))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = settings.ca_cert_path.as_deref().filter(|path| !path.trim().is_empty()) {
+        builder = builder.add_root_certificate(load_certificate(path)?);
+    }
+
+    builder
+        .build()
+        .map_err(|e| LlmError::Other(format!("Failed to build HTTP client: {}", e)))
+}
+
+fn load_certificate(path: &str) -> Result<reqwest::Certificate, LlmError> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| LlmError::Other(format!("Failed to read CA certificate '{}': {}", path, e)))?;
+
+    reqwest::Certificate::from_pem(&bytes)
+        .or_else(|_| reqwest::Certificate::from_der(&bytes))
+        .map_err(|e| LlmError::Other(format!("Invalid CA certificate '{}': {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_timeouts_come_from_settings() {
+        let timeouts = OperationTimeouts {
+            chat_secs: 5,
+            ..Default::default()
+        };
+        assert_eq!(Operation::Chat.timeout(&timeouts), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_invalid_settings_are_reported() {
+        let http = LlmHttp::new();
+
+        let bad_proxy = NetworkSettings {
+            proxy_url: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(http.client(&LLMProvider::Ollama, &bad_proxy).is_err());
+
+        let missing_ca = NetworkSettings {
+            ca_cert_path: Some("/nonexistent/ca.pem".to_string()),
+            ..Default::default()
+        };
+        let err = http.client(&LLMProvider::OpenAI, &missing_ca).unwrap_err();
+        assert!(err.to_string().contains("CA certificate"));
+
+        assert!(http.client(&LLMProvider::Anthropic, &NetworkSettings::default()).is_ok());
+        assert_eq!(http.client(&LLMProvider::Disabled, &NetworkSettings::default()).unwrap_err(), LlmError::Disabled);
+    }
+
+    #[test]
+    fn test_forks_share_clients_but_not_usage() {
+        let http = LlmHttp::new();
+        let first = http.fork();
+        let second = http.fork();
+
+        first.client(&LLMProvider::Ollama, &NetworkSettings::default()).unwrap();
+        assert!(http.clients.lock().unwrap().is_some());
+
+        first.record_usage(CreateLlmUsageInput {
+            provider: "ollama".to_string(),
+            model: "llama3.2".to_string(),
+            operation: "chat".to_string(),
+            prompt_tokens: 10,
+            completion_tokens: 5,
+        });
+        assert!(second.take_usage().is_empty());
+        assert!(http.take_usage().is_empty());
+        assert_eq!(first.take_usage().len(), 1);
+    }
+}
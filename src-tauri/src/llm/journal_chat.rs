@@ -0,0 +1,147 @@
+use super::client;
+use super::embeddings::{self, JournalEntry};
+use super::error::LlmError;
+use super::http::LlmHttp;
+use super::templates::{PromptRegistry, PromptTemplateId};
+use super::types::{AskJournalResponse, JournalSource, LLMConfig};
+use crate::db::models::EntryType;
+use crate::db::Database;
+use serde_json::Value;
+
+/// Longest excerpt of one entry put into the prompt
+const ENTRY_CONTEXT_CHARS: usize = 1500;
+
+/// Answer `question` from the journal: retrieve the most relevant dreams, mind dumps
+/// and resolved bugs, pass them to the model with `[type:id]` references and
+/// report which of them the answer cites
+pub async fn ask_journal(
+    question: &str,
+    messages: &[Value],
+    limit: usize,
+    db: &Database,
+    http: &LlmHttp,
+    prompts: &PromptRegistry,
+    config: &LLMConfig,
+) -> Result<AskJournalResponse, LlmError> {
+    let entries = candidate_entries(db)?;
+    let results = embeddings::search_entries(question, &entries, limit, db, http, config).await?;
+
+    let retrieved: Vec<&JournalEntry> = results
+        .iter()
+        .filter_map(|r| entries.iter().find(|e| e.entry_type == r.entity_type && e.id == r.entity_id))
+        .collect();
+
+    let system_prompt = prompts.render(PromptTemplateId::JournalChat, &[("journal_context", &journal_context(&retrieved))])?;
+    let answer = client::chat_with_system_prompt(&system_prompt, question, messages, http, config).await?;
+
+    let cited = citations(&answer);
+    let mut sources: Vec<JournalSource> = retrieved
+        .iter()
+        .map(|entry| JournalSource {
+            entity_type: entry.entry_type,
+            entity_id: entry.id,
+            title: entry.title.clone(),
+            date: entry.date,
+            cited: cited.contains(&(entry.entry_type, entry.id)),
+        })
+        .collect();
+    // Stable, so retrieval order is kept within each group
+    sources.sort_by_key(|source| !source.cited);
+
+    Ok(AskJournalResponse { answer, sources })
+}
+
+/// Dreams, mind dumps and bugs the user has resolved; open bugs are still being worked out
+fn candidate_entries(db: &Database) -> Result<Vec<JournalEntry>, String> {
+    let mut entries = embeddings::journal_entries(db, &[EntryType::Dream, EntryType::MindDump])?;
+    let bugs = db.list_bugs(Some("resolved".to_string()), None).map_err(|e| e.to_string())?;
+    entries.extend(bugs.iter().filter_map(JournalEntry::from_bug));
+    Ok(entries)
+}
+
+fn journal_context(entries: &[&JournalEntry]) -> String {
+    if entries.is_empty() {
+        return "No journal entries match this question.".to_string();
+    }
+
+    entries
+        .iter()
+        .map(|entry| {
+            let text = match entry.text.char_indices().nth(ENTRY_CONTEXT_CHARS) {
+                Some((end, _)) => format!("{}…", &entry.text[..end]),
+                None => entry.text.clone(),
+            };
+            format!(
+                "[{}:{}] {} ({})\n{}",
+                entry.entry_type.as_str(),
+                entry.id,
+                entry.title,
+                entry.date.format("%Y-%m-%d"),
+                text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n")
+}
+
+/// `[dream:12]`-style references in the answer, in order of first appearance
+fn citations(answer: &str) -> Vec<(EntryType, i64)> {
+    let mut cited = Vec::new();
+
+    for candidate in answer.split('[').skip(1) {
+        let Some((reference, _)) = candidate.split_once(']') else {
+            continue;
+        };
+        let Some((entity_type, id)) = reference.split_once(':') else {
+            continue;
+        };
+        if let (Some(entity_type), Ok(id)) = (EntryType::parse(entity_type.trim()), id.trim().parse::<i64>()) {
+            if !cited.contains(&(entity_type, id)) {
+                cited.push((entity_type, id));
+            }
+        }
+    }
+
+    cited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::CreateMindDumpInput;
+    use crate::db::test_support::{block_on, create_dream, setup_test_db};
+    use crate::llm::types::LLMProvider;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_citations_parse_known_types_once() {
+        let answer = "You flew twice [dream:3] [dream:3], and wrote about it [mind_dump:7]. See [note:1] and [bug:x].";
+        assert_eq!(citations(answer), vec![(EntryType::Dream, 3), (EntryType::MindDump, 7)]);
+    }
+
+    #[test]
+    fn test_ask_journal_returns_cited_sources() {
+        let db = setup_test_db();
+        let flying = create_dream(&db, "Flying", "I was flying over the ocean at night");
+        db.create_mind_dump(CreateMindDumpInput {
+            title: None,
+            content: "Worried about flying to the conference".to_string(),
+            word_count: 6,
+        })
+        .unwrap();
+
+        let flying_id = flying.id.unwrap();
+        let config = LLMConfig {
+            provider: LLMProvider::Mock,
+            mock_responses: HashMap::from([("chat".to_string(), format!("You dreamt of flying [dream:{}] [bug:99].", flying_id))]),
+            ..Default::default()
+        };
+
+        let response = block_on(ask_journal("When did I dream about flying?", &[], 6, &db, &LlmHttp::new(), &PromptRegistry::default(), &config)).unwrap();
+
+        assert_eq!(response.sources.len(), 2);
+        assert_eq!((response.sources[0].entity_type, response.sources[0].entity_id), (EntryType::Dream, flying_id));
+        assert!(response.sources[0].cited);
+        assert!(!response.sources[1].cited);
+    }
+}
@@ -0,0 +1,29 @@
+use super::error::LlmError;
+use super::hash::fnv1a;
+use super::http::Operation;
+use super::types::LLMConfig;
+
+/// The scripted response for `operation`, falling back to the `default` entry.
+/// Responses go through the same parsing and validation as real provider output.
+pub fn respond(operation: Operation, config: &LLMConfig) -> Result<String, LlmError> {
+    config
+        .mock_responses
+        .get(operation.as_str())
+        .or_else(|| config.mock_responses.get("default"))
+        .cloned()
+        .ok_or_else(|| LlmError::Other(format!("No mock response scripted for '{}'", operation.as_str())))
+}
+
+const MOCK_EMBEDDING_DIMENSIONS: usize = 64;
+
+/// A deterministic bag-of-words vector, so texts sharing words score as similar
+pub fn embed(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; MOCK_EMBEDDING_DIMENSIONS];
+
+    for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        let hash = fnv1a(word.to_lowercase().as_bytes());
+        vector[(hash % MOCK_EMBEDDING_DIMENSIONS as u64) as usize] += 1.0;
+    }
+
+    vector
+}
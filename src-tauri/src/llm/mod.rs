@@ -1,5 +1,29 @@
 pub mod client;
 pub mod types;
 pub mod prompts;
+pub mod templates;
+pub mod hash;
+pub mod structured;
+pub mod error;
+pub mod http;
+pub mod discovery;
+pub mod settings;
+pub mod profile;
+pub mod usage;
+pub mod cache;
+pub mod mock;
+pub mod fixtures;
+pub mod embeddings;
+pub mod similar_dreams;
+pub mod journal_chat;
+pub mod dream_history;
+pub mod digest;
+pub mod bug_from_entry;
+pub mod blocker_patterns;
+pub mod bug_retrospective;
+pub mod bug_patterns;
+#[cfg(test)]
+pub mod stub_server;
 
 pub use types::*;
+pub use error::LlmError;
@@ -0,0 +1,169 @@
+use crate::db::Database;
+use serde::{Deserialize, Serialize};
+
+/// `app_settings` key holding the user profile
+const USER_PROFILE_KEY: &str = "user_profile";
+
+const ZODIAC_SIGNS: [&str; 12] = [
+    "aries", "taurus", "gemini", "cancer", "leo", "virgo", "libra", "scorpio", "sagittarius", "capricorn", "aquarius",
+    "pisces",
+];
+
+/// Features whose prompts can include the user profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PersonalizedFeature {
+    CardCommentary,
+    DiscoveryChat,
+    DreamAnalysis,
+    MindDumpAnalysis,
+    JournalChat,
+    Digest,
+    BugRetrospective,
+}
+
+/// Who the user is and how they like to be addressed, added to personalized prompts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UserProfile {
+    pub name: String,
+    pub pronouns: Option<String>,
+    pub zodiac_sign: Option<String>,
+    pub mbti_type: Option<String>,
+    pub preferred_tone: Option<String>,
+    pub language: Option<String>,
+    /// Features whose prompts must not include the profile
+    pub personalization_opt_out: Vec<PersonalizedFeature>,
+}
+
+impl UserProfile {
+    /// The "User Profile:" block for a feature's prompt, or `None` when the
+    /// profile is empty or the user opted the feature out
+    pub fn prompt_context(&self, feature: PersonalizedFeature) -> Option<String> {
+        if self.personalization_opt_out.contains(&feature) {
+            return None;
+        }
+
+        let fields = [
+            ("Name", Some(self.name.as_str())),
+            ("Pronouns", self.pronouns.as_deref()),
+            ("Zodiac Sign", self.zodiac_sign.as_deref()),
+            ("MBTI Type", self.mbti_type.as_deref()),
+            ("Preferred tone", self.preferred_tone.as_deref()),
+            ("Language (reply in this language)", self.language.as_deref()),
+        ];
+        let lines: Vec<String> = fields
+            .iter()
+            .filter_map(|(label, value)| value.filter(|v| !v.is_empty()).map(|v| format!("- {}: {}", label, v)))
+            .collect();
+
+        (!lines.is_empty()).then(|| format!("User Profile:\n{}", lines.join("\n")))
+    }
+}
+
+/// The saved profile, empty when nothing has been saved yet
+pub fn load_profile(db: &Database) -> Result<UserProfile, String> {
+    match db.get_setting(USER_PROFILE_KEY).map_err(|e| e.to_string())? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid stored user profile: {}", e)),
+        None => Ok(UserProfile::default()),
+    }
+}
+
+/// Validate and save the profile. Text fields are trimmed and blank ones cleared;
+/// zodiac signs and MBTI types are stored lowercase, as the frontend lists them.
+pub fn save_profile(db: &Database, profile: UserProfile) -> Result<UserProfile, String> {
+    let profile = normalize(profile)?;
+    let json = serde_json::to_string(&profile).map_err(|e| e.to_string())?;
+    db.set_setting(USER_PROFILE_KEY, &json).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+fn normalize(mut profile: UserProfile) -> Result<UserProfile, String> {
+    fn clean(value: Option<String>) -> Option<String> {
+        value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+    }
+
+    profile.name = profile.name.trim().to_string();
+    profile.pronouns = clean(profile.pronouns);
+    profile.preferred_tone = clean(profile.preferred_tone);
+    profile.language = clean(profile.language);
+    profile.zodiac_sign = clean(profile.zodiac_sign).map(|v| v.to_lowercase());
+    profile.mbti_type = clean(profile.mbti_type).map(|v| v.to_lowercase());
+
+    if let Some(sign) = &profile.zodiac_sign {
+        if !ZODIAC_SIGNS.contains(&sign.as_str()) {
+            return Err(format!("Unknown zodiac sign '{}'", sign));
+        }
+    }
+    if let Some(mbti) = &profile.mbti_type {
+        if !is_mbti_type(mbti) {
+            return Err(format!("Unknown MBTI type '{}'", mbti));
+        }
+    }
+
+    profile.personalization_opt_out.sort_by_key(|feature| *feature as u8);
+    profile.personalization_opt_out.dedup();
+
+    Ok(profile)
+}
+
+/// One letter from each of the four MBTI dichotomies, e.g. "infp"
+fn is_mbti_type(value: &str) -> bool {
+    let letters: Vec<char> = value.chars().collect();
+    letters.len() == 4
+        && ["ei", "sn", "tf", "jp"]
+            .iter()
+            .zip(&letters)
+            .all(|(pair, letter)| pair.contains(*letter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::setup_test_db;
+
+    #[test]
+    fn test_profile_is_normalized_and_validated() {
+        let db = setup_test_db();
+        assert_eq!(load_profile(&db).unwrap(), UserProfile::default());
+
+        let saved = save_profile(
+            &db,
+            UserProfile {
+                name: " Sam ".to_string(),
+                pronouns: Some(" ".to_string()),
+                zodiac_sign: Some("Pisces".to_string()),
+                mbti_type: Some("INFP".to_string()),
+                personalization_opt_out: vec![PersonalizedFeature::Digest, PersonalizedFeature::Digest],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(saved.name, "Sam");
+        assert_eq!(saved.pronouns, None);
+        assert_eq!(saved.zodiac_sign.as_deref(), Some("pisces"));
+        assert_eq!(saved.mbti_type.as_deref(), Some("infp"));
+        assert_eq!(saved.personalization_opt_out, vec![PersonalizedFeature::Digest]);
+        assert_eq!(load_profile(&db).unwrap(), saved);
+
+        let invalid = UserProfile { mbti_type: Some("abcd".to_string()), ..Default::default() };
+        assert!(save_profile(&db, invalid).is_err());
+    }
+
+    #[test]
+    fn test_prompt_context_respects_opt_out() {
+        let profile = UserProfile {
+            name: "Sam".to_string(),
+            language: Some("French".to_string()),
+            personalization_opt_out: vec![PersonalizedFeature::JournalChat],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            profile.prompt_context(PersonalizedFeature::DiscoveryChat).as_deref(),
+            Some("User Profile:\n- Name: Sam\n- Language (reply in this language): French")
+        );
+        assert_eq!(profile.prompt_context(PersonalizedFeature::JournalChat), None);
+        assert_eq!(UserProfile::default().prompt_context(PersonalizedFeature::DiscoveryChat), None);
+    }
+}
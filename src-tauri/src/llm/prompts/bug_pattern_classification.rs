@@ -0,0 +1,36 @@
+pub const BUG_PATTERN_CLASSIFICATION_PROMPT: &str = r#"You classify the blocker patterns present in a "bug": a recurring issue or pattern in someone's life that they explored with a card-based discovery process.
+
+## Blocker Patterns
+
+{blocker_patterns}
+
+## Your Task
+
+Read the bug below (its description, the cards drawn, the discovery conversation and their notes) and list the patterns from the list above that are actually present, with your confidence in each.
+
+## Output Structure
+
+Return a JSON object with this exact structure:
+
+```json
+{
+  "patterns": [
+    {
+      "pattern": "one pattern id copied exactly from the list above",
+      "confidence": 0.8,
+      "evidence": "One sentence quoting or paraphrasing what in the bug shows this pattern"
+    }
+  ]
+}
+```
+
+## Guidelines
+
+- List at most 3 patterns, each at most once, most confident first
+- confidence is a number from 0.0 (a guess) to 1.0 (clearly and repeatedly shown)
+- Only include a pattern when something in the text supports it; use an empty array if none apply
+- The response MUST be valid JSON - no extra text before or after
+
+---
+
+Bug to classify:"#;
@@ -5,6 +5,7 @@ A person is exploring a bug or issue in their {life_area} area. They've drawn a
 Card: {card_name}
 Question: {card_question}
 Meaning: {card_meaning}
+In the {life_area} area: {life_area_insight}
 
 Provide a brief, insightful commentary (1-2 short sentences) on how this card's meaning might relate to issues in their {life_area} area. Help them see potential connections without being prescriptive.
 
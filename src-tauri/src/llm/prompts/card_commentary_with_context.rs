@@ -9,6 +9,7 @@ New card being considered:
 Card: {card_name}
 Question: {card_question}
 Meaning: {card_meaning}
+In the {life_area} area: {life_area_insight}
 
 Provide a brief, insightful commentary (1-2 short sentences) on:
 1. How this new card's meaning might relate to issues in their {life_area} area
@@ -6,6 +6,11 @@ pub mod multiple_cards_commentary;
 pub mod discovery_chat;
 pub mod dream_analysis;
 pub mod creative_prompts;
+pub mod journal_chat;
+pub mod period_digest;
+pub mod mind_dump_analysis;
+pub mod bug_retrospective;
+pub mod bug_pattern_classification;
 
 pub use title_generation::TITLE_GENERATION_PROMPT;
 pub use description_optimization::DESCRIPTION_OPTIMIZATION_PROMPT;
@@ -15,3 +20,8 @@ pub use multiple_cards_commentary::MULTIPLE_CARDS_COMMENTARY_PROMPT;
 pub use discovery_chat::DISCOVERY_CHAT_SYSTEM_PROMPT;
 pub use dream_analysis::DREAM_ANALYSIS_PROMPT;
 pub use creative_prompts::CREATIVE_PROMPTS_GENERATION;
+pub use journal_chat::JOURNAL_CHAT_SYSTEM_PROMPT;
+pub use period_digest::PERIOD_DIGEST_PROMPT;
+pub use mind_dump_analysis::MIND_DUMP_ANALYSIS_PROMPT;
+pub use bug_retrospective::BUG_RETROSPECTIVE_PROMPT;
+pub use bug_pattern_classification::BUG_PATTERN_CLASSIFICATION_PROMPT;
@@ -0,0 +1,164 @@
+use super::types::{LLMConfig, LLMProvider, LlmSettingsView};
+use crate::db::Database;
+use crate::secrets::Secrets;
+
+/// `app_settings` key holding the LLM config, minus API keys
+const LLM_CONFIG_KEY: &str = "llm_config";
+
+pub const OPENAI_API_KEY: &str = "openai_api_key";
+pub const ANTHROPIC_API_KEY: &str = "anthropic_api_key";
+
+/// Secret name for a provider's API key; Ollama, Mock and Disabled have none
+pub fn api_key_name(provider: &LLMProvider) -> Option<&'static str> {
+    match provider {
+        LLMProvider::OpenAI => Some(OPENAI_API_KEY),
+        LLMProvider::Anthropic => Some(ANTHROPIC_API_KEY),
+        LLMProvider::Ollama | LLMProvider::Disabled | LLMProvider::Mock => None,
+    }
+}
+
+/// The saved config with API keys filled in from the secret store.
+/// Defaults (LLM disabled) when nothing has been saved yet.
+pub fn load_config(db: &Database, secrets: &Secrets) -> Result<LLMConfig, String> {
+    let config = match db.get_setting(LLM_CONFIG_KEY).map_err(|e| e.to_string())? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid stored LLM config: {}", e))?,
+        None => LLMConfig::default(),
+    };
+
+    fill_api_keys(config, secrets)
+}
+
+/// Save the config. Non-empty API keys go to the secret store; empty ones keep the stored key.
+pub fn save_config(db: &Database, secrets: &Secrets, mut config: LLMConfig) -> Result<(), String> {
+    if !config.openai_api_key.is_empty() {
+        secrets.set(OPENAI_API_KEY, &config.openai_api_key)?;
+    }
+    if !config.anthropic_api_key.is_empty() {
+        secrets.set(ANTHROPIC_API_KEY, &config.anthropic_api_key)?;
+    }
+
+    config.openai_api_key.clear();
+    config.anthropic_api_key.clear();
+
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    db.set_setting(LLM_CONFIG_KEY, &json).map_err(|e| e.to_string())
+}
+
+/// The config a command should use: one passed in (e.g. unsaved settings being tested)
+/// with missing API keys taken from the secret store, or else the stored config
+pub fn resolve_config(db: &Database, secrets: &Secrets, config: Option<LLMConfig>) -> Result<LLMConfig, String> {
+    match config {
+        Some(config) => fill_api_keys(config, secrets),
+        None => load_config(db, secrets),
+    }
+}
+
+pub fn settings_view(db: &Database, secrets: &Secrets) -> Result<LlmSettingsView, String> {
+    let mut config = load_config(db, secrets)?;
+
+    let has_openai_api_key = !config.openai_api_key.is_empty();
+    let has_anthropic_api_key = !config.anthropic_api_key.is_empty();
+    config.openai_api_key.clear();
+    config.anthropic_api_key.clear();
+
+    Ok(LlmSettingsView {
+        config,
+        has_openai_api_key,
+        has_anthropic_api_key,
+        secrets_backend: secrets.backend_name().to_string(),
+    })
+}
+
+fn fill_api_keys(mut config: LLMConfig, secrets: &Secrets) -> Result<LLMConfig, String> {
+    if config.openai_api_key.is_empty() {
+        config.openai_api_key = secrets.get(OPENAI_API_KEY)?.unwrap_or_default();
+    }
+    if config.anthropic_api_key.is_empty() {
+        config.anthropic_api_key = secrets.get(ANTHROPIC_API_KEY)?.unwrap_or_default();
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::SecretBackend;
+    use rusqlite::Connection;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryBackend(Mutex<HashMap<String, String>>);
+
+    impl SecretBackend for MemoryBackend {
+        fn name(&self) -> &'static str {
+            "memory"
+        }
+
+        fn get(&self, key: &str) -> Result<Option<String>, String> {
+            Ok(self.0.lock().unwrap().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), String> {
+            self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, key: &str) -> Result<bool, String> {
+            Ok(self.0.lock().unwrap().remove(key).is_some())
+        }
+    }
+
+    fn setup() -> (Database, Secrets) {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
+        (Database::from_connection(conn), Secrets::with_backend(Box::<MemoryBackend>::default()))
+    }
+
+    #[test]
+    fn test_api_keys_are_kept_out_of_the_database() {
+        let (db, secrets) = setup();
+
+        let config = LLMConfig {
+            provider: LLMProvider::OpenAI,
+            openai_api_key: "sk-test".to_string(),
+            openai_model: "gpt-4o".to_string(),
+            ..Default::default()
+        };
+        save_config(&db, &secrets, config).unwrap();
+
+        let stored = db.get_setting(LLM_CONFIG_KEY).unwrap().unwrap();
+        assert!(!stored.contains("sk-test"));
+        assert_eq!(secrets.get(OPENAI_API_KEY).unwrap().as_deref(), Some("sk-test"));
+
+        let loaded = load_config(&db, &secrets).unwrap();
+        assert_eq!(loaded.openai_api_key, "sk-test");
+        assert_eq!(loaded.openai_model, "gpt-4o");
+
+        let view = settings_view(&db, &secrets).unwrap();
+        assert!(view.has_openai_api_key);
+        assert!(!view.has_anthropic_api_key);
+        assert!(view.config.openai_api_key.is_empty());
+    }
+
+    #[test]
+    fn test_saving_without_key_keeps_stored_key() {
+        let (db, secrets) = setup();
+        secrets.set(ANTHROPIC_API_KEY, "sk-ant").unwrap();
+
+        let config = LLMConfig {
+            provider: LLMProvider::Anthropic,
+            anthropic_model: "claude-sonnet".to_string(),
+            ..Default::default()
+        };
+        save_config(&db, &secrets, config).unwrap();
+
+        let resolved = resolve_config(&db, &secrets, None).unwrap();
+        assert_eq!(resolved.anthropic_api_key, "sk-ant");
+        assert_eq!(resolved.anthropic_model, "claude-sonnet");
+
+        let passed_in = resolve_config(&db, &secrets, Some(LLMConfig::default())).unwrap();
+        assert!(matches!(passed_in.provider, LLMProvider::Disabled));
+        assert_eq!(passed_in.anthropic_api_key, "sk-ant");
+    }
+}
@@ -0,0 +1,217 @@
+use super::embeddings::{self, cosine_similarity, query_terms};
+use super::error::LlmError;
+use super::types::LLMConfig;
+use crate::db::models::{Dream, EntryType, RecurrenceSuggestion, SimilarDream};
+use crate::db::Database;
+use std::collections::{HashMap, HashSet};
+
+/// Relative weights of the similarity signals; a signal the target dream lacks is left out
+const CONTENT_WEIGHT: f32 = 0.6;
+const CARDS_WEIGHT: f32 = 0.25;
+const TAGS_WEIGHT: f32 = 0.15;
+
+/// Score above which a past dream is taken to be the same dream recurring
+pub const RECURRING_THRESHOLD: f32 = 0.7;
+
+/// Earlier dreams most like `dream_id`, best first, by content (embeddings when
+/// enabled, shared words otherwise), shared analysis cards and shared emotion tags
+pub fn find_similar_dreams(
+    dream_id: i64,
+    limit: usize,
+    db: &Database,
+    config: &LLMConfig,
+) -> Result<Vec<SimilarDream>, LlmError> {
+    let target = db
+        .get_dream(dream_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Dream {} not found", dream_id))?;
+
+    let candidates: Vec<Dream> = db
+        .list_all_dreams()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|dream| dream.id != Some(dream_id) && dream.date_occurred <= target.date_occurred)
+        .collect();
+
+    // Only vectors stored when dreams were saved or synced; nothing is embedded here
+    let vectors = if embeddings::enabled(config) {
+        dream_vectors(db, config)?
+    } else {
+        HashMap::new()
+    };
+
+    let cards = db.current_analysis_card_names().map_err(|e| e.to_string())?;
+    let no_cards = Vec::new();
+    let target_cards = cards.get(&dream_id).unwrap_or(&no_cards);
+    let target_tags = emotion_tags(&target);
+    let target_terms: HashSet<String> = query_terms(&format!("{} {}", target.title, target.content)).into_iter().collect();
+
+    let mut similar: Vec<SimilarDream> = candidates
+        .iter()
+        .filter_map(|dream| {
+            let id = dream.id?;
+
+            let content_score = match (vectors.get(&dream_id), vectors.get(&id)) {
+                (Some(a), Some(b)) => cosine_similarity(a, b).max(0.0),
+                _ => {
+                    let terms: HashSet<String> = query_terms(&format!("{} {}", dream.title, dream.content)).into_iter().collect();
+                    jaccard(&target_terms, &terms)
+                }
+            };
+
+            let dream_cards = cards.get(&id).unwrap_or(&no_cards);
+            let shared_cards: Vec<String> = target_cards.iter().filter(|c| dream_cards.contains(c)).cloned().collect();
+            let dream_tags = emotion_tags(dream);
+            let shared_tags: Vec<String> = target_tags.iter().filter(|t| dream_tags.contains(t)).cloned().collect();
+
+            let mut weighted = CONTENT_WEIGHT * content_score;
+            let mut total_weight = CONTENT_WEIGHT;
+            if !target_cards.is_empty() {
+                weighted += CARDS_WEIGHT * overlap(shared_cards.len(), target_cards.len(), dream_cards.len());
+                total_weight += CARDS_WEIGHT;
+            }
+            if !target_tags.is_empty() {
+                weighted += TAGS_WEIGHT * overlap(shared_tags.len(), target_tags.len(), dream_tags.len());
+                total_weight += TAGS_WEIGHT;
+            }
+
+            Some(SimilarDream {
+                dream_id: id,
+                title: dream.title.clone(),
+                date_occurred: dream.date_occurred,
+                score: weighted / total_weight,
+                content_score,
+                shared_cards,
+                shared_tags,
+            })
+        })
+        .filter(|similar| similar.score > 0.0)
+        .collect();
+
+    similar.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.date_occurred.cmp(&a.date_occurred)));
+    similar.truncate(limit);
+    Ok(similar)
+}
+
+/// Mark the dream as recurring when its best match clears the threshold.
+/// The period is how long before this dream the matching one occurred.
+pub fn recurrence_suggestion(dream: &Dream, similar: &[SimilarDream]) -> Option<RecurrenceSuggestion> {
+    let best = similar.iter().find(|s| s.score >= RECURRING_THRESHOLD)?;
+    let days = (dream.date_occurred - best.date_occurred).num_days();
+
+    let period = match days {
+        0 => "today",
+        1 => "yesterday",
+        2..=10 => "last_week",
+        11..=24 => "few_weeks_ago",
+        25..=45 => "last_month",
+        46..=300 => "months_ago",
+        _ => "last_year",
+    };
+
+    Some(RecurrenceSuggestion {
+        is_recurring: true,
+        last_occurrence_period: period.to_string(),
+        matched_dream_id: best.dream_id,
+    })
+}
+
+fn dream_vectors(db: &Database, config: &LLMConfig) -> Result<HashMap<i64, Vec<f32>>, String> {
+    let model = embeddings::embedding_model(config).unwrap_or_default();
+
+    Ok(db
+        .list_embeddings(config.provider.as_str(), &model)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|e| e.entity_type == EntryType::Dream)
+        .map(|e| (e.entity_id, e.vector))
+        .collect())
+}
+
+/// `emotions_tags` is a JSON array of strings; older entries may be comma-separated
+pub(super) fn emotion_tags(dream: &Dream) -> Vec<String> {
+    let Some(raw) = dream.emotions_tags.as_deref().filter(|t| !t.trim().is_empty()) else {
+        return Vec::new();
+    };
+
+    let tags: Vec<String> = serde_json::from_str(raw)
+        .unwrap_or_else(|_| raw.split(',').map(|t| t.to_string()).collect());
+
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()) {
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    overlap(a.intersection(b).count(), a.len(), b.len())
+}
+
+/// Shared items over the size of the union
+fn overlap(shared: usize, a: usize, b: usize) -> f32 {
+    let union = a + b - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f32 / union as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::CreateDreamInput;
+    use crate::db::test_support::{block_on, dream_input, setup_test_db};
+    use crate::llm::http::LlmHttp;
+    use crate::llm::types::{EmbeddingSettings, LLMProvider};
+    use chrono::{Duration, Utc};
+
+    fn create_dream(db: &Database, days_ago: i64, title: &str, content: &str, tags: Option<&str>) -> Dream {
+        db.create_dream(CreateDreamInput {
+            date_occurred: Utc::now() - Duration::days(days_ago),
+            emotions_tags: tags.map(|t| t.to_string()),
+            ..dream_input(title, content)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_emotion_tags_accept_json_and_comma_lists() {
+        let mut dream = create_dream(&setup_test_db(), 0, "T", "C", Some(r#"["Fear", "joy", "fear"]"#));
+        assert_eq!(emotion_tags(&dream), vec!["fear", "joy"]);
+
+        dream.emotions_tags = Some("anxious, calm".to_string());
+        assert_eq!(emotion_tags(&dream), vec!["anxious", "calm"]);
+    }
+
+    #[test]
+    fn test_finds_earlier_matching_dream_and_suggests_recurrence() {
+        let db = setup_test_db();
+        let config = LLMConfig {
+            provider: LLMProvider::Mock,
+            embeddings: EmbeddingSettings { enabled: true, ..Default::default() },
+            ..Default::default()
+        };
+
+        let earlier = create_dream(&db, 20, "Teeth falling out", "My teeth crumbled and fell out at school", Some(r#"["fear"]"#));
+        create_dream(&db, 5, "Beach", "Warm sand and a calm blue sea", Some(r#"["calm"]"#));
+        let later = create_dream(&db, -3, "Teeth", "My teeth crumbled and fell out at school", None);
+        let dream = create_dream(&db, 0, "Teeth again", "My teeth crumbled and fell out at school again", Some(r#"["fear"]"#));
+
+        let entries = embeddings::journal_entries(&db, &[EntryType::Dream]).unwrap();
+        block_on(embeddings::sync_index(&db, &LlmHttp::new(), &config, &entries)).unwrap();
+
+        let similar = find_similar_dreams(dream.id.unwrap(), 5, &db, &config).unwrap();
+
+        assert_eq!(similar[0].dream_id, earlier.id.unwrap());
+        assert_eq!(similar[0].shared_tags, vec!["fear"]);
+        assert!(similar.iter().all(|s| s.dream_id != later.id.unwrap()));
+
+        let suggestion = recurrence_suggestion(&dream, &similar).unwrap();
+        assert_eq!(suggestion.matched_dream_id, earlier.id.unwrap());
+        assert_eq!(suggestion.last_occurrence_period, "few_weeks_ago");
+    }
+}
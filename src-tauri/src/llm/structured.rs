@@ -0,0 +1,622 @@
+use super::error::LlmError;
+use super::types::{BugPatternClassificationResponse, BugRetrospectiveResponse, GenerateCreativePromptsResponse, GenerateDreamAnalysisResponse, MindDumpAnalysisResponse, SymbolCard};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+
+/// Number of prompts the creative prompts template asks for in each category
+pub const PROMPTS_PER_CATEGORY: usize = 3;
+
+/// JSON schema for providers that can constrain their output.
+/// Kept to the subset every provider accepts: objects list all their properties as
+/// required and forbid extras, and counts are left to the validators below.
+#[derive(Debug, Clone)]
+pub struct OutputSchema {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub schema: Value,
+}
+
+pub fn dream_analysis_schema(deck: &[String]) -> OutputSchema {
+    OutputSchema {
+        name: "dream_analysis",
+        description: "Record the analysis of a dream and the symbol cards that match it",
+        schema: json!({
+            "type": "object",
+            "properties": {
+                "themes_patterns": { "type": "string" },
+                "emotional_analysis": { "type": "string" },
+                "narrative_summary": { "type": "string" },
+                "symbol_cards": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "card_name": { "type": "string", "enum": deck },
+                            "relevance_note": { "type": "string" }
+                        },
+                        "required": ["card_name", "relevance_note"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["themes_patterns", "emotional_analysis", "narrative_summary", "symbol_cards"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+pub fn mind_dump_analysis_schema(deck: &[String]) -> OutputSchema {
+    OutputSchema {
+        name: "mind_dump_analysis",
+        description: "Record the summary, worries, action items and suggested cards for a mind dump",
+        schema: json!({
+            "type": "object",
+            "properties": {
+                "summary": { "type": "string" },
+                "worries": { "type": "array", "items": { "type": "string" } },
+                "action_items": { "type": "array", "items": { "type": "string" } },
+                "suggested_cards": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "card_name": { "type": "string", "enum": deck },
+                            "relevance_note": { "type": "string" }
+                        },
+                        "required": ["card_name", "relevance_note"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["summary", "worries", "action_items", "suggested_cards"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+pub fn bug_retrospective_schema(pattern_ids: &[String]) -> OutputSchema {
+    OutputSchema {
+        name: "bug_retrospective",
+        description: "Record the root pattern, blocker pattern, what helped and follow-ups for a bug",
+        schema: json!({
+            "type": "object",
+            "properties": {
+                "root_pattern": { "type": "string" },
+                "blocker_pattern": { "type": "string", "enum": pattern_ids },
+                "what_helped": { "type": "array", "items": { "type": "string" } },
+                "follow_ups": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["root_pattern", "blocker_pattern", "what_helped", "follow_ups"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+pub fn bug_pattern_classification_schema(pattern_ids: &[String]) -> OutputSchema {
+    OutputSchema {
+        name: "bug_pattern_classification",
+        description: "Record the blocker patterns present in a bug with confidence and evidence",
+        schema: json!({
+            "type": "object",
+            "properties": {
+                "patterns": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "pattern": { "type": "string", "enum": pattern_ids },
+                            "confidence": { "type": "number" },
+                            "evidence": { "type": "string" }
+                        },
+                        "required": ["pattern", "confidence", "evidence"],
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "required": ["patterns"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+pub fn creative_prompts_schema() -> OutputSchema {
+    let prompts = json!({
+        "type": "array",
+        "description": format!("Exactly {} prompts", PROMPTS_PER_CATEGORY),
+        "items": { "type": "string" }
+    });
+
+    OutputSchema {
+        name: "creative_prompts",
+        description: "Record image, music and story prompts inspired by a dream",
+        schema: json!({
+            "type": "object",
+            "properties": {
+                "image_prompts": prompts,
+                "music_prompts": prompts,
+                "story_prompts": prompts
+            },
+            "required": ["image_prompts", "music_prompts", "story_prompts"],
+            "additionalProperties": false
+        }),
+    }
+}
+
+/// One commentary string per card, keyed by the card id
+pub fn card_commentary_schema(card_ids: &[String]) -> OutputSchema {
+    let properties: serde_json::Map<String, Value> = card_ids
+        .iter()
+        .map(|id| (id.clone(), json!({ "type": "string" })))
+        .collect();
+
+    OutputSchema {
+        name: "card_commentary",
+        description: "Record a short commentary for each drawn card, keyed by card id",
+        schema: json!({
+            "type": "object",
+            "properties": properties,
+            "required": card_ids,
+            "additionalProperties": false
+        }),
+    }
+}
+
+/// Parse a JSON object out of free-form model output.
+/// Handles code fences, leading/trailing prose, smart quotes and trailing commas.
+pub fn parse<T: DeserializeOwned>(text: &str) -> Result<T, String> {
+    let json = extract_object(text).ok_or("No JSON object found in response")?;
+
+    match serde_json::from_str(json) {
+        Ok(value) => Ok(value),
+        Err(first_error) => serde_json::from_str(&repair(json))
+            .map_err(|_| format!("Failed to parse JSON response: {}", first_error)),
+    }
+}
+
+/// Parse and then check the result, so a single error covers both steps
+pub fn parse_validated<T, F>(text: &str, validate: F) -> Result<T, String>
+where
+    T: DeserializeOwned,
+    F: Fn(&T) -> Result<(), String>,
+{
+    let value = parse(text)?;
+    validate(&value).map_err(|e| format!("Response failed validation: {}", e))?;
+    Ok(value)
+}
+
+/// Send `prompt`, parse and validate the answer, and if that fails ask once more
+/// with the error spelled out for the model.
+pub async fn request_with_retry<T, S, Fut, V>(prompt: &str, send: S, validate: V) -> Result<T, LlmError>
+where
+    T: DeserializeOwned,
+    S: Fn(String) -> Fut,
+    Fut: Future<Output = Result<String, LlmError>>,
+    V: Fn(&T) -> Result<(), String>,
+{
+    let text = send(prompt.to_string()).await?;
+
+    match parse_validated(&text, &validate) {
+        Ok(value) => Ok(value),
+        Err(error) => {
+            eprintln!("Structured response rejected, asking again: {}", error);
+            let text = send(corrective_prompt(prompt, &error)).await?;
+            parse_validated(&text, &validate).map_err(LlmError::InvalidResponse)
+        }
+    }
+}
+
+/// Instructions appended to the original prompt when the first answer was unusable
+pub fn corrective_prompt(prompt: &str, error: &str) -> String {
+    format!(
+        "{}\n\nIMPORTANT: Your previous answer could not be used ({}). \
+         Respond again with ONLY a single valid JSON object in exactly the format described above, \
+         with no code fences or commentary.",
+        prompt, error
+    )
+}
+
+pub fn validate_dream_analysis(
+    analysis: &GenerateDreamAnalysisResponse,
+    deck: &[String],
+) -> Result<(), String> {
+    for (field, value) in [
+        ("themes_patterns", &analysis.themes_patterns),
+        ("emotional_analysis", &analysis.emotional_analysis),
+        ("narrative_summary", &analysis.narrative_summary),
+    ] {
+        if value.trim().is_empty() {
+            return Err(format!("'{}' is empty", field));
+        }
+    }
+
+    validate_card_names(&analysis.symbol_cards, deck)
+}
+
+pub fn validate_mind_dump_analysis(
+    analysis: &MindDumpAnalysisResponse,
+    deck: &[String],
+) -> Result<(), String> {
+    if analysis.summary.trim().is_empty() {
+        return Err("'summary' is empty".to_string());
+    }
+    for (field, values) in [("worries", &analysis.worries), ("action_items", &analysis.action_items)] {
+        if values.iter().any(|v| v.trim().is_empty()) {
+            return Err(format!("'{}' contains an empty item", field));
+        }
+    }
+
+    validate_card_names(&analysis.suggested_cards, deck)
+}
+
+pub fn validate_bug_retrospective(
+    retrospective: &BugRetrospectiveResponse,
+    pattern_ids: &[String],
+) -> Result<(), String> {
+    if retrospective.root_pattern.trim().is_empty() {
+        return Err("'root_pattern' is empty".to_string());
+    }
+    if !pattern_ids.contains(&retrospective.blocker_pattern) {
+        return Err(format!(
+            "unknown blocker pattern '{}'; blocker_pattern must be copied exactly from the pattern list",
+            retrospective.blocker_pattern
+        ));
+    }
+    for (field, values) in [("what_helped", &retrospective.what_helped), ("follow_ups", &retrospective.follow_ups)] {
+        if values.iter().any(|v| v.trim().is_empty()) {
+            return Err(format!("'{}' contains an empty item", field));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_bug_pattern_classification(
+    classification: &BugPatternClassificationResponse,
+    pattern_ids: &[String],
+) -> Result<(), String> {
+    let mut seen = Vec::new();
+    for detected in &classification.patterns {
+        if !pattern_ids.contains(&detected.pattern) {
+            return Err(format!(
+                "unknown pattern '{}'; pattern must be copied exactly from the pattern list",
+                detected.pattern
+            ));
+        }
+        if seen.contains(&detected.pattern.as_str()) {
+            return Err(format!("pattern '{}' is listed more than once", detected.pattern));
+        }
+        if !(0.0..=1.0).contains(&detected.confidence) {
+            return Err(format!("confidence for '{}' must be between 0.0 and 1.0", detected.pattern));
+        }
+        seen.push(detected.pattern.as_str());
+    }
+
+    Ok(())
+}
+
+fn validate_card_names(cards: &[SymbolCard], deck: &[String]) -> Result<(), String> {
+    let unknown: Vec<&str> = cards
+        .iter()
+        .map(|card| card.card_name.as_str())
+        .filter(|name| !deck.iter().any(|card| card == name))
+        .collect();
+
+    if !unknown.is_empty() {
+        return Err(format!(
+            "unknown symbol cards {}; card_name must be copied exactly from the card list",
+            unknown.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn validate_creative_prompts(prompts: &GenerateCreativePromptsResponse) -> Result<(), String> {
+    for (field, values) in [
+        ("image_prompts", &prompts.image_prompts),
+        ("music_prompts", &prompts.music_prompts),
+        ("story_prompts", &prompts.story_prompts),
+    ] {
+        if values.len() != PROMPTS_PER_CATEGORY {
+            return Err(format!(
+                "'{}' must contain exactly {} prompts, got {}",
+                field,
+                PROMPTS_PER_CATEGORY,
+                values.len()
+            ));
+        }
+        if values.iter().any(|v| v.trim().is_empty()) {
+            return Err(format!("'{}' contains an empty prompt", field));
+        }
+    }
+
+    Ok(())
+}
+
+/// Every requested card id needs a non-empty commentary
+pub fn validate_card_commentary(
+    commentary: &HashMap<String, String>,
+    card_ids: &[String],
+) -> Result<(), String> {
+    let missing: Vec<&str> = card_ids
+        .iter()
+        .filter(|id| commentary.get(*id).is_none_or(|text| text.trim().is_empty()))
+        .map(|id| id.as_str())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(format!("missing commentary for cards {}", missing.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Find the first balanced `{...}` in the text, skipping any code fences around it
+fn extract_object(text: &str) -> Option<&str> {
+    let text = strip_code_fence(text);
+    let start = text.find('{')?;
+
+    let mut depth = 0usize;
+    let mut string = StringState::default();
+
+    for (offset, c) in text[start..].char_indices() {
+        if string.opener.is_some() {
+            string.step(c, &text[start + offset + c.len_utf8()..]);
+            continue;
+        }
+
+        match c {
+            '"' => string.opener = Some(Quote::Straight),
+            _ if is_curly_double_quote(c) => string.opener = Some(Quote::Curly),
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..=start + offset]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Unbalanced, e.g. the model was cut off; fall back to the last closing brace
+    text.rfind('}').filter(|end| *end > start).map(|end| &text[start..=end])
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    let Some(fence_start) = trimmed.find("```") else {
+        return trimmed;
+    };
+
+    // Skip the fence and an optional language tag such as ```json
+    let after = &trimmed[fence_start + 3..];
+    let body_start = after.find('\n').map(|i| i + 1).unwrap_or(0);
+    let body = &after[body_start..];
+
+    match body.find("```") {
+        Some(end) => &body[..end],
+        None => body,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Quote {
+    Straight,
+    Curly,
+}
+
+/// Tracks the JSON string being scanned, if any. Curly quotes only delimit a
+/// string that a curly quote opened, and only where a delimiter can stand
+/// (before `:`, `,`, `}`, `]` or the end); elsewhere they are prose.
+#[derive(Debug, Default)]
+struct StringState {
+    opener: Option<Quote>,
+    escaped: bool,
+}
+
+impl StringState {
+    /// Consume `c` inside a string; returns true if it closed the string
+    fn step(&mut self, c: char, rest: &str) -> bool {
+        let closes = match c {
+            _ if self.escaped => {
+                self.escaped = false;
+                false
+            }
+            '\\' => {
+                self.escaped = true;
+                false
+            }
+            '"' => true,
+            _ => {
+                self.opener == Some(Quote::Curly)
+                    && is_curly_double_quote(c)
+                    && matches!(rest.trim_start().chars().next(), None | Some(':' | ',' | '}' | ']'))
+            }
+        };
+        if closes {
+            self.opener = None;
+        }
+        closes
+    }
+}
+
+fn is_curly_double_quote(c: char) -> bool {
+    matches!(c, '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}')
+}
+
+/// Fix the mistakes models commonly make when writing JSON by hand: curly
+/// quotes used as string delimiters and commas before a closing bracket.
+/// Only applied after a strict parse fails; quotes inside strings are kept.
+fn repair(json: &str) -> String {
+    let mut repaired = String::with_capacity(json.len());
+    let mut string = StringState::default();
+
+    for (i, c) in json.char_indices() {
+        let rest = &json[i + c.len_utf8()..];
+
+        if string.opener.is_some() {
+            let closes = string.step(c, rest);
+            repaired.push(if closes { '"' } else { c });
+            continue;
+        }
+
+        if c == '"' {
+            string.opener = Some(Quote::Straight);
+        } else if is_curly_double_quote(c) {
+            string.opener = Some(Quote::Curly);
+            repaired.push('"');
+            continue;
+        } else if c == ',' && matches!(rest.trim_start().chars().next(), Some('}' | ']')) {
+            continue;
+        }
+        repaired.push(c);
+    }
+
+    repaired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::block_on;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Sample {
+        title: String,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_parse_fenced_json_with_prose() {
+        let text = "Here you go:\n```json\n{\"title\": \"Fox {den}\", \"tags\": [\"a\"]}\n```\nHope this helps! {not json}";
+        let sample: Sample = parse(text).unwrap();
+        assert_eq!(sample.title, "Fox {den}");
+    }
+
+    #[test]
+    fn test_parse_repairs_trailing_commas_and_smart_quotes() {
+        let text = "{\u{201C}title\u{201D}: \u{201C}It\u{2019}s fine\u{201D}, \"tags\": [\"a\", \"b\",],}";
+        let sample: Sample = parse(text).unwrap();
+        assert_eq!(
+            sample,
+            Sample { title: "It\u{2019}s fine".to_string(), tags: vec!["a".to_string(), "b".to_string()] }
+        );
+    }
+
+    #[test]
+    fn test_repair_keeps_curly_quotes_inside_strings() {
+        let text = "{\"title\": \"She said \u{201C}stay\u{201D}, then left {late}\", \"tags\": [\u{201C}a \u{201C}b\u{201D} c\u{201D},],}";
+        let sample: Sample = parse(text).unwrap();
+        assert_eq!(sample.title, "She said \u{201C}stay\u{201D}, then left {late}");
+        assert_eq!(sample.tags, vec!["a \u{201C}b\u{201D} c".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_keeps_commas_inside_strings() {
+        let sample: Sample = parse("{\"title\": \"a ,}\", \"tags\": [],}").unwrap();
+        assert_eq!(sample.title, "a ,}");
+    }
+
+    #[test]
+    fn test_validate_dream_analysis_checks_deck() {
+        let deck = vec!["Delivery Driver".to_string(), "Fox".to_string()];
+        let mut analysis: GenerateDreamAnalysisResponse = parse(
+            r#"{"themes_patterns": "t", "emotional_analysis": "e", "narrative_summary": "n",
+                "symbol_cards": [{"card_name": "Fox", "relevance_note": "r"}]}"#,
+        )
+        .unwrap();
+        assert!(validate_dream_analysis(&analysis, &deck).is_ok());
+
+        analysis.symbol_cards[0].card_name = "The Moon".to_string();
+        let err = validate_dream_analysis(&analysis, &deck).unwrap_err();
+        assert!(err.contains("The Moon"));
+    }
+
+    #[test]
+    fn test_validate_mind_dump_analysis_checks_items_and_deck() {
+        let deck = vec!["Fox".to_string()];
+        let mut analysis: MindDumpAnalysisResponse = parse(
+            r#"{"summary": "s", "worries": ["rent"], "action_items": ["call the landlord"],
+                "suggested_cards": [{"card_name": "Fox", "relevance_note": "r"}]}"#,
+        )
+        .unwrap();
+        assert!(validate_mind_dump_analysis(&analysis, &deck).is_ok());
+
+        analysis.action_items.push(" ".to_string());
+        assert!(validate_mind_dump_analysis(&analysis, &deck).unwrap_err().contains("action_items"));
+
+        analysis.action_items.pop();
+        analysis.suggested_cards[0].card_name = "The Moon".to_string();
+        assert!(validate_mind_dump_analysis(&analysis, &deck).unwrap_err().contains("The Moon"));
+    }
+
+    #[test]
+    fn test_validate_creative_prompts_requires_three_each() {
+        let three = r#"["a", "b", "c"]"#;
+        let valid: GenerateCreativePromptsResponse = parse(&format!(
+            r#"{{"image_prompts": {three}, "music_prompts": {three}, "story_prompts": {three}}}"#
+        ))
+        .unwrap();
+        assert!(validate_creative_prompts(&valid).is_ok());
+
+        let short: GenerateCreativePromptsResponse = parse(&format!(
+            r#"{{"image_prompts": {three}, "music_prompts": ["a"], "story_prompts": {three}}}"#
+        ))
+        .unwrap();
+        assert!(validate_creative_prompts(&short).unwrap_err().contains("music_prompts"));
+    }
+
+    #[test]
+    fn test_validate_card_commentary_requires_every_card() {
+        let commentary: HashMap<String, String> = parse(r#"{"1": "one", "2": " "}"#).unwrap();
+        let err = validate_card_commentary(&commentary, &["1".to_string(), "2".to_string(), "3".to_string()])
+            .unwrap_err();
+        assert!(err.contains("2, 3"));
+    }
+
+    #[test]
+    fn test_request_with_retry_reasks_once() {
+        let calls = std::cell::RefCell::new(Vec::new());
+        let result: Result<Sample, LlmError> = block_on(request_with_retry(
+            "Give me JSON",
+            |prompt| {
+                calls.borrow_mut().push(prompt);
+                let answer = if calls.borrow().len() == 1 {
+                    "Sure! {\"title\": \"x\"}".to_string()
+                } else {
+                    "{\"title\": \"x\", \"tags\": [\"a\"]}".to_string()
+                };
+                async move { Ok(answer) }
+            },
+            |_: &Sample| Ok(()),
+        ));
+
+        assert_eq!(result.unwrap().tags, vec!["a".to_string()]);
+        let calls = calls.into_inner();
+        assert_eq!(calls.len(), 2);
+        assert!(calls[1].starts_with("Give me JSON"));
+        assert!(calls[1].contains("could not be used"));
+    }
+
+    #[test]
+    fn test_card_commentary_schema_requires_each_card() {
+        let schema = card_commentary_schema(&["1".to_string(), "7".to_string()]).schema;
+        assert_eq!(schema["required"], json!(["1", "7"]));
+        assert_eq!(schema["properties"]["7"]["type"], "string");
+    }
+
+    #[test]
+    fn test_parse_validated_reports_validation_errors() {
+        let err = parse_validated::<Sample, _>("{\"title\": \"x\", \"tags\": []}", |s| {
+            if s.tags.is_empty() { Err("no tags".to_string()) } else { Ok(()) }
+        })
+        .unwrap_err();
+        assert!(err.contains("no tags"));
+
+        assert!(parse::<Sample>("no json here").is_err());
+    }
+}
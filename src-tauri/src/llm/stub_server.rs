@@ -0,0 +1,185 @@
+//! A local HTTP server that stands in for Ollama, OpenAI and Anthropic in tests.
+//! It answers requests with scripted responses in order and records what it was sent.
+
+use super::types::{LLMConfig, LLMProvider};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub struct StubResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl StubResponse {
+    pub fn ok(body: Value) -> Self {
+        StubResponse {
+            status: 200,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn error(status: u16, body: Value) -> Self {
+        StubResponse {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// `POST /api/generate` reply
+    pub fn ollama_generate(text: &str) -> Self {
+        Self::ok(json!({ "response": text, "done": true, "prompt_eval_count": 10, "eval_count": 5 }))
+    }
+
+    /// `POST /v1/chat/completions` reply
+    pub fn openai_chat(text: &str) -> Self {
+        Self::ok(json!({
+            "choices": [{ "index": 0, "message": { "role": "assistant", "content": text }, "finish_reason": "stop" }],
+            "usage": { "prompt_tokens": 10, "completion_tokens": 5 }
+        }))
+    }
+
+    /// `POST /v1/messages` reply
+    pub fn anthropic_message(text: &str) -> Self {
+        Self::ok(json!({
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "text", "text": text }],
+            "stop_reason": "end_turn",
+            "usage": { "input_tokens": 10, "output_tokens": 5 }
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == &name.to_ascii_lowercase())
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub struct StubServer {
+    url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl StubServer {
+    /// Serve `responses` one per request, then stop accepting connections
+    pub fn start(responses: Vec<StubResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind stub server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for response in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                serve(stream, &response, &recorded);
+            }
+        });
+
+        StubServer { url, requests }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// A config for `provider` with every base URL pointing at this server
+    pub fn config(&self, provider: LLMProvider) -> LLMConfig {
+        LLMConfig {
+            provider,
+            ollama_url: self.url.clone(),
+            ollama_model: "llama3.2".to_string(),
+            openai_base_url: self.url.clone(),
+            openai_api_key: "sk-test".to_string(),
+            openai_model: "gpt-4o-mini".to_string(),
+            anthropic_base_url: self.url.clone(),
+            anthropic_api_key: "sk-ant-test".to_string(),
+            anthropic_model: "claude-haiku-4-5".to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Handle one request, recording it before replying so the test sees it once the client returns
+fn serve(stream: TcpStream, response: &StubResponse, recorded: &Mutex<Vec<RecordedRequest>>) -> Option<()> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    recorded.lock().unwrap().push(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+    });
+
+    let payload = response.body.to_string();
+    let mut reply = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        payload.len()
+    );
+    for (name, value) in &response.headers {
+        reply.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    reply.push_str("\r\n");
+    reply.push_str(&payload);
+
+    let mut stream = stream;
+    stream.write_all(reply.as_bytes()).ok()?;
+    stream.flush().ok()
+}
@@ -0,0 +1,475 @@
+use super::prompts::{
+    BUG_PATTERN_CLASSIFICATION_PROMPT, BUG_RETROSPECTIVE_PROMPT, CARD_COMMENTARY_PROMPT, CARD_COMMENTARY_WITH_CONTEXT_PROMPT, CREATIVE_PROMPTS_GENERATION,
+    DESCRIPTION_OPTIMIZATION_PROMPT, DISCOVERY_CHAT_SYSTEM_PROMPT, DREAM_ANALYSIS_PROMPT,
+    JOURNAL_CHAT_SYSTEM_PROMPT, MIND_DUMP_ANALYSIS_PROMPT, MULTIPLE_CARDS_COMMENTARY_PROMPT, PERIOD_DIGEST_PROMPT, TITLE_GENERATION_PROMPT,
+};
+use super::hash::fnv1a;
+use super::profile::{PersonalizedFeature, UserProfile};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Identifies one of the built-in prompt templates.
+/// The snake_case name is what gets stored in the database and sent by the frontend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptTemplateId {
+    TitleGeneration,
+    DescriptionOptimization,
+    CardCommentary,
+    CardCommentaryWithContext,
+    MultipleCardsCommentary,
+    DiscoveryChat,
+    DreamAnalysis,
+    CreativePrompts,
+    JournalChat,
+    PeriodDigest,
+    MindDumpAnalysis,
+    BugRetrospective,
+    BugPatternClassification,
+}
+
+impl PromptTemplateId {
+    pub const ALL: [PromptTemplateId; 13] = [
+        PromptTemplateId::TitleGeneration,
+        PromptTemplateId::DescriptionOptimization,
+        PromptTemplateId::CardCommentary,
+        PromptTemplateId::CardCommentaryWithContext,
+        PromptTemplateId::MultipleCardsCommentary,
+        PromptTemplateId::DiscoveryChat,
+        PromptTemplateId::DreamAnalysis,
+        PromptTemplateId::CreativePrompts,
+        PromptTemplateId::JournalChat,
+        PromptTemplateId::PeriodDigest,
+        PromptTemplateId::MindDumpAnalysis,
+        PromptTemplateId::BugRetrospective,
+        PromptTemplateId::BugPatternClassification,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptTemplateId::TitleGeneration => "title_generation",
+            PromptTemplateId::DescriptionOptimization => "description_optimization",
+            PromptTemplateId::CardCommentary => "card_commentary",
+            PromptTemplateId::CardCommentaryWithContext => "card_commentary_with_context",
+            PromptTemplateId::MultipleCardsCommentary => "multiple_cards_commentary",
+            PromptTemplateId::DiscoveryChat => "discovery_chat",
+            PromptTemplateId::DreamAnalysis => "dream_analysis",
+            PromptTemplateId::CreativePrompts => "creative_prompts",
+            PromptTemplateId::JournalChat => "journal_chat",
+            PromptTemplateId::PeriodDigest => "period_digest",
+            PromptTemplateId::MindDumpAnalysis => "mind_dump_analysis",
+            PromptTemplateId::BugRetrospective => "bug_retrospective",
+            PromptTemplateId::BugPatternClassification => "bug_pattern_classification",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|t| t.as_str() == id)
+    }
+
+    /// The built-in definition for this template
+    pub fn definition(&self) -> &'static PromptTemplate {
+        TEMPLATES
+            .iter()
+            .find(|t| t.id == *self)
+            .expect("every template id has a definition")
+    }
+}
+
+/// A built-in prompt template and the variables it may reference
+#[derive(Debug)]
+pub struct PromptTemplate {
+    pub id: PromptTemplateId,
+    pub description: &'static str,
+    pub text: &'static str,
+    pub variables: &'static [&'static str],
+    /// Bumped whenever the built-in text changes in a way that affects output
+    pub version: u32,
+    /// The feature whose user profile opt-out applies; `None` for prompts that are never personalized
+    pub personalized: Option<PersonalizedFeature>,
+}
+
+const CARD_VARIABLES: &[&str] = &["life_area", "card_name", "card_question", "card_meaning", "life_area_insight"];
+
+pub static TEMPLATES: &[PromptTemplate] = &[
+    PromptTemplate {
+        id: PromptTemplateId::TitleGeneration,
+        description: "Generates a short title for a dream or bug. The entry content is appended after the template.",
+        text: TITLE_GENERATION_PROMPT,
+        variables: &[],
+        version: 1,
+        personalized: None,
+    },
+    PromptTemplate {
+        id: PromptTemplateId::DescriptionOptimization,
+        description: "Rewrites a raw description for clarity. The entry content is appended after the template.",
+        text: DESCRIPTION_OPTIMIZATION_PROMPT,
+        variables: &[],
+        version: 1,
+        personalized: None,
+    },
+    PromptTemplate {
+        id: PromptTemplateId::CardCommentary,
+        description: "Short commentary on a single drawn card, with the deck's insight for the life area.",
+        text: CARD_COMMENTARY_PROMPT,
+        variables: CARD_VARIABLES,
+        version: 2,
+        personalized: Some(PersonalizedFeature::CardCommentary),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::CardCommentaryWithContext,
+        description: "Commentary on a card, taking already selected cards into account.",
+        text: CARD_COMMENTARY_WITH_CONTEXT_PROMPT,
+        variables: &["life_area", "card_name", "card_question", "card_meaning", "life_area_insight", "selected_cards_list"],
+        version: 2,
+        personalized: Some(PersonalizedFeature::CardCommentary),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::MultipleCardsCommentary,
+        description: "Commentary on several cards at once, returned as a JSON object keyed by card id.",
+        text: MULTIPLE_CARDS_COMMENTARY_PROMPT,
+        variables: &["life_area", "cards_list"],
+        version: 1,
+        personalized: Some(PersonalizedFeature::CardCommentary),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::DiscoveryChat,
+        description: "System prompt for the card-guided discovery chat.",
+        text: DISCOVERY_CHAT_SYSTEM_PROMPT,
+        variables: &["life_area", "card_name", "card_question", "card_meaning", "selected_cards_context"],
+        version: 1,
+        personalized: Some(PersonalizedFeature::DiscoveryChat),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::DreamAnalysis,
+        description: "Dream analysis returning themes, emotions, narrative and symbol cards as JSON. The dream is appended after the template.",
+        text: DREAM_ANALYSIS_PROMPT,
+        variables: &["CARDS_JSON"],
+        version: 1,
+        personalized: Some(PersonalizedFeature::DreamAnalysis),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::CreativePrompts,
+        description: "Image, music and story prompts generated from a dream analysis. The analysis is appended after the template.",
+        text: CREATIVE_PROMPTS_GENERATION,
+        variables: &[],
+        version: 1,
+        personalized: None,
+    },
+    PromptTemplate {
+        id: PromptTemplateId::JournalChat,
+        description: "System prompt for \"ask my journal\" chat, with the retrieved entries and their [type:id] references.",
+        text: JOURNAL_CHAT_SYSTEM_PROMPT,
+        variables: &["journal_context"],
+        version: 1,
+        personalized: Some(PersonalizedFeature::JournalChat),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::PeriodDigest,
+        description: "Weekly, monthly or custom-range reflection over the period's statistics and entries.",
+        text: PERIOD_DIGEST_PROMPT,
+        variables: &["period_label", "date_range", "stats", "entries"],
+        version: 1,
+        personalized: Some(PersonalizedFeature::Digest),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::MindDumpAnalysis,
+        description: "Mind dump summary, worries, action items and suggested cards as JSON. The mind dump is appended after the template.",
+        text: MIND_DUMP_ANALYSIS_PROMPT,
+        variables: &["CARDS_JSON"],
+        version: 1,
+        personalized: Some(PersonalizedFeature::MindDumpAnalysis),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::BugRetrospective,
+        description: "Retrospective of a bug as JSON: root pattern, blocker pattern, what helped and follow-ups. The bug is appended after the template.",
+        text: BUG_RETROSPECTIVE_PROMPT,
+        variables: &["blocker_patterns"],
+        version: 1,
+        personalized: Some(PersonalizedFeature::BugRetrospective),
+    },
+    PromptTemplate {
+        id: PromptTemplateId::BugPatternClassification,
+        description: "Blocker patterns present in a bug, with confidence and evidence, as JSON. The bug is appended after the template.",
+        text: BUG_PATTERN_CLASSIFICATION_PROMPT,
+        variables: &["blocker_patterns"],
+        version: 1,
+        personalized: None,
+    },
+];
+
+/// Find every `{name}` placeholder in a template.
+/// Only identifier-like names count, so JSON examples such as `{"key": ...}` or
+/// `{{"1": ...}}` inside prompts are left alone.
+pub fn placeholders(text: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let name_len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+
+        if name_len > 0 && after[name_len..].starts_with('}') {
+            let name = &after[..name_len];
+            if !found.contains(&name) {
+                found.push(name);
+            }
+            rest = &after[name_len + 1..];
+        } else {
+            rest = after;
+        }
+    }
+
+    found
+}
+
+/// Fill the placeholders of `text` with `variables`.
+/// Fails if any placeholder is left without a value.
+pub fn render(text: &str, variables: &[(&str, &str)]) -> Result<String, String> {
+    let missing: Vec<&str> = placeholders(text)
+        .into_iter()
+        .filter(|name| !variables.iter().any(|(key, _)| key == name))
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(format!("Missing prompt variables: {}", missing.join(", ")));
+    }
+
+    // Single pass so values that happen to contain `{name}` are never substituted again
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            variables
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (end, *value))
+        });
+
+        match value {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+/// Check that a user-supplied template only references variables the template defines
+pub fn validate_template_text(id: PromptTemplateId, text: &str) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Err("Template text cannot be empty".to_string());
+    }
+
+    let allowed = id.definition().variables;
+    let unknown: Vec<&str> = placeholders(text)
+        .into_iter()
+        .filter(|name| !allowed.contains(name))
+        .collect();
+
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown variables for template '{}': {} (allowed: {})",
+            id.as_str(),
+            unknown.join(", "),
+            if allowed.is_empty() { "none".to_string() } else { allowed.join(", ") }
+        ));
+    }
+
+    Ok(())
+}
+
+/// Built-in templates merged with the user's overrides
+#[derive(Debug, Clone, Default)]
+pub struct PromptRegistry {
+    overrides: HashMap<PromptTemplateId, String>,
+    profile: UserProfile,
+}
+
+impl PromptRegistry {
+    pub fn new(overrides: HashMap<PromptTemplateId, String>) -> Self {
+        PromptRegistry { overrides, profile: UserProfile::default() }
+    }
+
+    /// Add the user profile to personalized prompts, except for features the user opted out of
+    pub fn with_profile(mut self, profile: UserProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// The active text for a template: the override if there is one, otherwise the default
+    pub fn text(&self, id: PromptTemplateId) -> &str {
+        self.overrides
+            .get(&id)
+            .map(|s| s.as_str())
+            .unwrap_or(id.definition().text)
+    }
+
+    pub fn is_overridden(&self, id: PromptTemplateId) -> bool {
+        self.overrides.contains_key(&id)
+    }
+
+    /// Identifies the exact template text in use, e.g. `dream_analysis@v1`.
+    /// Overrides get a content hash so editing one makes earlier output outdated.
+    pub fn version(&self, id: PromptTemplateId) -> String {
+        match self.overrides.get(&id) {
+            Some(text) => format!("{}@custom-{:016x}", id.as_str(), fnv1a(text.as_bytes())),
+            None => format!("{}@v{}", id.as_str(), id.definition().version),
+        }
+    }
+
+    /// The template with its variables filled in, followed by the user profile
+    /// when the template is personalized
+    pub fn render(&self, id: PromptTemplateId, variables: &[(&str, &str)]) -> Result<String, String> {
+        let mut text = render(self.text(id), variables)
+            .map_err(|e| format!("Failed to render '{}' prompt: {}", id.as_str(), e))?;
+
+        let profile_context = id
+            .definition()
+            .personalized
+            .and_then(|feature| self.profile.prompt_context(feature));
+        if let Some(context) = profile_context {
+            text.push_str("\n\n");
+            text.push_str(&context);
+        }
+
+        Ok(text)
+    }
+
+    pub fn info(&self, id: PromptTemplateId) -> PromptTemplateInfo {
+        let definition = id.definition();
+        PromptTemplateInfo {
+            id,
+            description: definition.description.to_string(),
+            variables: definition.variables.iter().map(|v| v.to_string()).collect(),
+            default_text: definition.text.to_string(),
+            override_text: self.overrides.get(&id).cloned(),
+            personalized: definition.personalized,
+        }
+    }
+}
+
+/// Template details returned to the settings page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateInfo {
+    pub id: PromptTemplateId,
+    pub description: String,
+    pub variables: Vec<String>,
+    pub default_text: String,
+    pub override_text: Option<String>,
+    pub personalized: Option<PersonalizedFeature>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_ignore_json_examples() {
+        for template in TEMPLATES {
+            let mut found = placeholders(template.text);
+            let mut expected = template.variables.to_vec();
+            found.sort();
+            expected.sort();
+            assert_eq!(found, expected, "placeholders of {}", template.id.as_str());
+        }
+    }
+
+    #[test]
+    fn test_render_fills_all_variables() {
+        let rendered = render(
+            "Card {card_name} in {life_area}, again {card_name}",
+            &[("card_name", "Delivery Driver"), ("life_area", "work")],
+        )
+        .unwrap();
+
+        assert_eq!(rendered, "Card Delivery Driver in work, again Delivery Driver");
+    }
+
+    #[test]
+    fn test_render_reports_missing_variables() {
+        let err = render("{life_area} and {card_name}", &[("life_area", "work")]).unwrap_err();
+        assert!(err.contains("card_name"));
+    }
+
+    #[test]
+    fn test_render_does_not_substitute_inside_values() {
+        let rendered = render(
+            "{a} {b}",
+            &[("a", "{b}"), ("b", "x")],
+        )
+        .unwrap();
+
+        assert_eq!(rendered, "{b} x");
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_variables() {
+        assert!(validate_template_text(PromptTemplateId::CardCommentary, "About {card_name}").is_ok());
+        assert!(validate_template_text(PromptTemplateId::CardCommentary, "About {dream}").is_err());
+        assert!(validate_template_text(PromptTemplateId::CardCommentary, "   ").is_err());
+    }
+
+    #[test]
+    fn test_registry_prefers_override() {
+        let mut overrides = HashMap::new();
+        overrides.insert(PromptTemplateId::CardCommentary, "Custom {card_name}".to_string());
+        let registry = PromptRegistry::new(overrides);
+
+        assert!(registry.is_overridden(PromptTemplateId::CardCommentary));
+        assert_eq!(
+            registry.render(PromptTemplateId::CardCommentary, &[("card_name", "Fox")]).unwrap(),
+            "Custom Fox"
+        );
+        assert_eq!(
+            registry.text(PromptTemplateId::TitleGeneration),
+            TITLE_GENERATION_PROMPT
+        );
+    }
+
+    #[test]
+    fn test_registry_adds_profile_to_personalized_prompts() {
+        let registry = PromptRegistry::default().with_profile(UserProfile {
+            name: "Sam".to_string(),
+            personalization_opt_out: vec![PersonalizedFeature::Digest],
+            ..Default::default()
+        });
+        let render = |id: PromptTemplateId| {
+            let variables: Vec<(&str, &str)> = id.definition().variables.iter().map(|name| (*name, "")).collect();
+            registry.render(id, &variables).unwrap()
+        };
+
+        assert!(render(PromptTemplateId::CardCommentary).ends_with("\n\nUser Profile:\n- Name: Sam"));
+        assert!(!render(PromptTemplateId::TitleGeneration).contains("User Profile"));
+        assert!(!render(PromptTemplateId::PeriodDigest).contains("User Profile"));
+    }
+
+    #[test]
+    fn test_registry_version_tracks_overrides() {
+        let default = PromptRegistry::default();
+        assert_eq!(default.version(PromptTemplateId::DreamAnalysis), "dream_analysis@v1");
+
+        let custom = |text: &str| {
+            let mut overrides = HashMap::new();
+            overrides.insert(PromptTemplateId::DreamAnalysis, text.to_string());
+            PromptRegistry::new(overrides).version(PromptTemplateId::DreamAnalysis)
+        };
+
+        assert!(custom("Analyse {CARDS_JSON}").starts_with("dream_analysis@custom-"));
+        assert_eq!(custom("Analyse {CARDS_JSON}"), custom("Analyse {CARDS_JSON}"));
+        assert_ne!(custom("Analyse {CARDS_JSON}"), custom("Interpret {CARDS_JSON}"));
+    }
+}
@@ -1,4 +1,11 @@
+use super::error::LlmError;
+use crate::db::models::EntryType;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com";
+pub const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -7,6 +14,20 @@ pub enum LLMProvider {
     Ollama,
     OpenAI,
     Anthropic,
+    /// Scripted responses from `LLMConfig::mock_responses`, no network
+    Mock,
+}
+
+impl LLMProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LLMProvider::Disabled => "disabled",
+            LLMProvider::Ollama => "ollama",
+            LLMProvider::OpenAI => "openai",
+            LLMProvider::Anthropic => "anthropic",
+            LLMProvider::Mock => "mock",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,12 +45,215 @@ pub struct LLMConfig {
     pub anthropic_api_key: String,
     #[serde(rename = "anthropicModel")]
     pub anthropic_model: String,
+    /// Without trailing slash; point at a proxy or a local stub
+    #[serde(rename = "openaiBaseUrl", default = "default_openai_base_url")]
+    pub openai_base_url: String,
+    #[serde(rename = "anthropicBaseUrl", default = "default_anthropic_base_url")]
+    pub anthropic_base_url: String,
+    /// Response text per operation name (`title`, `chat`, ...) or `default`, for the mock provider
+    #[serde(rename = "mockResponses", default)]
+    pub mock_responses: HashMap<String, String>,
+    #[serde(default)]
+    pub network: NetworkSettings,
+    #[serde(default)]
+    pub cache: CacheSettings,
+    #[serde(default)]
+    pub fixtures: FixtureSettings,
+    #[serde(default)]
+    pub embeddings: EmbeddingSettings,
+}
+
+fn default_openai_base_url() -> String {
+    DEFAULT_OPENAI_BASE_URL.to_string()
+}
+
+fn default_anthropic_base_url() -> String {
+    DEFAULT_ANTHROPIC_BASE_URL.to_string()
+}
+
+/// Opt-in response cache for titles and card commentary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CacheSettings {
+    pub enabled: bool,
+    pub ttl_hours: u64,
+    pub max_entries: usize,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        CacheSettings {
+            enabled: false,
+            ttl_hours: 24 * 7,
+            max_entries: 500,
+        }
+    }
+}
+
+/// Vectors for semantic search, computed by the configured provider (Ollama or OpenAI)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EmbeddingSettings {
+    pub enabled: bool,
+    pub ollama_model: String,
+    pub openai_model: String,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        EmbeddingSettings {
+            enabled: false,
+            ollama_model: "nomic-embed-text".to_string(),
+            openai_model: "text-embedding-3-small".to_string(),
+        }
+    }
+}
+
+/// Whether provider calls are written to, or served from, JSON fixtures
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureMode {
+    #[default]
+    Off,
+    Record,
+    Replay,
+}
+
+/// Request/response fixtures for debugging and reproducing reported issues
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FixtureSettings {
+    pub mode: FixtureMode,
+    /// Empty means `llm_fixtures` in the app data directory
+    pub dir: String,
+}
+
+impl Default for LLMConfig {
+    fn default() -> Self {
+        LLMConfig {
+            provider: LLMProvider::Disabled,
+            ollama_url: "http://localhost:11434".to_string(),
+            ollama_model: "llama".to_string(),
+            openai_api_key: String::new(),
+            openai_model: "gpt4-mini".to_string(),
+            anthropic_api_key: String::new(),
+            anthropic_model: "claude-haiku".to_string(),
+            openai_base_url: default_openai_base_url(),
+            anthropic_base_url: default_anthropic_base_url(),
+            mock_responses: HashMap::new(),
+            network: NetworkSettings::default(),
+            cache: CacheSettings::default(),
+            fixtures: FixtureSettings::default(),
+            embeddings: EmbeddingSettings::default(),
+        }
+    }
+}
+
+/// Stored LLM settings as shown to the frontend. API keys are never sent back,
+/// only whether one is saved.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmSettingsView {
+    #[serde(flatten)]
+    pub config: LLMConfig,
+    pub has_openai_api_key: bool,
+    pub has_anthropic_api_key: bool,
+    /// `keyring` or `encrypted_file`
+    pub secrets_backend: String,
+}
+
+/// Connection settings shared by all LLM requests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+    /// e.g. `http://proxy.corp:3128`; applies to every provider
+    pub proxy_url: Option<String>,
+    /// PEM or DER certificate to trust in addition to the system roots
+    pub ca_cert_path: Option<String>,
+    pub ollama: ProviderTimeouts,
+    pub openai: ProviderTimeouts,
+    pub anthropic: ProviderTimeouts,
+    pub operation_timeouts: OperationTimeouts,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderTimeouts {
+    pub connect_secs: u64,
+    /// Longest wait between two chunks of the response
+    pub read_secs: u64,
+}
+
+impl Default for ProviderTimeouts {
+    fn default() -> Self {
+        ProviderTimeouts {
+            connect_secs: 10,
+            read_secs: 120,
+        }
+    }
+}
+
+/// Total time allowed for one request, per kind of operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OperationTimeouts {
+    pub title_secs: u64,
+    pub description_secs: u64,
+    pub card_commentary_secs: u64,
+    pub chat_secs: u64,
+    pub dream_analysis_secs: u64,
+    pub creative_prompts_secs: u64,
+    /// Connection tests and model listing from the settings page
+    pub connection_test_secs: u64,
+    pub embedding_secs: u64,
+    pub digest_secs: u64,
+    pub mind_dump_analysis_secs: u64,
+    pub bug_retrospective_secs: u64,
+    pub pattern_classification_secs: u64,
+}
+
+impl Default for OperationTimeouts {
+    fn default() -> Self {
+        OperationTimeouts {
+            title_secs: 30,
+            description_secs: 90,
+            card_commentary_secs: 60,
+            chat_secs: 90,
+            dream_analysis_secs: 180,
+            creative_prompts_secs: 180,
+            connection_test_secs: 15,
+            embedding_secs: 30,
+            digest_secs: 180,
+            mind_dump_analysis_secs: 180,
+            bug_retrospective_secs: 180,
+            pattern_classification_secs: 90,
+        }
+    }
+}
+
+/// Outcome of `test_llm_connection`; failures are reported here rather than as an error
+#[derive(Debug, Serialize)]
+pub struct ConnectionTestResult {
+    pub ok: bool,
+    pub provider: String,
+    pub model: Option<String>,
+    pub latency_ms: u64,
+    pub error: Option<LlmError>,
+}
+
+/// A model the configured provider can serve
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AvailableModel {
+    pub id: String,
+    pub display_name: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GenerateTitleRequest {
     pub content: String,
-    pub config: LLMConfig,
+    /// Falls back to the stored settings when omitted
+    #[serde(default)]
+    pub config: Option<LLMConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -40,7 +264,9 @@ pub struct GenerateTitleResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OptimizeDescriptionRequest {
     pub content: String,
-    pub config: LLMConfig,
+    /// Falls back to the stored settings when omitted
+    #[serde(default)]
+    pub config: Option<LLMConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,7 +285,12 @@ pub struct GenerateDreamAnalysisRequest {
     pub dream_title: String,
     pub dream_content: String,
     pub sleep_quality: Option<i32>,
-    pub config: LLMConfig,
+    /// Include the dream's emotions, lucidity and recurrence, and related earlier dreams with their cards
+    #[serde(default)]
+    pub with_history: bool,
+    /// Falls back to the stored settings when omitted
+    #[serde(default)]
+    pub config: Option<LLMConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -76,13 +307,43 @@ pub struct GenerateDreamAnalysisResponse {
     pub symbol_cards: Vec<SymbolCard>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MindDumpAnalysisResponse {
+    pub summary: String,
+    pub worries: Vec<String>,
+    pub action_items: Vec<String>,
+    pub suggested_cards: Vec<SymbolCard>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BugRetrospectiveResponse {
+    pub root_pattern: String,
+    pub blocker_pattern: String,
+    pub what_helped: Vec<String>,
+    pub follow_ups: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetectedPattern {
+    pub pattern: String,
+    pub confidence: f64,
+    pub evidence: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BugPatternClassificationResponse {
+    pub patterns: Vec<DetectedPattern>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GenerateCreativePromptsRequest {
     pub dream_analysis_id: i64,
     pub themes_patterns: String,
     pub emotional_analysis: String,
     pub narrative_summary: String,
-    pub config: LLMConfig,
+    /// Falls back to the stored settings when omitted
+    #[serde(default)]
+    pub config: Option<LLMConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -91,3 +352,21 @@ pub struct GenerateCreativePromptsResponse {
     pub music_prompts: Vec<String>,
     pub story_prompts: Vec<String>,
 }
+
+/// A journal entry given to the model as context for an "ask my journal" answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalSource {
+    pub entity_type: EntryType,
+    pub entity_id: i64,
+    pub title: String,
+    pub date: DateTime<Utc>,
+    /// Whether the answer references this entry
+    pub cited: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AskJournalResponse {
+    pub answer: String,
+    /// Every retrieved entry, cited ones first
+    pub sources: Vec<JournalSource>,
+}
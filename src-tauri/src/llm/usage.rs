@@ -0,0 +1,221 @@
+use super::http::{LlmHttp, Operation};
+use crate::db::models::{CreateLlmUsageInput, LlmUsageSummary, LlmUsageTotals};
+use crate::db::Database;
+use serde_json::Value;
+use std::ops::Deref;
+
+/// USD per million (input, output) tokens. An entry also prices dated or versioned ids
+/// such as `gpt-4o-2024-08-06`, but not other variants like `o3-pro`, which need their
+/// own entry. Local Ollama models cost nothing.
+const PRICES_PER_MILLION: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("gpt-5-nano", 0.05, 0.40),
+    ("gpt-5-mini", 0.25, 2.00),
+    ("gpt-5", 1.25, 10.00),
+    ("o3-mini", 1.10, 4.40),
+    ("o4-mini", 1.10, 4.40),
+    ("o3-pro", 20.00, 80.00),
+    ("o3", 2.00, 8.00),
+    ("o1-mini", 1.10, 4.40),
+    ("o1-pro", 150.00, 600.00),
+    ("o1", 15.00, 60.00),
+    ("claude-haiku-4-5", 1.00, 5.00),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-sonnet-4", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-opus-4", 15.00, 75.00),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("text-embedding-ada-002", 0.10, 0.0),
+];
+
+/// Token counts reported in a provider response body.
+/// Missing fields count as zero; the call is still recorded.
+pub fn from_response(provider: &str, model: &str, operation: Operation, data: &Value) -> CreateLlmUsageInput {
+    let count = |value: Option<&Value>| value.and_then(|v| v.as_i64()).unwrap_or(0);
+
+    let (prompt_tokens, completion_tokens) = match provider {
+        "Ollama" => (count(data.get("prompt_eval_count")), count(data.get("eval_count"))),
+        "Anthropic" => {
+            let usage = data.get("usage");
+            (
+                count(usage.and_then(|u| u.get("input_tokens"))),
+                count(usage.and_then(|u| u.get("output_tokens"))),
+            )
+        }
+        _ => {
+            let usage = data.get("usage");
+            (
+                count(usage.and_then(|u| u.get("prompt_tokens"))),
+                count(usage.and_then(|u| u.get("completion_tokens"))),
+            )
+        }
+    };
+
+    CreateLlmUsageInput {
+        provider: provider.to_lowercase(),
+        model: model.to_string(),
+        operation: operation.as_str().to_string(),
+        prompt_tokens,
+        completion_tokens,
+    }
+}
+
+/// Write usage recorded by `http` to the `llm_usage` table
+pub fn flush(db: &Database, http: &LlmHttp) {
+    for usage in http.take_usage() {
+        if let Err(e) = db.record_llm_usage(&usage) {
+            eprintln!("Failed to record LLM usage: {}", e);
+        }
+    }
+}
+
+/// A command's own view of the shared HTTP clients. Pass it wherever an `LlmHttp`
+/// is expected; it flushes the command's usage when dropped, so tokens spent are
+/// recorded even when the command fails part-way.
+pub struct UsageRecorder<'a> {
+    db: &'a Database,
+    http: LlmHttp,
+}
+
+impl<'a> UsageRecorder<'a> {
+    pub fn new(db: &'a Database, http: &LlmHttp) -> Self {
+        UsageRecorder { db, http: http.fork() }
+    }
+}
+
+impl Deref for UsageRecorder<'_> {
+    type Target = LlmHttp;
+
+    fn deref(&self) -> &LlmHttp {
+        &self.http
+    }
+}
+
+impl Drop for UsageRecorder<'_> {
+    fn drop(&mut self) {
+        flush(self.db, &self.http);
+    }
+}
+
+/// Estimated cost in USD, or None for a model without a known price
+pub fn estimate_cost(provider: &str, model: &str, prompt_tokens: i64, completion_tokens: i64) -> Option<f64> {
+    if provider == "ollama" {
+        return Some(0.0);
+    }
+
+    let (_, input, output) = PRICES_PER_MILLION
+        .iter()
+        .filter(|(name, _, _)| is_release_of(model, name))
+        .max_by_key(|(name, _, _)| name.len())?;
+
+    Some((prompt_tokens as f64 * input + completion_tokens as f64 * output) / 1_000_000.0)
+}
+
+/// `model` is `name` itself, its `-latest` alias, or a release of it tagged with a date or version number
+fn is_release_of(model: &str, name: &str) -> bool {
+    match model.strip_prefix(name) {
+        Some("") | Some("-latest") => true,
+        Some(rest) => rest.strip_prefix('-').is_some_and(|tag| tag.starts_with(|c: char| c.is_ascii_digit())),
+        None => false,
+    }
+}
+
+/// Merge per-model totals into one summary per key, pricing each model separately
+pub fn summarize(totals: Vec<LlmUsageTotals>) -> Vec<LlmUsageSummary> {
+    let mut summaries: Vec<LlmUsageSummary> = Vec::new();
+
+    for row in totals {
+        let cost = estimate_cost(&row.provider, &row.model, row.prompt_tokens, row.completion_tokens);
+
+        match summaries.last_mut().filter(|s| s.key == row.key) {
+            Some(summary) => {
+                summary.calls += row.calls;
+                summary.prompt_tokens += row.prompt_tokens;
+                summary.completion_tokens += row.completion_tokens;
+                summary.estimated_cost_usd = summary.estimated_cost_usd.zip(cost).map(|(a, b)| a + b);
+            }
+            None => summaries.push(LlmUsageSummary {
+                key: row.key,
+                calls: row.calls,
+                prompt_tokens: row.prompt_tokens,
+                completion_tokens: row.completion_tokens,
+                estimated_cost_usd: cost,
+            }),
+        }
+    }
+
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reads_each_provider_usage_format() {
+        let ollama = from_response("Ollama", "llama3.2", Operation::Chat, &json!({"prompt_eval_count": 12, "eval_count": 34}));
+        assert_eq!((ollama.prompt_tokens, ollama.completion_tokens), (12, 34));
+        assert_eq!((ollama.provider.as_str(), ollama.operation.as_str()), ("ollama", "chat"));
+
+        let openai = from_response("OpenAI", "gpt-4o", Operation::Title, &json!({"usage": {"prompt_tokens": 5, "completion_tokens": 6}}));
+        assert_eq!((openai.prompt_tokens, openai.completion_tokens), (5, 6));
+
+        let anthropic = from_response("Anthropic", "claude-haiku-4-5", Operation::DreamAnalysis, &json!({"usage": {"input_tokens": 7, "output_tokens": 8}}));
+        assert_eq!((anthropic.prompt_tokens, anthropic.completion_tokens), (7, 8));
+
+        let missing = from_response("OpenAI", "gpt-4o", Operation::Title, &json!({}));
+        assert_eq!((missing.prompt_tokens, missing.completion_tokens), (0, 0));
+    }
+
+    #[test]
+    fn test_estimates_cost_by_longest_prefix() {
+        assert_eq!(estimate_cost("openai", "gpt-4o-mini", 1_000_000, 0), Some(0.15));
+        assert_eq!(estimate_cost("openai", "gpt-4o-2024-08-06", 0, 1_000_000), Some(10.0));
+        assert_eq!(estimate_cost("ollama", "llama3.2", 5_000, 5_000), Some(0.0));
+        assert_eq!(estimate_cost("openai", "some-new-model", 1, 1), None);
+    }
+
+    #[test]
+    fn test_variants_are_not_priced_as_their_base_model() {
+        assert_eq!(estimate_cost("openai", "o1-mini", 1_000_000, 0), Some(1.10));
+        assert_eq!(estimate_cost("openai", "o1-2024-12-17", 1_000_000, 0), Some(15.0));
+        assert_eq!(estimate_cost("openai", "o3-pro", 1_000_000, 0), Some(20.0));
+        assert_eq!(estimate_cost("anthropic", "claude-3-5-haiku-latest", 1_000_000, 0), Some(0.80));
+        assert_eq!(estimate_cost("openai", "o3-deep-research", 1, 1), None);
+        assert_eq!(estimate_cost("openai", "gpt-4o-audio-preview", 1, 1), None);
+    }
+
+    #[test]
+    fn test_summarize_merges_models_per_key() {
+        let row = |key: &str, provider: &str, model: &str, tokens: i64| LlmUsageTotals {
+            key: key.to_string(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            calls: 1,
+            prompt_tokens: tokens,
+            completion_tokens: 0,
+        };
+
+        let summaries = summarize(vec![
+            row("chat", "openai", "gpt-4o", 1_000_000),
+            row("chat", "ollama", "llama3.2", 500),
+            row("title", "openai", "mystery", 10),
+        ]);
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].calls, 2);
+        assert_eq!(summaries[0].prompt_tokens, 1_000_500);
+        assert_eq!(summaries[0].estimated_cost_usd, Some(2.5));
+        assert_eq!(summaries[1].estimated_cost_usd, None);
+    }
+}
@@ -0,0 +1,292 @@
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Keyring service name the credentials are filed under
+const SERVICE: &str = "limnl-journal";
+
+/// Set to `file` to skip the OS keyring, e.g. on headless Linux test machines
+const BACKEND_ENV: &str = "LIMNL_SECRETS_BACKEND";
+
+const NONCE_LEN: usize = 12;
+
+/// Somewhere to keep credentials such as API keys outside the database
+pub trait SecretBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn get(&self, key: &str) -> Result<Option<String>, String>;
+    fn set(&self, key: &str, value: &str) -> Result<(), String>;
+    fn delete(&self, key: &str) -> Result<bool, String>;
+}
+
+/// Secret storage managed as Tauri state.
+/// Uses the OS keyring when one is reachable, otherwise an encrypted file in the app data dir.
+pub struct Secrets {
+    backend: Box<dyn SecretBackend>,
+}
+
+impl Secrets {
+    pub fn new(data_dir: &Path) -> Self {
+        let force_file = std::env::var(BACKEND_ENV).is_ok_and(|v| v.eq_ignore_ascii_case("file"));
+
+        if !force_file && KeyringBackend::is_available() {
+            return Secrets::with_backend(Box::new(KeyringBackend));
+        }
+
+        Secrets::with_backend(Box::new(EncryptedFileBackend::new(data_dir)))
+    }
+
+    pub fn with_backend(backend: Box<dyn SecretBackend>) -> Self {
+        Secrets { backend }
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>, String> {
+        self.backend.get(key)
+    }
+
+    pub fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        self.backend.set(key, value)
+    }
+
+    pub fn delete(&self, key: &str) -> Result<bool, String> {
+        self.backend.delete(key)
+    }
+}
+
+/// macOS Keychain, Windows Credential Manager or the Secret Service on Linux
+pub struct KeyringBackend;
+
+impl KeyringBackend {
+    fn entry(key: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, key).map_err(|e| format!("Keyring unavailable: {}", e))
+    }
+
+    /// Round-trip a throwaway entry; fails when no keyring daemon is running
+    fn is_available() -> bool {
+        let probe = || -> Result<(), keyring::Error> {
+            let entry = keyring::Entry::new(SERVICE, "availability_probe")?;
+            entry.set_password("ok")?;
+            entry.get_password()?;
+            entry.delete_credential()
+        };
+        probe().is_ok()
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        match Self::entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read '{}' from keyring: {}", key, e)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        Self::entry(key)?
+            .set_password(value)
+            .map_err(|e| format!("Failed to store '{}' in keyring: {}", key, e))
+    }
+
+    fn delete(&self, key: &str) -> Result<bool, String> {
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(format!("Failed to delete '{}' from keyring: {}", key, e)),
+        }
+    }
+}
+
+/// `secrets.json` maps each key to a hex-encoded nonce and ChaCha20-Poly1305 ciphertext.
+/// The encryption key sits next to it in `secrets.key`, readable only by the user,
+/// so a copied or backed-up secrets file alone does not reveal the API keys.
+pub struct EncryptedFileBackend {
+    path: PathBuf,
+    key_path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl EncryptedFileBackend {
+    pub fn new(dir: &Path) -> Self {
+        EncryptedFileBackend {
+            path: dir.join("secrets.json"),
+            key_path: dir.join("secrets.key"),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn cipher(&self) -> Result<ChaCha20Poly1305, String> {
+        let key = match std::fs::read(&self.key_path) {
+            Ok(bytes) => match <[u8; 32]>::try_from(bytes.as_slice()) {
+                Ok(arr) => Key::from(arr),
+                Err(_) => return Err(format!("Corrupt secrets key at {}", self.key_path.display())),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+                write_private(&self.key_path, &key[..])?;
+                key
+            }
+            Err(e) => return Err(format!("Failed to read secrets key: {}", e)),
+        };
+
+        Ok(ChaCha20Poly1305::new(&key))
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Corrupt secrets file: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(format!("Failed to read secrets file: {}", e)),
+        }
+    }
+
+    fn save(&self, entries: &BTreeMap<String, String>) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+        write_private(&self.path, text.as_bytes())
+    }
+}
+
+impl SecretBackend for EncryptedFileBackend {
+    fn name(&self) -> &'static str {
+        "encrypted_file"
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        let _guard = self.lock.lock().unwrap();
+
+        let Some(encoded) = self.load()?.remove(key) else {
+            return Ok(None);
+        };
+
+        let bytes = decode_hex(&encoded).ok_or_else(|| format!("Corrupt secret '{}'", key))?;
+        if bytes.len() < NONCE_LEN {
+            return Err(format!("Corrupt secret '{}'", key));
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let nonce = <[u8; NONCE_LEN]>::try_from(nonce).map_err(|_| format!("Corrupt secret '{}'", key))?;
+
+        let plaintext = self
+            .cipher()?
+            .decrypt(&Nonce::from(nonce), ciphertext)
+            .map_err(|_| format!("Failed to decrypt secret '{}'", key))?;
+
+        String::from_utf8(plaintext)
+            .map(Some)
+            .map_err(|_| format!("Corrupt secret '{}'", key))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().unwrap();
+
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(&nonce, value.as_bytes())
+            .map_err(|_| format!("Failed to encrypt secret '{}'", key))?;
+
+        let mut bytes = nonce.to_vec();
+        bytes.extend_from_slice(&ciphertext);
+
+        let mut entries = self.load()?;
+        entries.insert(key.to_string(), encode_hex(&bytes));
+        self.save(&entries)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool, String> {
+        let _guard = self.lock.lock().unwrap();
+
+        let mut entries = self.load()?;
+        if entries.remove(key).is_none() {
+            return Ok(false);
+        }
+        self.save(&entries)?;
+        Ok(true)
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    use std::io::Write;
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("limnl-secrets-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let dir = temp_dir("round-trip");
+        let secrets = Secrets::with_backend(Box::new(EncryptedFileBackend::new(&dir)));
+
+        assert_eq!(secrets.get("openai_api_key").unwrap(), None);
+
+        secrets.set("openai_api_key", "sk-first").unwrap();
+        secrets.set("openai_api_key", "sk-second").unwrap();
+        secrets.set("anthropic_api_key", "sk-ant").unwrap();
+
+        // A fresh instance reads the same file and key
+        let reopened = EncryptedFileBackend::new(&dir);
+        assert_eq!(reopened.get("openai_api_key").unwrap().as_deref(), Some("sk-second"));
+
+        let on_disk = std::fs::read_to_string(dir.join("secrets.json")).unwrap();
+        assert!(!on_disk.contains("sk-second"));
+
+        assert!(secrets.delete("openai_api_key").unwrap());
+        assert!(!secrets.delete("openai_api_key").unwrap());
+        assert_eq!(secrets.get("anthropic_api_key").unwrap().as_deref(), Some("sk-ant"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = vec![0, 1, 127, 128, 255];
+        assert_eq!(decode_hex(&encode_hex(&bytes)), Some(bytes));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+}
@@ -1,17 +1,28 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Bug, CreateBugInput, UpdateBugInput } from '$lib/types/bug';
+import type {
+	Bug,
+	BugFromEntry,
+	BugPattern,
+	BugRetrospective,
+	CreateBugInput,
+	PatternFrequency,
+	UpdateBugInput
+} from '$lib/types/bug';
+import type { EntryType } from '$lib/types/llm';
+import type { WithLinks } from '$lib/types/entry-link';
+import { isLlmError, LlmRequestError } from '$lib/api/llm';
 
 export const bugsApi = {
 	async create(input: CreateBugInput): Promise<Bug> {
 		return await invoke<Bug>('create_bug', { input });
 	},
 
-	async get(id: number): Promise<Bug | null> {
-		return await invoke<Bug | null>('get_bug', { id });
+	async get(id: number): Promise<WithLinks<Bug> | null> {
+		return await invoke<WithLinks<Bug> | null>('get_bug', { id });
 	},
 
-	async list(status?: string): Promise<Bug[]> {
-		return await invoke<Bug[]>('list_bugs', { status });
+	async list(status?: string, lifeAreaId?: number): Promise<Bug[]> {
+		return await invoke<Bug[]>('list_bugs', { status, lifeAreaId });
 	},
 
 	async update(input: UpdateBugInput): Promise<Bug | null> {
@@ -20,5 +31,68 @@ export const bugsApi = {
 
 	async delete(id: number): Promise<boolean> {
 		return await invoke<boolean>('delete_bug', { id });
+	},
+
+	// Generates the title and description from the entry and links the bug back to it
+	async createFromEntry(
+		entryType: EntryType,
+		entryId: number,
+		drawCards = false
+	): Promise<BugFromEntry> {
+		try {
+			return await invoke<BugFromEntry>('create_bug_from_entry', {
+				entryType,
+				entryId,
+				drawCards
+			});
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Failed to create bug from entry');
+		}
+	},
+
+	// Replaces any earlier retrospective of the bug
+	async generateRetrospective(bugId: number): Promise<BugRetrospective> {
+		try {
+			return await invoke<BugRetrospective>('generate_bug_retrospective', {
+				bugId
+			});
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Failed to generate retrospective');
+		}
+	},
+
+	async getRetrospective(bugId: number): Promise<BugRetrospective | null> {
+		return await invoke<BugRetrospective | null>('get_bug_retrospective', { bugId });
+	},
+
+	// Replaces any earlier classification of the bug
+	async classifyPatterns(bugId: number): Promise<BugPattern[]> {
+		try {
+			return await invoke<BugPattern[]>('classify_bug_patterns', {
+				bugId
+			});
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Failed to classify bug patterns');
+		}
+	},
+
+	async getPatterns(bugId: number): Promise<BugPattern[]> {
+		return await invoke<BugPattern[]>('get_bug_patterns', { bugId });
+	},
+
+	// Counts patterns with at least `minConfidence` (backend default 0.5)
+	async getPatternFrequencies(minConfidence?: number): Promise<PatternFrequency[]> {
+		return await invoke<PatternFrequency[]>('get_pattern_frequencies', {
+			minConfidence: minConfidence ?? null
+		});
 	}
 };
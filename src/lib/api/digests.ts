@@ -0,0 +1,33 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { Digest, DigestPeriod } from '$lib/types/digest';
+import { isLlmError, LlmRequestError } from '$lib/api/llm';
+
+export const digestsApi = {
+	// Week and month digests run from startDate; custom ones need endDate (exclusive)
+	async generate(periodType: DigestPeriod, startDate: string, endDate?: string): Promise<Digest> {
+		try {
+			return await invoke<Digest>('generate_period_digest', {
+				periodType,
+				startDate,
+				endDate: endDate ?? null
+			});
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Failed to generate digest');
+		}
+	},
+
+	async list(periodType?: DigestPeriod): Promise<Digest[]> {
+		return await invoke<Digest[]>('list_digests', { periodType: periodType ?? null });
+	},
+
+	async get(id: number): Promise<Digest | null> {
+		return await invoke<Digest | null>('get_digest', { id });
+	},
+
+	async delete(id: number): Promise<boolean> {
+		return await invoke<boolean>('delete_digest', { id });
+	}
+};
@@ -7,16 +7,28 @@ import type {
 	GenerateDreamAnalysisRequest,
 	DreamCreativePrompts,
 	DreamCreativePromptsData,
-	GenerateCreativePromptsRequest
+	GenerateCreativePromptsRequest,
+	SimilarDreamsResult
 } from '$lib/types/dream';
+import type { WithLinks } from '$lib/types/entry-link';
+import { isLlmError, LlmRequestError } from '$lib/api/llm';
+
+// Turn structured LLM errors from the backend into Error instances the pages can display
+async function withLlmErrors<T>(call: Promise<T>): Promise<T> {
+	try {
+		return await call;
+	} catch (error) {
+		throw isLlmError(error) ? new LlmRequestError(error) : error;
+	}
+}
 
 export const dreamsApi = {
 	async create(input: CreateDreamInput): Promise<Dream> {
 		return await invoke<Dream>('create_dream', { input });
 	},
 
-	async get(id: number): Promise<Dream | null> {
-		return await invoke<Dream | null>('get_dream', { id });
+	async get(id: number): Promise<WithLinks<Dream> | null> {
+		return await invoke<WithLinks<Dream> | null>('get_dream', { id });
 	},
 
 	async list(limit?: number, offset?: number): Promise<Dream[]> {
@@ -37,7 +49,7 @@ export const dreamsApi = {
 
 	// Dream Analysis
 	async generateAnalysis(request: GenerateDreamAnalysisRequest): Promise<DreamAnalysisWithCards> {
-		return await invoke<DreamAnalysisWithCards>('generate_dream_analysis', { request });
+		return withLlmErrors(invoke<DreamAnalysisWithCards>('generate_dream_analysis', { request }));
 	},
 
 	async getAnalysisWithCards(dreamId: number): Promise<DreamAnalysisWithCards | null> {
@@ -46,9 +58,37 @@ export const dreamsApi = {
 		});
 	},
 
+	// withHistory adds the dream's metadata and related earlier dreams to the prompt
+	async regenerateAnalysis(dreamId: number, withHistory = false): Promise<DreamAnalysisWithCards> {
+		return withLlmErrors(invoke<DreamAnalysisWithCards>('regenerate_dream_analysis', { dreamId, withHistory }));
+	},
+
+	async listAnalysisVersions(dreamId: number): Promise<DreamAnalysisWithCards[]> {
+		return await invoke<DreamAnalysisWithCards[]>('list_dream_analysis_versions', { dreamId });
+	},
+
+	async pinAnalysis(analysisId: number): Promise<boolean> {
+		return await invoke<boolean>('pin_dream_analysis', { analysisId });
+	},
+
+	async unpinAnalysis(dreamId: number): Promise<boolean> {
+		return await invoke<boolean>('unpin_dream_analysis', { dreamId });
+	},
+
+	async deleteAnalysisVersion(analysisId: number): Promise<boolean> {
+		return await invoke<boolean>('delete_dream_analysis_version', { analysisId });
+	},
+
+	// Earlier dreams like this one; `apply` saves the recurrence suggestion
+	async findSimilar(dreamId: number, limit?: number, apply = false): Promise<SimilarDreamsResult> {
+		return withLlmErrors(
+			invoke<SimilarDreamsResult>('find_similar_dreams', { dreamId, limit: limit ?? null, apply })
+		);
+	},
+
 	// Dream Creative Prompts
 	async generateCreativePrompts(request: GenerateCreativePromptsRequest): Promise<DreamCreativePrompts> {
-		return await invoke<DreamCreativePrompts>('generate_dream_creative_prompts', { request });
+		return withLlmErrors(invoke<DreamCreativePrompts>('generate_dream_creative_prompts', { request }));
 	},
 
 	async getCreativePrompts(dreamAnalysisId: number): Promise<DreamCreativePromptsData | null> {
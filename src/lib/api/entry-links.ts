@@ -0,0 +1,30 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { EntryLink, EntryRelation, LinkedEntry } from '$lib/types/entry-link';
+import type { EntryType } from '$lib/types/llm';
+
+export const entryLinksApi = {
+	// Reads source -> target, e.g. a bug 'caused_by' a dream
+	async link(
+		sourceType: EntryType,
+		sourceId: number,
+		targetType: EntryType,
+		targetId: number,
+		relation: EntryRelation
+	): Promise<EntryLink> {
+		return await invoke<EntryLink>('link_entries', {
+			sourceType,
+			sourceId,
+			targetType,
+			targetId,
+			relation
+		});
+	},
+
+	async unlink(linkId: number): Promise<boolean> {
+		return await invoke<boolean>('unlink_entries', { linkId });
+	},
+
+	async list(entryType: EntryType, entryId: number): Promise<LinkedEntry[]> {
+		return await invoke<LinkedEntry[]>('list_entry_links', { entryType, entryId });
+	}
+};
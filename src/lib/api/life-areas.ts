@@ -0,0 +1,17 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { CreateLifeAreaInput, LifeArea } from '$lib/types/life-area';
+
+export const lifeAreasApi = {
+	async list(): Promise<LifeArea[]> {
+		return await invoke<LifeArea[]>('list_life_areas');
+	},
+
+	async create(input: CreateLifeAreaInput): Promise<LifeArea> {
+		return await invoke<LifeArea>('create_life_area', { input });
+	},
+
+	// Built-in areas cannot be deleted; bugs in a deleted area keep no area
+	async delete(id: number): Promise<boolean> {
+		return await invoke<boolean>('delete_life_area', { id });
+	}
+};
@@ -1,7 +1,43 @@
-import type { GenerateTitleRequest, GenerateTitleResponse } from '$lib/types/llm';
+import type {
+	AvailableModel,
+	ConnectionTestResult,
+	GenerateTitleRequest,
+	GenerateTitleResponse,
+	LLMConfig,
+	LlmError,
+	LlmErrorKind,
+	LLMProvider,
+	LlmSettingsView,
+	LlmUsageSummary,
+	EntryType,
+	AskJournalResponse,
+	SemanticSearchResult,
+	UsageGrouping
+} from '$lib/types/llm';
 import { llmSettings } from '$lib/stores/llm-settings.svelte';
 import { invoke } from '@tauri-apps/api/core';
 
+export class LlmRequestError extends Error {
+	kind: LlmErrorKind;
+	retryAfterSecs: number | null;
+
+	constructor(error: LlmError) {
+		super(error.message);
+		this.name = 'LlmRequestError';
+		this.kind = error.kind;
+		this.retryAfterSecs = error.retry_after_secs;
+	}
+}
+
+export function isLlmError(error: unknown): error is LlmError {
+	return (
+		typeof error === 'object' &&
+		error !== null &&
+		'kind' in error &&
+		'message' in error
+	);
+}
+
 export const llmApi = {
 	async generateTitle(request: GenerateTitleRequest): Promise<GenerateTitleResponse> {
 		const config = llmSettings.config;
@@ -17,8 +53,7 @@ export const llmApi = {
 		try {
 			const response = await invoke<GenerateTitleResponse>('generate_dream_title', {
 				request: {
-					content: request.content,
-					config: config
+					content: request.content
 				}
 			});
 			return response;
@@ -26,6 +61,9 @@ export const llmApi = {
 			if (error instanceof Error) {
 				throw error;
 			}
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
 			if (typeof error === 'string') {
 				throw new Error(error);
 			}
@@ -47,8 +85,7 @@ export const llmApi = {
 		try {
 			const response = await invoke<{ optimized: string }>('optimize_dream_description', {
 				request: {
-					content: request.content,
-					config: config
+					content: request.content
 				}
 			});
 			return response;
@@ -56,6 +93,9 @@ export const llmApi = {
 			if (error instanceof Error) {
 				throw error;
 			}
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
 			if (typeof error === 'string') {
 				throw new Error(error);
 			}
@@ -77,8 +117,7 @@ export const llmApi = {
 		try {
 			const response = await invoke<{ optimized: string }>('optimize_bug_description', {
 				request: {
-					content: request.content,
-					config: config
+					content: request.content
 				}
 			});
 			return response;
@@ -86,6 +125,9 @@ export const llmApi = {
 			if (error instanceof Error) {
 				throw error;
 			}
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
 			if (typeof error === 'string') {
 				throw new Error(error);
 			}
@@ -107,8 +149,7 @@ export const llmApi = {
 		try {
 			const response = await invoke<GenerateTitleResponse>('generate_bug_title', {
 				request: {
-					content: request.content,
-					config: config
+					content: request.content
 				}
 			});
 			return response;
@@ -116,6 +157,9 @@ export const llmApi = {
 			if (error instanceof Error) {
 				throw error;
 			}
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
 			if (typeof error === 'string') {
 				throw new Error(error);
 			}
@@ -137,8 +181,7 @@ export const llmApi = {
 					card_question: request.cardQuestion,
 					card_meaning: request.cardMeaning,
 					life_area: request.lifeArea,
-					selected_cards: request.selectedCards || [],
-					config: config
+					selected_cards: request.selectedCards || []
 				}
 			});
 			return response;
@@ -146,6 +189,9 @@ export const llmApi = {
 			if (error instanceof Error) {
 				throw error;
 			}
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
 			if (typeof error === 'string') {
 				throw new Error(error);
 			}
@@ -165,8 +211,7 @@ export const llmApi = {
 				request: {
 					cards: request.cards,
 					life_area: request.lifeArea,
-					selected_cards: request.selectedCards || [],
-					config: config
+					selected_cards: request.selectedCards || []
 				}
 			});
 			return response;
@@ -174,6 +219,9 @@ export const llmApi = {
 			if (error instanceof Error) {
 				throw error;
 			}
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
 			if (typeof error === 'string') {
 				throw new Error(error);
 			}
@@ -181,7 +229,7 @@ export const llmApi = {
 		}
 	},
 
-	chatWithHistory: async (request: { userMessage: string; messages: Array<{ role: 'user' | 'assistant'; content: string }>; cardName: string; cardQuestion: string; cardMeaning: string; cardInsights?: string; lifeArea: string; userName?: string; zodiacSign?: string; mbtiType?: string; selectedCards?: Array<{ id: number; name: string; card_question: string; core_meaning: string; emoji: string; commentary: string }> }) => {
+	chatWithHistory: async (request: { userMessage: string; messages: Array<{ role: 'user' | 'assistant'; content: string }>; cardName: string; cardQuestion: string; cardMeaning: string; cardInsights?: string; lifeArea: string; selectedCards?: Array<{ id: number; name: string; card_question: string; core_meaning: string; emoji: string; commentary: string }> }) => {
 		const config = llmSettings.config;
 
 		if (config.provider === 'disabled') {
@@ -198,11 +246,7 @@ export const llmApi = {
 					card_meaning: request.cardMeaning,
 					card_insights: request.cardInsights || '',
 					life_area: request.lifeArea,
-					user_name: request.userName || '',
-					zodiac_sign: request.zodiacSign || null,
-					mbti_type: request.mbtiType || null,
-					selected_cards: request.selectedCards || [],
-					config: config
+					selected_cards: request.selectedCards || []
 				}
 			});
 			return response;
@@ -210,10 +254,98 @@ export const llmApi = {
 			if (error instanceof Error) {
 				throw error;
 			}
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
 			if (typeof error === 'string') {
 				throw new Error(error);
 			}
 			throw new Error('Failed to get chat response');
 		}
+	},
+
+	// Backend-owned settings. LLM commands fall back to these when no config is sent.
+	getConfig: async (): Promise<LlmSettingsView> => {
+		return await invoke<LlmSettingsView>('get_llm_config');
+	},
+
+	// Empty API keys leave the stored key unchanged
+	setConfig: async (config: LLMConfig): Promise<LlmSettingsView> => {
+		return await invoke<LlmSettingsView>('set_llm_config', { config });
+	},
+
+	deleteApiKey: async (provider: LLMProvider): Promise<boolean> => {
+		return await invoke<boolean>('delete_llm_api_key', { provider });
+	},
+
+	// `from`/`to` are ISO timestamps; both optional
+	getUsage: async (groupBy: UsageGrouping, from?: string, to?: string): Promise<LlmUsageSummary[]> => {
+		return await invoke<LlmUsageSummary[]>('get_llm_usage', { groupBy, from: from ?? null, to: to ?? null });
+	},
+
+	// Returns the number of cached responses removed
+	clearCache: async (): Promise<number> => {
+		return await invoke<number>('clear_llm_cache');
+	},
+
+	// Settings page helpers: take the config being edited rather than the saved one
+	testConnection: async (config?: LLMConfig): Promise<ConnectionTestResult> => {
+		return await invoke<ConnectionTestResult>('test_llm_connection', { config });
+	},
+
+	listAvailableModels: async (config?: LLMConfig): Promise<AvailableModel[]> => {
+		try {
+			return await invoke<AvailableModel[]>('list_available_models', { config });
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Failed to list models');
+		}
+	},
+
+	// Ranks dreams, mind dumps and bugs; embeds new or edited entries first
+	semanticSearch: async (
+		query: string,
+		entryTypes?: EntryType[],
+		limit?: number
+	): Promise<SemanticSearchResult[]> => {
+		try {
+			return await invoke<SemanticSearchResult[]>('semantic_search', {
+				query,
+				entryTypes: entryTypes ?? null,
+				limit: limit ?? null
+			});
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Semantic search failed');
+		}
+	},
+
+	// Answers from the most relevant dreams, mind dumps and resolved bugs
+	askJournal: async (
+		question: string,
+		messages: Array<{ role: 'user' | 'assistant'; content: string }> = [],
+		limit?: number
+	): Promise<AskJournalResponse> => {
+		try {
+			return await invoke<AskJournalResponse>('ask_journal', {
+				question,
+				messages,
+				limit: limit ?? null
+			});
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Asking the journal failed');
+		}
+	},
+
+	// Returns the number of entries embedded
+	syncEmbeddings: async (): Promise<number> => {
+		return await invoke<number>('sync_embeddings');
 	}
 };
@@ -1,13 +1,20 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { MindDump, CreateMindDumpInput, UpdateMindDumpInput } from '$lib/types/mind-dump';
+import type {
+	MindDump,
+	CreateMindDumpInput,
+	UpdateMindDumpInput,
+	MindDumpAnalysisWithCards
+} from '$lib/types/mind-dump';
+import type { WithLinks } from '$lib/types/entry-link';
+import { isLlmError, LlmRequestError } from '$lib/api/llm';
 
 export const mindDumpApi = {
 	async create(input: CreateMindDumpInput): Promise<MindDump> {
 		return await invoke<MindDump>('create_mind_dump', { input });
 	},
 
-	async get(id: number): Promise<MindDump | null> {
-		return await invoke<MindDump | null>('get_mind_dump', { id });
+	async get(id: number): Promise<WithLinks<MindDump> | null> {
+		return await invoke<WithLinks<MindDump> | null>('get_mind_dump', { id });
 	},
 
 	async list(limit?: number, offset?: number): Promise<MindDump[]> {
@@ -24,5 +31,25 @@ export const mindDumpApi = {
 
 	async search(query: string): Promise<MindDump[]> {
 		return await invoke<MindDump[]>('search_mind_dumps', { query });
+	},
+
+	// Replaces any earlier analysis of the mind dump
+	async analyze(mindDumpId: number): Promise<MindDumpAnalysisWithCards> {
+		try {
+			return await invoke<MindDumpAnalysisWithCards>('analyze_mind_dump', {
+				mindDumpId
+			});
+		} catch (error) {
+			if (isLlmError(error)) {
+				throw new LlmRequestError(error);
+			}
+			throw new Error(typeof error === 'string' ? error : 'Failed to analyze mind dump');
+		}
+	},
+
+	async getAnalysis(mindDumpId: number): Promise<MindDumpAnalysisWithCards | null> {
+		return await invoke<MindDumpAnalysisWithCards | null>('get_mind_dump_analysis_with_cards', {
+			mindDumpId
+		});
 	}
 };
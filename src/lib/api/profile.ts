@@ -0,0 +1,13 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { UserProfile } from '$lib/types/user';
+
+export const profileApi = {
+	async get(): Promise<UserProfile> {
+		return await invoke<UserProfile>('get_user_profile');
+	},
+
+	// Returns the profile as stored: trimmed, with blank fields cleared
+	async update(profile: UserProfile): Promise<UserProfile> {
+		return await invoke<UserProfile>('update_user_profile', { profile });
+	}
+};
@@ -1,52 +1,77 @@
 import { browser } from '$app/environment';
-import type { LLMConfig } from '$lib/types/llm';
+import { invoke } from '@tauri-apps/api/core';
+import type { LLMConfig, LLMProvider, LlmSettingsView } from '$lib/types/llm';
 import { DEFAULT_LLM_CONFIG } from '$lib/types/llm';
 
-const STORAGE_KEY = 'limnl-llm-config';
-
-function loadConfig(): LLMConfig {
-	if (!browser) return DEFAULT_LLM_CONFIG;
+// Where the config, API keys included, was kept before the backend owned it
+const LEGACY_STORAGE_KEY = 'limnl-llm-config';
 
+function takeLegacyConfig(): LLMConfig | null {
 	try {
-		const stored = localStorage.getItem(STORAGE_KEY);
-		if (stored) {
-			return { ...DEFAULT_LLM_CONFIG, ...JSON.parse(stored) };
-		}
+		const stored = localStorage.getItem(LEGACY_STORAGE_KEY);
+		return stored ? { ...DEFAULT_LLM_CONFIG, ...JSON.parse(stored) } : null;
 	} catch (error) {
-		console.error('Failed to load LLM config:', error);
+		console.error('Failed to read legacy LLM config:', error);
+		return null;
 	}
-
-	return DEFAULT_LLM_CONFIG;
 }
 
-function saveConfig(config: LLMConfig): void {
-	if (!browser) return;
+// Called directly rather than through llmApi, which imports this store
+const getConfig = () => invoke<LlmSettingsView>('get_llm_config');
+const setConfig = (config: LLMConfig) => invoke<LlmSettingsView>('set_llm_config', { config });
+const deleteApiKey = (provider: LLMProvider) => invoke<boolean>('delete_llm_api_key', { provider });
 
-	try {
-		localStorage.setItem(STORAGE_KEY, JSON.stringify(config));
-	} catch (error) {
-		console.error('Failed to save LLM config:', error);
+// Mirrors the backend settings (`get_llm_config`). LLM commands read the stored
+// config themselves, so nothing here is sent with requests. API keys are only
+// held while the user types them; the backend keeps them in the secret store.
+class LLMSettingsStore {
+	config = $state<LLMConfig>(DEFAULT_LLM_CONFIG);
+	hasOpenaiApiKey = $state(false);
+	hasAnthropicApiKey = $state(false);
+	secretsBackend = $state<LlmSettingsView['secretsBackend'] | null>(null);
+	ready: Promise<void>;
+
+	constructor() {
+		this.ready = browser ? this.load() : Promise.resolve();
 	}
-}
 
-class LLMSettingsStore {
-	config = $state<LLMConfig>(loadConfig());
+	private async load() {
+		try {
+			// One-time migration: move a localStorage config and its keys to the backend
+			const legacy = takeLegacyConfig();
+			this.apply(legacy ? await setConfig(legacy) : await getConfig());
+			if (legacy) {
+				localStorage.removeItem(LEGACY_STORAGE_KEY);
+			}
+		} catch (error) {
+			console.error('Failed to load LLM config:', error);
+		}
+	}
+
+	private apply(view: LlmSettingsView) {
+		const { hasOpenaiApiKey, hasAnthropicApiKey, secretsBackend, ...config } = view;
+		this.config = config;
+		this.hasOpenaiApiKey = hasOpenaiApiKey;
+		this.hasAnthropicApiKey = hasAnthropicApiKey;
+		this.secretsBackend = secretsBackend;
+	}
 
-	updateConfig(updates: Partial<LLMConfig>) {
-		this.config = { ...this.config, ...updates };
-		saveConfig(this.config);
+	// Empty API keys keep the stored ones
+	async updateConfig(updates: Partial<LLMConfig>) {
+		this.apply(await setConfig({ ...this.config, ...updates }));
 	}
 
-	resetConfig() {
-		this.config = DEFAULT_LLM_CONFIG;
-		saveConfig(this.config);
+	async resetConfig() {
+		await deleteApiKey('openai');
+		await deleteApiKey('anthropic');
+		this.apply(await setConfig(DEFAULT_LLM_CONFIG));
 	}
 
 	get isConfigured(): boolean {
 		if (this.config.provider === 'disabled') return false;
-		if (this.config.provider === 'ollama') return true; // Assumes local Ollama is running
-		if (this.config.provider === 'openai') return this.config.openaiApiKey.length > 0;
-		if (this.config.provider === 'anthropic') return this.config.anthropicApiKey.length > 0;
+		if (this.config.provider === 'ollama' || this.config.provider === 'mock') return true; // Assumes local Ollama is running
+		if (this.config.provider === 'openai') return this.hasOpenaiApiKey;
+		if (this.config.provider === 'anthropic') return this.hasAnthropicApiKey;
 		return false;
 	}
 }
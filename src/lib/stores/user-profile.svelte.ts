@@ -1,6 +1,7 @@
 import { browser } from '$app/environment';
 import type { UserProfile } from '$lib/types/user';
 import { DEFAULT_USER_PROFILE } from '$lib/types/user';
+import { profileApi } from '$lib/api/profile';
 
 const STORAGE_KEY = 'limnl-user-profile';
 
@@ -29,17 +30,51 @@ function saveProfile(profile: UserProfile): void {
 	}
 }
 
+// The backend owns the profile (it is added to prompts there); localStorage
+// keeps a copy so the settings page renders before the backend answers
 class UserProfileStore {
 	profile = $state<UserProfile>(loadProfile());
 
+	constructor() {
+		if (browser) {
+			this.syncFromBackend();
+		}
+	}
+
+	// A profile saved before the backend stored it is uploaded once
+	private async syncFromBackend() {
+		try {
+			const stored = await profileApi.get();
+			if (!stored.name && !stored.zodiacSign && !stored.mbtiType && this.profile.name) {
+				await this.persist(this.profile);
+			} else {
+				this.profile = { ...DEFAULT_USER_PROFILE, ...stored };
+				saveProfile(this.profile);
+			}
+		} catch (error) {
+			console.error('Failed to load user profile:', error);
+		}
+	}
+
+	private async persist(profile: UserProfile) {
+		try {
+			this.profile = await profileApi.update(profile);
+			saveProfile(this.profile);
+		} catch (error) {
+			console.error('Failed to save user profile:', error);
+		}
+	}
+
 	updateProfile(updates: Partial<UserProfile>) {
 		this.profile = { ...this.profile, ...updates };
 		saveProfile(this.profile);
+		this.persist(this.profile);
 	}
 
 	resetProfile() {
 		this.profile = DEFAULT_USER_PROFILE;
 		saveProfile(this.profile);
+		this.persist(this.profile);
 	}
 }
 
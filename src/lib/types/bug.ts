@@ -1,3 +1,7 @@
+import type { GenerationProvenance } from '$lib/types/dream';
+import type { DbCard } from '$lib/types/card';
+import type { EntryLink } from '$lib/types/entry-link';
+
 export interface Bug {
 	id?: number;
 	title: string;
@@ -6,6 +10,7 @@ export interface Bug {
 	cards_drawn?: string; // JSON string of card IDs
 	conversation_history?: string; // JSON string of conversation messages
 	notes?: string; // JSON string of notes array
+	life_area_id?: number;
 	created_at: string;
 	updated_at: string;
 	resolved_at?: string;
@@ -17,6 +22,7 @@ export interface CreateBugInput {
 	cards_drawn?: string;
 	conversation_history?: string;
 	notes?: string;
+	life_area_id?: number;
 }
 
 export interface UpdateBugInput {
@@ -27,6 +33,8 @@ export interface UpdateBugInput {
 	cards_drawn?: string;
 	conversation_history?: string;
 	notes?: string;
+	life_area_id?: number;
+	clear_life_area?: boolean; // remove the bug from its area; ignored when life_area_id is set
 	resolved_at?: string;
 }
 
@@ -40,3 +48,36 @@ export interface BugNote {
 	content: string;
 	timestamp: string;
 }
+
+export interface BugFromEntry {
+	bug: Bug;
+	link: EntryLink;
+	cards: DbCard[];
+}
+
+export interface BugRetrospective extends GenerationProvenance {
+	id?: number;
+	bug_id: number;
+	root_pattern: string;
+	blocker_pattern: string; // id from the discovery chat's blocker pattern taxonomy
+	what_helped: string[];
+	follow_ups: string[];
+	created_at: string;
+	updated_at: string;
+}
+
+// A blocker pattern detected in a bug; `pattern` is a discovery chat taxonomy id
+export interface BugPattern extends GenerationProvenance {
+	bug_id: number;
+	pattern: string;
+	confidence: number; // 0.0 to 1.0
+	evidence?: string;
+	created_at: string;
+}
+
+export interface PatternFrequency {
+	pattern: string;
+	group?: string;
+	bug_count: number;
+	average_confidence: number;
+}
@@ -0,0 +1,31 @@
+import type { GenerationProvenance } from '$lib/types/dream';
+
+export type DigestPeriod = 'week' | 'month' | 'custom';
+
+export interface TagCount {
+	name: string;
+	count: number;
+}
+
+export interface DigestStats {
+	dream_count: number;
+	lucid_dream_count: number;
+	recurring_dream_count: number;
+	average_sleep_quality: number | null;
+	mind_dump_count: number;
+	mind_dump_words: number;
+	bugs_created: number;
+	bugs_resolved: number;
+	top_emotions: TagCount[];
+	top_cards: TagCount[];
+}
+
+export interface Digest extends GenerationProvenance {
+	id?: number;
+	period_type: DigestPeriod;
+	start_date: string;
+	end_date: string; // Exclusive
+	stats: DigestStats;
+	summary: string;
+	created_at: string;
+}
@@ -55,13 +55,24 @@ export const RECURRENCE_TIME_PERIODS: { value: RecurrenceTimePeriod; label: stri
 	{ value: 'last_year', label: 'Last year' }
 ];
 
+// Provider, model and prompt version a stored generation was produced with.
+// All null for generations made before this was recorded.
+export interface GenerationProvenance {
+	provider: string | null;
+	model: string | null;
+	prompt_version: string | null;
+	temperature: number | null;
+}
+
 // Dream Analysis types
-export interface DreamAnalysis {
+export interface DreamAnalysis extends GenerationProvenance {
 	id?: number;
 	dream_id: number;
 	themes_patterns: string;
 	emotional_analysis: string;
 	narrative_summary: string;
+	is_current: boolean;
+	is_pinned: boolean;
 	created_at: string;
 	updated_at: string;
 }
@@ -84,19 +95,11 @@ export interface GenerateDreamAnalysisRequest {
 	dream_title: string;
 	dream_content: string;
 	sleep_quality?: number;
-	config: {
-		provider: 'disabled' | 'ollama' | 'openai' | 'anthropic';
-		ollamaUrl: string;
-		ollamaModel: string;
-		openaiApiKey: string;
-		openaiModel: string;
-		anthropicApiKey: string;
-		anthropicModel: string;
-	};
+	with_history?: boolean; // Include metadata and related earlier dreams with their cards
 }
 
 // Dream Creative Prompts types
-export interface DreamCreativePrompts {
+export interface DreamCreativePrompts extends GenerationProvenance {
 	id?: number;
 	dream_analysis_id: number;
 	image_prompts: string; // JSON string
@@ -117,13 +120,27 @@ export interface GenerateCreativePromptsRequest {
 	themes_patterns: string;
 	emotional_analysis: string;
 	narrative_summary: string;
-	config: {
-		provider: 'disabled' | 'ollama' | 'openai' | 'anthropic';
-		ollamaUrl: string;
-		ollamaModel: string;
-		openaiApiKey: string;
-		openaiModel: string;
-		anthropicApiKey: string;
-		anthropicModel: string;
-	};
+}
+
+export interface SimilarDream {
+	dream_id: number;
+	title: string;
+	date_occurred: string;
+	score: number; // 0-1 blend of content, card and tag similarity
+	content_score: number;
+	shared_cards: string[];
+	shared_tags: string[];
+}
+
+export interface RecurrenceSuggestion {
+	is_recurring: boolean;
+	last_occurrence_period: RecurrenceTimePeriod;
+	matched_dream_id: number;
+}
+
+export interface SimilarDreamsResult {
+	dream_id: number;
+	similar: SimilarDream[];
+	suggestion: RecurrenceSuggestion | null;
+	applied: boolean; // Whether the suggestion was saved to the dream
 }
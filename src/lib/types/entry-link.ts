@@ -0,0 +1,27 @@
+import type { EntryType } from '$lib/types/llm';
+
+export type EntryRelation = 'relates_to' | 'caused_by' | 'follow_up' | 'promoted_from';
+
+export interface EntryLink {
+	id?: number;
+	source_type: EntryType;
+	source_id: number;
+	target_type: EntryType;
+	target_id: number;
+	relation: EntryRelation;
+	created_at: string;
+}
+
+// A link seen from one entry; 'incoming' links are backlinks
+export interface LinkedEntry {
+	link_id: number;
+	relation: EntryRelation;
+	direction: 'outgoing' | 'incoming';
+	entry_type: EntryType;
+	entry_id: number;
+	title: string;
+	created_at: string;
+}
+
+// get_dream, get_bug and get_mind_dump responses
+export type WithLinks<T> = T & { links: LinkedEntry[] };
@@ -0,0 +1,16 @@
+// Built-in areas match the keys of a card's `life_area_insights`
+export type BuiltinLifeAreaKey = 'creative' | 'work' | 'life' | 'relationship';
+
+export interface LifeArea {
+	id?: number;
+	key: string;
+	name: string;
+	description?: string;
+	is_builtin: boolean;
+	created_at: string;
+}
+
+export interface CreateLifeAreaInput {
+	name: string;
+	description?: string;
+}
@@ -1,4 +1,4 @@
-export type LLMProvider = 'disabled' | 'ollama' | 'openai' | 'anthropic';
+export type LLMProvider = 'disabled' | 'ollama' | 'openai' | 'anthropic' | 'mock';
 
 export interface LLMConfig {
 	provider: LLMProvider;
@@ -8,6 +8,143 @@ export interface LLMConfig {
 	openaiModel: string; // Default: gpt4-mini
 	anthropicApiKey: string;
 	anthropicModel: string; // Default: claude-haiku
+	openaiBaseUrl?: string; // Default: https://api.openai.com
+	anthropicBaseUrl?: string; // Default: https://api.anthropic.com
+	mockResponses?: Record<string, string>; // Mock provider replies by operation, or 'default'
+	network?: NetworkSettings; // Backend defaults apply when omitted
+	cache?: CacheSettings; // Off unless enabled
+	fixtures?: FixtureSettings; // Off unless set
+	embeddings?: EmbeddingSettings; // Off unless enabled
+}
+
+// Opt-in cache for titles and card commentary
+export interface CacheSettings {
+	enabled: boolean;
+	ttlHours: number;
+	maxEntries: number;
+}
+
+// Vectors for semantic search, computed by Ollama or OpenAI (Anthropic has no embeddings API)
+export interface EmbeddingSettings {
+	enabled: boolean;
+	ollamaModel: string; // Default: nomic-embed-text
+	openaiModel: string; // Default: text-embedding-3-small
+}
+
+// Record provider calls to redacted JSON fixtures, or replay them without a network
+export type FixtureMode = 'off' | 'record' | 'replay';
+
+export interface FixtureSettings {
+	mode: FixtureMode;
+	dir: string; // Empty: llm_fixtures in the app data directory
+}
+
+export interface ProviderTimeouts {
+	connectSecs: number;
+	readSecs: number;
+}
+
+export interface OperationTimeouts {
+	titleSecs: number;
+	descriptionSecs: number;
+	cardCommentarySecs: number;
+	chatSecs: number;
+	dreamAnalysisSecs: number;
+	creativePromptsSecs: number;
+	connectionTestSecs: number;
+	embeddingSecs: number;
+	digestSecs: number;
+	mindDumpAnalysisSecs: number;
+	bugRetrospectiveSecs: number;
+	patternClassificationSecs: number;
+}
+
+export interface NetworkSettings {
+	proxyUrl?: string | null;
+	caCertPath?: string | null;
+	ollama?: ProviderTimeouts;
+	openai?: ProviderTimeouts;
+	anthropic?: ProviderTimeouts;
+	operationTimeouts?: OperationTimeouts;
+}
+
+// Error returned by LLM commands. `kind` lets the UI react differently to a bad key,
+// a rate limit or Ollama not running.
+export type LlmErrorKind =
+	| 'disabled'
+	| 'auth'
+	| 'rate_limited'
+	| 'model_not_found'
+	| 'connection_refused'
+	| 'timeout'
+	| 'network'
+	| 'api'
+	| 'invalid_response'
+	| 'cancelled'
+	| 'other';
+
+export interface LlmError {
+	kind: LlmErrorKind;
+	message: string;
+	retry_after_secs: number | null;
+}
+
+// Stored LLM settings from `get_llm_config`. API keys are never returned;
+// the flags say whether one is saved in the keyring or encrypted secrets file.
+export interface LlmSettingsView extends LLMConfig {
+	hasOpenaiApiKey: boolean;
+	hasAnthropicApiKey: boolean;
+	secretsBackend: 'keyring' | 'encrypted_file';
+}
+
+export type UsageGrouping = 'day' | 'model' | 'operation';
+
+// Token totals for one day, model or operation. Cost is null when a model has no known price.
+export interface LlmUsageSummary {
+	key: string;
+	calls: number;
+	prompt_tokens: number;
+	completion_tokens: number;
+	estimated_cost_usd: number | null;
+}
+
+export interface ConnectionTestResult {
+	ok: boolean;
+	provider: LLMProvider;
+	model: string | null;
+	latency_ms: number;
+	error: LlmError | null;
+}
+
+export interface AvailableModel {
+	id: string;
+	display_name: string | null;
+}
+
+export type EntryType = 'dream' | 'mind_dump' | 'bug';
+
+export interface SemanticSearchResult {
+	entity_type: EntryType;
+	entity_id: number;
+	title: string;
+	snippet: string;
+	date: string;
+	score: number; // 0-1 blend of the two scores below
+	semantic_score: number | null; // null when embeddings are off
+	keyword_score: number;
+}
+
+export interface JournalSource {
+	entity_type: EntryType;
+	entity_id: number;
+	title: string;
+	date: string;
+	cited: boolean; // Referenced in the answer as [entity_type:entity_id]
+}
+
+export interface AskJournalResponse {
+	answer: string;
+	sources: JournalSource[]; // Cited entries first
 }
 
 export interface GenerateTitleRequest {
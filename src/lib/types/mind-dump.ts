@@ -1,3 +1,5 @@
+import type { GenerationProvenance } from '$lib/types/dream';
+
 export interface MindDump {
 	id?: number;
 	title?: string;
@@ -19,3 +21,26 @@ export interface UpdateMindDumpInput {
 	content?: string;
 	word_count?: number;
 }
+
+export interface MindDumpAnalysis extends GenerationProvenance {
+	id?: number;
+	mind_dump_id: number;
+	summary: string;
+	worries: string[];
+	action_items: string[];
+	created_at: string;
+	updated_at: string;
+}
+
+export interface MindDumpAnalysisCard {
+	mind_dump_analysis_id: number;
+	card_id: number;
+	card_name: string;
+	relevance_note?: string;
+	created_at: string;
+}
+
+export interface MindDumpAnalysisWithCards {
+	analysis: MindDumpAnalysis;
+	cards: MindDumpAnalysisCard[];
+}
@@ -30,10 +30,25 @@ export type MBTIType =
 	| 'enfj'
 	| 'entj';
 
+// Features whose prompts can include the profile; see `llm/profile.rs`
+export type PersonalizedFeature =
+	| 'card_commentary'
+	| 'discovery_chat'
+	| 'dream_analysis'
+	| 'mind_dump_analysis'
+	| 'journal_chat'
+	| 'digest'
+	| 'bug_retrospective';
+
+// Stored by the backend and added to every personalized prompt
 export interface UserProfile {
 	name: string;
+	pronouns?: string;
 	zodiacSign?: ZodiacSign;
 	mbtiType?: MBTIType;
+	preferredTone?: string;
+	language?: string;
+	personalizationOptOut?: PersonalizedFeature[];
 }
 
 export const ZODIAC_SIGNS: { value: ZodiacSign; label: string }[] = [
@@ -70,8 +85,19 @@ export const MBTI_TYPES: { value: MBTIType; label: string }[] = [
 	{ value: 'entj', label: '👑 ENTJ - The Commander' }
 ];
 
+export const PERSONALIZED_FEATURES: { value: PersonalizedFeature; label: string }[] = [
+	{ value: 'card_commentary', label: 'Card commentary' },
+	{ value: 'discovery_chat', label: 'Discovery chat' },
+	{ value: 'dream_analysis', label: 'Dream analysis' },
+	{ value: 'mind_dump_analysis', label: 'Mind dump analysis' },
+	{ value: 'journal_chat', label: 'Ask my journal' },
+	{ value: 'digest', label: 'Digests' },
+	{ value: 'bug_retrospective', label: 'Bug retrospectives' }
+];
+
 export const DEFAULT_USER_PROFILE: UserProfile = {
 	name: '',
 	zodiacSign: undefined,
-	mbtiType: undefined
+	mbtiType: undefined,
+	personalizationOptOut: []
 };